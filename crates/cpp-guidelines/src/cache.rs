@@ -4,60 +4,151 @@
 /// callers fall through to compute from source.
 ///
 /// Key schema (namespaced to avoid collisions):
-/// - `cpg:v1:guideline:{id}` — JSON-serialized Guideline (no TTL, invalidated on update)
-/// - `cpg:v1:search:{sha256(query)}` — JSON-serialized Vec<GuidelineResult> (TTL: 3600s)
-/// - `cpg:v1:categories` — JSON-serialized Vec<Category> (no TTL, invalidated on update)
-/// - `cpg:v1:category:{prefix}` — JSON-serialized Vec<String> of rule IDs (no TTL)
-/// - `cpg:v1:repo_commit` — Git commit hash string (no TTL)
+/// - `cpg:v3:guideline:{id}` — JSON-serialized Guideline (no TTL, invalidated on update)
+/// - `cpg:v3:search:{sha256(query|mode|metric|min_score)}` — JSON-serialized CachedSearch (TTL: `SEARCH_CACHE_TTL_SECS`, default 3600s)
+/// - `cpg:v3:categories` — JSON-serialized Vec<Category> (no TTL, invalidated on update)
+/// - `cpg:v3:category:{prefix}` — JSON-serialized Vec<String> of rule IDs (no TTL)
+/// - `cpg:v3:repo_commit` — Git commit hash string (no TTL)
+/// - `cpg:v3:content_hashes` — JSON map of guideline ID to indexed content hash (no TTL)
+use std::collections::HashMap;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use tracing::warn;
 
 use crate::model::{Category, Guideline, GuidelineResult};
-use mcp_common::redis::RedisCache;
+use mcp_common::mcp_api::{CacheMetrics, SearchMode};
+use mcp_common::redis::{HitCounter, RedisCache};
+use mcp_common::vectordb::DistanceType;
 
-const KEY_PREFIX: &str = "cpg:v1:";
-const SEARCH_TTL_SECS: u64 = 3600;
+const KEY_PREFIX: &str = "cpg:v3:";
+/// Default for `SEARCH_CACHE_TTL_SECS`.
+const DEFAULT_SEARCH_TTL_SECS: u64 = 3600;
+
+/// Search results for one query, cached at the limit the vector search ran with.
+///
+/// Results are ranked, so any smaller limit is served by truncation. When the search
+/// returned fewer rows than it asked for, the entry already holds every match and can
+/// serve any limit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedSearch {
+    pub fetched_limit: usize,
+    pub results: Vec<GuidelineResult>,
+}
+
+impl CachedSearch {
+    /// Returns `true` if this entry can answer a request for `limit` results.
+    pub fn serves(&self, limit: usize) -> bool {
+        limit <= self.fetched_limit || self.results.len() < self.fetched_limit
+    }
+}
 
 pub struct GuidelineCache {
     redis: RedisCache,
+    search_ttl_secs: u64,
+    guideline_lookups: HitCounter,
+    search_lookups: HitCounter,
+    category_lookups: HitCounter,
 }
 
 impl GuidelineCache {
+    /// Search results expire after `SEARCH_CACHE_TTL_SECS` seconds (default 3600; unset,
+    /// zero or unparsable values use the default).
     pub fn new(redis: RedisCache) -> Self {
-        Self { redis }
+        let search_ttl_secs = std::env::var("SEARCH_CACHE_TTL_SECS")
+            .ok()
+            .and_then(|s| s.trim().parse::<u64>().ok())
+            .filter(|&secs| secs > 0)
+            .unwrap_or(DEFAULT_SEARCH_TTL_SECS);
+        Self {
+            redis,
+            search_ttl_secs,
+            guideline_lookups: HitCounter::default(),
+            search_lookups: HitCounter::default(),
+            category_lookups: HitCounter::default(),
+        }
+    }
+
+    /// Seconds a cached search result lives.
+    pub fn search_ttl_secs(&self) -> u64 {
+        self.search_ttl_secs
+    }
+
+    /// Hit/miss counts of guideline, search and category lookups since startup.
+    pub fn metrics(&self) -> CacheMetrics {
+        CacheMetrics {
+            guideline: self.guideline_lookups.stats(),
+            search: self.search_lookups.stats(),
+            category: self.category_lookups.stats(),
+        }
+    }
+
+    /// Read and deserialize the JSON value at `key`.
+    async fn get_json<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let json = self.redis.get(key).await?;
+        parse_json(key, &json)
+    }
+
+    /// Returns `true` if Redis is reachable.
+    pub async fn is_available(&self) -> bool {
+        self.redis.is_available().await
     }
 
     // --- Guideline ---
 
     pub async fn get_guideline(&self, id: &str) -> Option<Guideline> {
         let key = format!("{KEY_PREFIX}guideline:{id}");
-        let json = self.redis.get(&key).await?;
-        serde_json::from_str(&json)
-            .inspect_err(|e| warn!(error = %e, key, "cache deserialization failed"))
-            .ok()
+        self.guideline_lookups.record(self.get_json(&key).await)
     }
 
-    pub async fn set_guideline(&self, guideline: &Guideline) {
-        let key = format!("{KEY_PREFIX}guideline:{}", guideline.id);
-        if let Ok(json) = serde_json::to_string(guideline) {
-            self.redis.set(&key, &json).await;
-        }
+    /// Batch form of [`Self::get_guideline`] using one `MGET`, in the order of `ids`.
+    pub async fn get_guidelines(&self, ids: &[String]) -> Option<Vec<Option<Guideline>>> {
+        let keys: Vec<String> = ids
+            .iter()
+            .map(|id| format!("{KEY_PREFIX}guideline:{id}"))
+            .collect();
+        let Some(values) = self.redis.mget(&keys).await else {
+            for _ in ids {
+                self.guideline_lookups.count(false);
+            }
+            return None;
+        };
+        let guidelines = keys
+            .iter()
+            .zip(values)
+            .map(|(key, json)| {
+                let guideline = json.and_then(|json| parse_json(key, &json));
+                self.guideline_lookups.record(guideline)
+            })
+            .collect();
+        Some(guidelines)
     }
 
     // --- Search results ---
 
-    pub async fn get_search_results(&self, query: &str, limit: usize) -> Option<Vec<GuidelineResult>> {
-        let key = search_key(query, limit);
-        let json = self.redis.get(&key).await?;
-        serde_json::from_str(&json)
-            .inspect_err(|e| warn!(error = %e, key, "cache deserialization failed"))
-            .ok()
+    pub async fn get_search_results(
+        &self,
+        query: &str,
+        mode: SearchMode,
+        distance_type: DistanceType,
+        min_score: Option<f32>,
+    ) -> Option<CachedSearch> {
+        let key = search_key(query, mode, distance_type, min_score);
+        self.search_lookups.record(self.get_json(&key).await)
     }
 
-    pub async fn set_search_results(&self, query: &str, limit: usize, results: &[GuidelineResult]) {
-        let key = search_key(query, limit);
-        if let Ok(json) = serde_json::to_string(results) {
-            self.redis.set_with_ttl(&key, &json, SEARCH_TTL_SECS).await;
+    pub async fn set_search_results(
+        &self,
+        query: &str,
+        mode: SearchMode,
+        distance_type: DistanceType,
+        min_score: Option<f32>,
+        cached: &CachedSearch,
+    ) {
+        let key = search_key(query, mode, distance_type, min_score);
+        if let Ok(json) = serde_json::to_string(cached) {
+            self.redis.set_with_ttl(&key, &json, self.search_ttl_secs).await;
         }
     }
 
@@ -65,32 +156,12 @@ impl GuidelineCache {
 
     pub async fn get_categories(&self) -> Option<Vec<Category>> {
         let key = format!("{KEY_PREFIX}categories");
-        let json = self.redis.get(&key).await?;
-        serde_json::from_str(&json)
-            .inspect_err(|e| warn!(error = %e, key, "cache deserialization failed"))
-            .ok()
-    }
-
-    pub async fn set_categories(&self, categories: &[Category]) {
-        let key = format!("{KEY_PREFIX}categories");
-        if let Ok(json) = serde_json::to_string(categories) {
-            self.redis.set(&key, &json).await;
-        }
+        self.category_lookups.record(self.get_json(&key).await)
     }
 
     pub async fn get_category_rule_ids(&self, prefix: &str) -> Option<Vec<String>> {
         let key = format!("{KEY_PREFIX}category:{prefix}");
-        let json = self.redis.get(&key).await?;
-        serde_json::from_str(&json)
-            .inspect_err(|e| warn!(error = %e, key, "cache deserialization failed"))
-            .ok()
-    }
-
-    pub async fn set_category_rule_ids(&self, prefix: &str, ids: &[String]) {
-        let key = format!("{KEY_PREFIX}category:{prefix}");
-        if let Ok(json) = serde_json::to_string(ids) {
-            self.redis.set(&key, &json).await;
-        }
+        self.category_lookups.record(self.get_json(&key).await)
     }
 
     // --- Repo commit ---
@@ -105,6 +176,49 @@ impl GuidelineCache {
         self.redis.set(&key, commit).await;
     }
 
+    // --- Content hashes ---
+
+    /// Per-guideline content hashes of the indexed rows, keyed by id.
+    pub async fn get_content_hashes(&self) -> Option<HashMap<String, String>> {
+        let key = format!("{KEY_PREFIX}content_hashes");
+        self.get_json(&key).await
+    }
+
+    pub async fn set_content_hashes(&self, hashes: &HashMap<String, String>) {
+        let key = format!("{KEY_PREFIX}content_hashes");
+        if let Ok(json) = serde_json::to_string(hashes) {
+            self.redis.set(&key, &json).await;
+        }
+    }
+
+    // --- Warmup ---
+
+    /// Populate every guideline, the category list (in the order given) and each
+    /// category's sorted rule ids with one `MSET`. Returns `true` if successful.
+    pub async fn warmup(&self, guidelines: &[Guideline], categories: &[Category]) -> bool {
+        let mut pairs = Vec::with_capacity(guidelines.len() + categories.len() + 1);
+        for g in guidelines {
+            if let Ok(json) = serde_json::to_string(g) {
+                pairs.push((format!("{KEY_PREFIX}guideline:{}", g.id), json));
+            }
+        }
+        if let Ok(json) = serde_json::to_string(categories) {
+            pairs.push((format!("{KEY_PREFIX}categories"), json));
+        }
+        for category in categories {
+            let mut ids: Vec<&str> = guidelines
+                .iter()
+                .filter(|g| g.category == category.prefix)
+                .map(|g| g.id.as_str())
+                .collect();
+            ids.sort();
+            if let Ok(json) = serde_json::to_string(&ids) {
+                pairs.push((format!("{KEY_PREFIX}category:{}", category.prefix), json));
+            }
+        }
+        self.redis.mset(&pairs).await
+    }
+
     // --- Invalidation ---
 
     /// Delete all cached data. Used when re-indexing after an update.
@@ -112,14 +226,95 @@ impl GuidelineCache {
     pub async fn invalidate_all(&self) {
         self.redis.delete_by_prefix(KEY_PREFIX).await;
     }
+
+    /// Delete cached search results only, leaving guideline and category entries in place.
+    /// Returns the number of entries removed, or `None` if Redis is unavailable.
+    pub async fn invalidate_search(&self) -> Option<usize> {
+        self.redis.delete_by_prefix(&format!("{KEY_PREFIX}search:")).await
+    }
+}
+
+fn parse_json<T: DeserializeOwned>(key: &str, json: &str) -> Option<T> {
+    serde_json::from_str(json)
+        .inspect_err(|e| warn!(error = %e, key, "cache deserialization failed"))
+        .ok()
 }
 
 /// Compute a deterministic cache key for a search query using SHA-256.
-fn search_key(query: &str, limit: usize) -> String {
+fn search_key(
+    query: &str,
+    mode: SearchMode,
+    distance_type: DistanceType,
+    min_score: Option<f32>,
+) -> String {
     let mut hasher = Sha256::new();
     hasher.update(query.as_bytes());
     hasher.update(b"|");
-    hasher.update(limit.to_string().as_bytes());
+    hasher.update(mode.as_str().as_bytes());
+    hasher.update(b"|");
+    hasher.update(distance_type.as_str().as_bytes());
+    hasher.update(b"|");
+    if let Some(min_score) = min_score {
+        hasher.update(min_score.to_string().as_bytes());
+    }
     let hash = hasher.finalize();
     format!("{KEY_PREFIX}search:{:x}", hash)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cached(fetched_limit: usize, count: usize) -> CachedSearch {
+        let results = (0..count)
+            .map(|i| GuidelineResult {
+                id: format!("P.{i}"),
+                title: String::new(),
+                category: "P".to_string(),
+                score: 1.0,
+                summary: String::new(),
+            })
+            .collect();
+        CachedSearch {
+            fetched_limit,
+            results,
+        }
+    }
+
+    #[test]
+    fn cached_search_serves_smaller_limits() {
+        let full = cached(50, 50);
+        assert!(full.serves(10));
+        assert!(full.serves(50));
+        assert!(!full.serves(51));
+    }
+
+    #[test]
+    fn under_filled_cached_search_serves_any_limit() {
+        let exhausted = cached(50, 7);
+        assert!(exhausted.serves(10));
+        assert!(exhausted.serves(500));
+    }
+
+    #[test]
+    fn search_key_ignores_limit_but_not_filters() {
+        assert_eq!(
+            search_key("raii", SearchMode::Semantic, DistanceType::Cosine, None),
+            search_key("raii", SearchMode::Semantic, DistanceType::Cosine, None)
+        );
+        assert_ne!(
+            search_key("raii", SearchMode::Semantic, DistanceType::Cosine, None),
+            search_key("raii", SearchMode::Semantic, DistanceType::L2, None)
+        );
+        assert_ne!(
+            search_key("raii", SearchMode::Semantic, DistanceType::Cosine, None),
+            search_key("raii", SearchMode::Semantic, DistanceType::Cosine, Some(0.5))
+        );
+        assert_ne!(
+            search_key("raii", SearchMode::Semantic, DistanceType::Cosine, None),
+            search_key("raii", SearchMode::Hybrid, DistanceType::Cosine, None)
+        );
+        assert!(search_key("raii", SearchMode::Semantic, DistanceType::Cosine, None)
+            .starts_with("cpg:v3:search:"));
+    }
+}
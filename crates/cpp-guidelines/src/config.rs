@@ -1,4 +1,11 @@
+use std::time::Duration;
+
 use crate::error::AppError;
+use crate::model::{CategoryOrder, EmbeddingSections};
+use mcp_common::tool_timeout::ToolTimeout;
+
+/// Default for `SEARCH_CACHE_CANONICAL_LIMIT`; matches the `search_guidelines` limit cap.
+const DEFAULT_CANONICAL_LIMIT: usize = 50;
 
 /// Application configuration loaded explicitly from environment variables.
 ///
@@ -12,6 +19,23 @@ pub struct Config {
     pub lancedb_path: String,
     /// Filesystem path to the cloned C++ Core Guidelines repository.
     pub repo_path: String,
+    /// Ordering for category listings (key-sorted unless document order is requested).
+    pub category_order: CategoryOrder,
+    /// Sections embedded after each guideline's title.
+    pub embedding_sections: EmbeddingSections,
+    /// Whether to fetch and hard-reset the repository to `origin`'s default branch before
+    /// each update check. Off by default, so sandboxed deployments never touch the network.
+    pub auto_pull: bool,
+    /// Interval between scheduled background update checks. `None` disables them.
+    pub refresh_interval: Option<Duration>,
+    /// Whether to repopulate the guideline and category caches at startup when no
+    /// re-index is needed.
+    pub cache_warmup: bool,
+    /// Minimum rows each search fetches, so one cached entry serves every smaller limit.
+    /// 0 fetches exactly the requested limit.
+    pub search_cache_canonical_limit: usize,
+    /// Deadline for each tool call (`update_guidelines` excepted).
+    pub tool_timeout: ToolTimeout,
 }
 
 impl Config {
@@ -23,6 +47,13 @@ impl Config {
     ///
     /// Optional:
     /// - `REDIS_URL`: Redis connection string (omit to disable caching)
+    /// - `CPP_GUIDELINES_CATEGORY_ORDER`: `key` (default) or `document`
+    /// - `CPP_EMBEDDING_SECTIONS`: sections to embed, default `reason,example`
+    /// - `GUIDELINES_AUTO_PULL`: `1` to pull upstream before checking for updates
+    /// - `GUIDELINES_REFRESH_SECS`: check for updates in the background this often
+    /// - `CACHE_WARMUP`: `1` to repopulate the guideline caches at startup
+    /// - `SEARCH_CACHE_CANONICAL_LIMIT`: minimum rows each search fetches, default 50
+    /// - `TOOL_TIMEOUT_SECS`: fail tool calls that run longer than this
     pub fn from_env() -> Result<Self, AppError> {
         let lancedb_path = std::env::var("LANCEDB_PATH").map_err(|_| {
             AppError::Config("LANCEDB_PATH environment variable is required".to_string())
@@ -46,10 +77,64 @@ impl Config {
 
         let redis_url = std::env::var("REDIS_URL").ok();
 
+        let category_order = match std::env::var("CPP_GUIDELINES_CATEGORY_ORDER") {
+            Ok(value) => CategoryOrder::parse(&value).ok_or_else(|| {
+                AppError::Config(format!(
+                    "CPP_GUIDELINES_CATEGORY_ORDER must be 'key' or 'document', got '{value}'"
+                ))
+            })?,
+            Err(_) => CategoryOrder::default(),
+        };
+
+        let embedding_sections = match std::env::var("CPP_EMBEDDING_SECTIONS") {
+            Ok(value) if !value.trim().is_empty() => EmbeddingSections::parse(&value)
+                .ok_or_else(|| {
+                    AppError::Config(format!(
+                        "CPP_EMBEDDING_SECTIONS must list reason, example and/or enforcement \
+                         (or be 'none'), got '{value}'"
+                    ))
+                })?,
+            _ => EmbeddingSections::default(),
+        };
+
+        let auto_pull = env_flag("GUIDELINES_AUTO_PULL")?;
+        let cache_warmup = env_flag("CACHE_WARMUP")?;
+
+        let refresh_interval = match std::env::var("GUIDELINES_REFRESH_SECS") {
+            Ok(value) => match value.trim().parse::<u64>() {
+                Ok(0) => None,
+                Ok(secs) => Some(Duration::from_secs(secs)),
+                Err(_) => {
+                    return Err(AppError::Config(format!(
+                        "GUIDELINES_REFRESH_SECS must be a whole number of seconds, got '{value}'"
+                    )));
+                }
+            },
+            Err(_) => None,
+        };
+
+        let search_cache_canonical_limit = match std::env::var("SEARCH_CACHE_CANONICAL_LIMIT") {
+            Ok(value) => value.trim().parse::<usize>().map_err(|_| {
+                AppError::Config(format!(
+                    "SEARCH_CACHE_CANONICAL_LIMIT must be a whole number, got '{value}'"
+                ))
+            })?,
+            Err(_) => DEFAULT_CANONICAL_LIMIT,
+        };
+
+        let tool_timeout = ToolTimeout::from_env().map_err(AppError::Config)?;
+
         Ok(Self {
             redis_url,
             lancedb_path,
             repo_path,
+            category_order,
+            embedding_sections,
+            auto_pull,
+            refresh_interval,
+            cache_warmup,
+            search_cache_canonical_limit,
+            tool_timeout,
         })
     }
 
@@ -58,3 +143,12 @@ impl Config {
         std::path::Path::new(&self.repo_path).join("CppCoreGuidelines.md")
     }
 }
+
+/// Read a `1`/`0` (or `true`/`false`) flag; unset or empty means off.
+fn env_flag(name: &str) -> Result<bool, AppError> {
+    match std::env::var(name).as_deref().map(str::trim) {
+        Ok("1" | "true") => Ok(true),
+        Ok("" | "0" | "false") | Err(_) => Ok(false),
+        Ok(value) => Err(AppError::Config(format!("{name} must be '1' or '0', got '{value}'"))),
+    }
+}
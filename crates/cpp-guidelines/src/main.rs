@@ -9,13 +9,15 @@ mod update;
 
 use std::sync::Arc;
 
+use mcp_common::shutdown::shutdown_signal;
 use rmcp::{ServiceExt, transport::stdio};
 use rmcp::transport::streamable_http_server::{
-    StreamableHttpService, session::local::LocalSessionManager,
+    StreamableHttpServerConfig, StreamableHttpService, session::local::LocalSessionManager,
 };
 use tokio::net::TcpListener;
 use tracing::info;
 use tracing_subscriber::EnvFilter;
+use tracing_subscriber::fmt::format::FmtSpan;
 
 use cache::GuidelineCache;
 use config::Config;
@@ -31,6 +33,7 @@ async fn main() -> anyhow::Result<()> {
         )
         .with_writer(std::io::stderr)
         .with_ansi(false)
+        .with_span_events(FmtSpan::CLOSE)
         .init();
 
     info!("starting cpp-guidelines MCP server");
@@ -51,11 +54,15 @@ async fn main() -> anyhow::Result<()> {
     } else {
         info!("redis unavailable, running without cache");
     }
-    let cache = Arc::new(GuidelineCache::new(redis_cache));
+    let cache = Arc::new(GuidelineCache::new(redis_cache.clone()));
 
     // 3. Initialize embedding model
     info!("initializing embedding model (may download on first run)");
-    let embedder = Arc::new(mcp_common::embedding::Embedder::new().await?);
+    let embedder = Arc::new(
+        mcp_common::embedding::Embedder::new()
+            .await?
+            .with_query_cache(redis_cache),
+    );
     info!("embedding model ready");
 
     // 4. Connect to LanceDB
@@ -70,9 +77,9 @@ async fn main() -> anyhow::Result<()> {
         Arc::clone(&cache),
     );
 
-    let (guidelines, categories) = if update_service.needs_update().await? {
+    let (guidelines, categories) = if update_service.needs_update(None).await? {
         info!("indexing guidelines (first run or content changed)");
-        let (guidelines, categories, commit) = update_service.full_reindex().await?;
+        let (guidelines, categories, commit) = update_service.full_reindex(None).await?;
         info!(
             commit = %commit,
             guidelines = guidelines.len(),
@@ -90,10 +97,18 @@ async fn main() -> anyhow::Result<()> {
             categories = categories.len(),
             "loaded guidelines from source"
         );
+        if config.cache_warmup {
+            let mut category_list: Vec<_> = categories.values().cloned().collect();
+            config.category_order.sort(&mut category_list);
+            if cache.warmup(&guidelines, &category_list).await {
+                info!(guidelines = guidelines.len(), "guideline caches warmed up");
+            }
+        }
         (guidelines, categories)
     };
 
-    // 6. Build MCP server and serve on stdio
+    // 6. Build MCP server and serve on HTTP/SSE (MCP_LISTEN_ADDR) or stdio
+    let refresh_interval = config.refresh_interval;
     let server = CppGuidelinesServer::new(
         guidelines,
         categories,
@@ -103,24 +118,53 @@ async fn main() -> anyhow::Result<()> {
         config,
     );
 
+    // Re-index in the background every GUIDELINES_REFRESH_SECS, if set
+    let (stop_refresh, stop) = tokio::sync::oneshot::channel();
+    let refresh = refresh_interval.map(|period| {
+        info!(refresh_secs = period.as_secs(), "scheduled updates enabled");
+        let server = server.clone();
+        tokio::spawn(async move { server.refresh_periodically(period, stop).await })
+    });
+
     if let Ok(addr) = std::env::var("MCP_LISTEN_ADDR") {
+        // Cancelling the service's token on shutdown ends open SSE streams; otherwise a
+        // connected client would keep the graceful shutdown from ever finishing.
+        let config = StreamableHttpServerConfig::default();
+        let cancel = config.cancellation_token.clone();
         let server_for_factory = server.clone();
         let http_service = StreamableHttpService::new(
             move || Ok(server_for_factory.clone()),
             LocalSessionManager::default().into(),
-            Default::default(),
+            config,
         );
         let router = axum::Router::new().fallback_service(http_service);
         let listener = TcpListener::bind(&addr).await?;
         info!(listen_addr = %addr, "MCP server ready, serving HTTP/SSE");
-        axum::serve(listener, router).await?;
+        axum::serve(listener, router)
+            .with_graceful_shutdown(async move {
+                shutdown_signal().await;
+                cancel.cancel();
+            })
+            .await?;
     } else {
         info!("MCP server ready, serving on stdio");
-        let service = server.serve(stdio()).await.inspect_err(|e| {
+        let service = server.clone().serve(stdio()).await.inspect_err(|e| {
             tracing::error!(error = %e, "MCP server error");
         })?;
+        let cancel = service.cancellation_token();
+        tokio::spawn(async move {
+            shutdown_signal().await;
+            cancel.cancel();
+        });
         service.waiting().await?;
-        info!("MCP server shut down");
     }
+
+    // A re-index interrupted mid-write would leave a half-built LanceDB table.
+    drop(stop_refresh);
+    if let Some(refresh) = refresh {
+        let _ = refresh.await;
+    }
+    server.wait_for_reindex().await;
+    info!("MCP server shut down");
     Ok(())
 }
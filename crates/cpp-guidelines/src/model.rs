@@ -15,6 +15,15 @@ pub struct Guideline {
     pub sections: Vec<GuidelineSection>,
     /// Full original markdown text of the rule
     pub raw_markdown: String,
+    /// IDs of other rules this one links to or mentions, in order of first appearance
+    #[serde(default)]
+    pub related: Vec<String>,
+    /// Code blocks from the rule's example sections, classified by heading suffix
+    #[serde(default)]
+    pub examples: Vec<CodeExample>,
+    /// How far tools can check the rule, from its Enforcement section
+    #[serde(default)]
+    pub enforcement: EnforcementLevel,
 }
 
 /// A sub-section within a guideline (e.g., "Reason", "Example", "Enforcement").
@@ -26,6 +35,39 @@ pub struct GuidelineSection {
     pub content: String,
 }
 
+/// Whether an example shows compliant code, per its `##### Example, good/bad` heading.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExampleKind {
+    Good,
+    Bad,
+    /// Plain `##### Example` with no verdict.
+    Neutral,
+}
+
+/// How mechanically checkable a rule is, judged from the wording of its `##### Enforcement`
+/// section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EnforcementLevel {
+    /// The section describes checks a tool can make ("Flag ...", "Warn ...").
+    Enforceable,
+    /// Checks exist but are heuristic, or cover only some cases of a hard rule.
+    Partially,
+    /// The section says the rule is hard or not enforceable, or describes no check.
+    Hard,
+    /// The rule has no Enforcement section.
+    #[default]
+    Unknown,
+}
+
+/// A single code block from an example section.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CodeExample {
+    pub kind: ExampleKind,
+    pub code: String,
+}
+
 /// A search result returned from vector similarity search.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GuidelineResult {
@@ -35,7 +77,7 @@ pub struct GuidelineResult {
     pub title: String,
     /// Category prefix
     pub category: String,
-    /// Similarity score (lower distance = more similar in LanceDB)
+    /// Similarity score in [0, 1] (higher is more similar)
     pub score: f32,
     /// Summary text (first portion of the rule content)
     pub summary: String,
@@ -50,4 +92,117 @@ pub struct Category {
     pub name: String,
     /// Number of rules in this category
     pub rule_count: usize,
+    /// Position of the category in the source document (0 = first chapter)
+    #[serde(default)]
+    pub order: usize,
+}
+
+/// How category listings are ordered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CategoryOrder {
+    /// Alphabetical by category prefix (deterministic default).
+    #[default]
+    Key,
+    /// The order chapters appear in CppCoreGuidelines.md (P, I, F, C, ...).
+    Document,
+}
+
+impl CategoryOrder {
+    /// Parse a `CPP_GUIDELINES_CATEGORY_ORDER` value (`key` or `document`).
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "key" => Some(Self::Key),
+            "document" => Some(Self::Document),
+            _ => None,
+        }
+    }
+
+    /// Sort categories in place according to this ordering.
+    pub fn sort(self, categories: &mut [Category]) {
+        match self {
+            Self::Key => categories.sort_by(|a, b| a.prefix.cmp(&b.prefix)),
+            Self::Document => categories
+                .sort_by(|a, b| a.order.cmp(&b.order).then_with(|| a.prefix.cmp(&b.prefix))),
+        }
+    }
+}
+
+/// Which sections follow the title in a guideline's embedding text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EmbeddingSections {
+    /// The `Reason` section.
+    pub reason: bool,
+    /// The first `Example` section, which is often code rather than prose.
+    pub example: bool,
+    /// The `Enforcement` section.
+    pub enforcement: bool,
+}
+
+impl Default for EmbeddingSections {
+    /// Title, Reason and the first Example.
+    fn default() -> Self {
+        Self {
+            reason: true,
+            example: true,
+            enforcement: false,
+        }
+    }
+}
+
+impl EmbeddingSections {
+    /// Parse a `CPP_EMBEDDING_SECTIONS` value: a comma-separated list of `reason`,
+    /// `example` and `enforcement`, or `none` for the title alone.
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut sections = Self {
+            reason: false,
+            example: false,
+            enforcement: false,
+        };
+        if value.trim().eq_ignore_ascii_case("none") {
+            return Some(sections);
+        }
+        for name in value.split(',') {
+            match name.trim().to_ascii_lowercase().as_str() {
+                "reason" => sections.reason = true,
+                "example" => sections.example = true,
+                "enforcement" => sections.enforcement = true,
+                _ => return None,
+            }
+        }
+        Some(sections)
+    }
+}
+
+impl std::fmt::Display for EmbeddingSections {
+    /// The canonical `CPP_EMBEDDING_SECTIONS` spelling, e.g. `reason,example` or `none`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names: Vec<&str> = [
+            (self.reason, "reason"),
+            (self.example, "example"),
+            (self.enforcement, "enforcement"),
+        ]
+        .into_iter()
+        .filter_map(|(on, name)| on.then_some(name))
+        .collect();
+        if names.is_empty() {
+            f.write_str("none")
+        } else {
+            f.write_str(&names.join(","))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn embedding_sections_display_round_trips_through_parse() {
+        for value in ["none", "reason", "reason,example", "example,enforcement"] {
+            let sections = EmbeddingSections::parse(value).unwrap();
+            assert_eq!(sections.to_string(), value);
+        }
+        let sections = EmbeddingSections::parse(" Enforcement , REASON ").unwrap();
+        assert_eq!(sections.to_string(), "reason,enforcement");
+    }
 }
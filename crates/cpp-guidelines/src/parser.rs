@@ -5,14 +5,20 @@
 /// - Rule headers: `### <a name="ANCHOR"></a>RULE_ID: Title`
 /// - Sub-sections within rules: `##### Heading`
 /// - Rule ends at next `###`, `##`, or `#` header, or EOF
+/// - Examples: `##### Example`, `##### Example, good`, `##### Example, bad` sections
+///   holding fenced or indented code blocks
+/// - Cross-references: `[text](#anchor)` links and bare rule IDs such as `C.20`
 ///
 /// Parser approach: line-by-line state machine with regex for header detection.
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use regex::Regex;
 use tracing::warn;
 
-use crate::model::{Category, Guideline, GuidelineSection};
+use crate::model::{
+    Category, CodeExample, EmbeddingSections, EnforcementLevel, ExampleKind, Guideline,
+    GuidelineSection,
+};
 
 /// Parse the CppCoreGuidelines.md content into a list of guidelines and a category map.
 ///
@@ -23,21 +29,24 @@ use crate::model::{Category, Guideline, GuidelineSection};
 /// Malformed rules are skipped with a warning log; the parser never panics.
 pub fn parse_guidelines(content: &str) -> (Vec<Guideline>, HashMap<String, Category>) {
     let rule_header_re =
-        Regex::new(r#"^### <a name="([^"]+)">\s*</a>\s*(.+)$"#).expect("valid regex");
+        Regex::new(r#"^###\s+<a name="([^"]+)">\s*</a>\s*(.+)$"#).expect("valid regex");
     let category_header_re =
-        Regex::new(r#"^# <a name="[^"]+">\s*</a>\s*(\S+):\s+(.+)$"#).expect("valid regex");
-    let section_header_re = Regex::new(r"^##### (.+)$").expect("valid regex");
-    let any_heading_re = Regex::new(r"^#{1,3} ").expect("valid regex");
+        Regex::new(r#"^#\s+<a name="[^"]+">\s*</a>\s*(\S+):\s+(.+)$"#).expect("valid regex");
+    let section_header_re = Regex::new(r"^#####\s+(.+)$").expect("valid regex");
+    let any_heading_re = Regex::new(r"^#{1,3}\s").expect("valid regex");
 
     let lines: Vec<&str> = content.lines().collect();
     let mut guidelines: Vec<Guideline> = Vec::new();
     let mut category_names: HashMap<String, String> = HashMap::new();
+    let mut category_order: HashMap<String, usize> = HashMap::new();
 
-    // First pass: extract category names from `# <a name=...` headers
+    // First pass: extract category names (and their document order) from `# <a name=...` headers
     for line in &lines {
         if let Some(caps) = category_header_re.captures(line) {
             let prefix = caps[1].to_string();
-            let name = caps[2].to_string();
+            let name = normalize_whitespace(&caps[2]);
+            let next = category_order.len();
+            category_order.entry(prefix.clone()).or_insert(next);
             category_names.insert(prefix, name);
         }
     }
@@ -49,20 +58,20 @@ pub fn parse_guidelines(content: &str) -> (Vec<Guideline>, HashMap<String, Categ
 
         // Check for rule header: ### <a name="ANCHOR"></a>...
         if let Some(caps) = rule_header_re.captures(line) {
-            let anchor = caps[1].to_string();
+            let anchor = caps[1].trim().to_string();
             let rest = caps[2].to_string();
 
             // Parse RULE_ID: Title from the rest
             // The rule_id is everything before the first `: `, title is after.
             // Some titles contain `:` so we only split on the first occurrence.
             let (rule_id, title) = match rest.find(": ") {
-                Some(pos) => (rest[..pos].trim().to_string(), rest[pos + 2..].trim().to_string()),
+                Some(pos) => (rest[..pos].trim().to_string(), normalize_whitespace(&rest[pos + 2..])),
                 None => {
                     // Some rules may use just `:` without space, or have no title
                     match rest.find(':') {
                         Some(pos) => (
                             rest[..pos].trim().to_string(),
-                            rest[pos + 1..].trim().to_string(),
+                            normalize_whitespace(&rest[pos + 1..]),
                         ),
                         None => {
                             warn!(
@@ -124,7 +133,7 @@ pub fn parse_guidelines(content: &str) -> (Vec<Guideline>, HashMap<String, Categ
                             });
                         }
                     }
-                    current_section_heading = Some(caps[1].to_string());
+                    current_section_heading = Some(normalize_whitespace(&caps[1]));
                     current_section_lines.clear();
                 } else {
                     current_section_lines.push(current_line);
@@ -146,6 +155,13 @@ pub fn parse_guidelines(content: &str) -> (Vec<Guideline>, HashMap<String, Categ
 
             // Build raw markdown from all lines of this rule
             let raw_markdown = lines[rule_start..i].join("\n");
+            let examples = extract_examples(&sections);
+            let enforcement = sections
+                .iter()
+                .find(|section| section.heading == "Enforcement")
+                .map_or(EnforcementLevel::Unknown, |section| {
+                    enforcement_level(&section.content)
+                });
 
             guidelines.push(Guideline {
                 id: rule_id,
@@ -154,16 +170,24 @@ pub fn parse_guidelines(content: &str) -> (Vec<Guideline>, HashMap<String, Categ
                 category,
                 sections,
                 raw_markdown,
+                related: Vec::new(),
+                examples,
+                enforcement,
             });
         } else {
             i += 1;
         }
     }
 
+    link_related(&mut guidelines);
+
     // Build category map
     let mut category_rule_counts: HashMap<String, usize> = HashMap::new();
     for g in &guidelines {
         *category_rule_counts.entry(g.category.clone()).or_insert(0) += 1;
+        // Categories without a chapter header sort after all headed ones, by first rule.
+        let next = category_order.len();
+        category_order.entry(g.category.clone()).or_insert(next);
     }
 
     let mut categories: HashMap<String, Category> = HashMap::new();
@@ -172,12 +196,14 @@ pub fn parse_guidelines(content: &str) -> (Vec<Guideline>, HashMap<String, Categ
             .get(&prefix)
             .cloned()
             .unwrap_or_else(|| prefix.clone());
+        let order = category_order[&prefix];
         categories.insert(
             prefix.clone(),
             Category {
                 prefix,
                 name,
                 rule_count: count,
+                order,
             },
         );
     }
@@ -185,6 +211,170 @@ pub fn parse_guidelines(content: &str) -> (Vec<Guideline>, HashMap<String, Categ
     (guidelines, categories)
 }
 
+/// Collect the code blocks of every example section, tagged good/bad/neutral from the
+/// heading suffix (`Example, bad`). Sections without code yield nothing.
+fn extract_examples(sections: &[GuidelineSection]) -> Vec<CodeExample> {
+    sections
+        .iter()
+        .filter_map(|section| Some((example_kind(&section.heading)?, section)))
+        .flat_map(|(kind, section)| {
+            code_blocks(&section.content)
+                .into_iter()
+                .map(move |code| CodeExample { kind, code })
+        })
+        .collect()
+}
+
+/// Classify an example heading; `None` for non-example sections.
+///
+/// Examples:
+/// - "Example" → Neutral
+/// - "Example, bad" → Bad
+/// - "Examples; good" → Good
+fn example_kind(heading: &str) -> Option<ExampleKind> {
+    let heading = heading.to_ascii_lowercase();
+    let rest = heading.strip_prefix("example")?;
+    let rest = rest.strip_prefix('s').unwrap_or(rest);
+    let verdict = rest.trim_start_matches([',', ';', ':', ' ']).split_whitespace().next();
+    match verdict {
+        Some("bad") => Some(ExampleKind::Bad),
+        Some("good") => Some(ExampleKind::Good),
+        _ if rest.is_empty() || rest.starts_with([',', ';', ':', ' ']) => {
+            Some(ExampleKind::Neutral)
+        }
+        _ => None,
+    }
+}
+
+/// Classify an Enforcement section by its wording.
+///
+/// Examples:
+/// - "Flag naked `new`." → Enforceable
+/// - "Very hard in general. Flag obvious cases." → Partially
+/// - "(Not enforceable) This is a philosophical guideline." → Hard
+/// - "???" → Hard
+fn enforcement_level(content: &str) -> EnforcementLevel {
+    const CHECK_WORDS: &[&str] = &[
+        "flag", "flags", "warn", "warns", "diagnose", "diagnostic", "detect", "enforce",
+    ];
+    const HARD_WORDS: &[&str] = &["hard", "difficult", "impossible", "infeasible", "unenforceable"];
+    const PARTIAL_WORDS: &[&str] = &["heuristic", "heuristics", "partial", "partially", "obvious"];
+
+    let text = content.to_ascii_lowercase();
+    let words: Vec<&str> = text
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect();
+    let has = |list: &[&str]| words.iter().any(|w| list.contains(w));
+
+    let hard = has(HARD_WORDS)
+        || ["not enforceable", "not possible", "not checkable", "???"]
+            .iter()
+            .any(|phrase| text.contains(phrase));
+    let check = has(CHECK_WORDS) || text.contains("look for");
+    match (hard, check) {
+        (true, true) => EnforcementLevel::Partially,
+        (false, true) if has(PARTIAL_WORDS) => EnforcementLevel::Partially,
+        (false, true) => EnforcementLevel::Enforceable,
+        (_, false) => EnforcementLevel::Hard,
+    }
+}
+
+/// Extract fenced (```) and indented (4 spaces or a tab) code blocks from markdown.
+/// Indented blocks may contain blank lines; trailing blank lines are dropped.
+fn code_blocks(content: &str) -> Vec<String> {
+    let mut blocks = Vec::new();
+    let mut current: Vec<&str> = Vec::new();
+    let mut in_fence = false;
+    let mut prev_blank = true;
+
+    let mut flush = |current: &mut Vec<&str>| {
+        while current.last().is_some_and(|l| l.trim().is_empty()) {
+            current.pop();
+        }
+        if !current.is_empty() {
+            blocks.push(current.join("\n"));
+        }
+        current.clear();
+    };
+
+    for line in content.lines() {
+        if line.trim_start().starts_with("```") {
+            if in_fence {
+                flush(&mut current);
+            }
+            in_fence = !in_fence;
+            prev_blank = false;
+            continue;
+        }
+        if in_fence {
+            current.push(line);
+            continue;
+        }
+
+        let indented = line.strip_prefix("    ").or_else(|| line.strip_prefix('\t'));
+        match indented {
+            // An indented block starts after a blank line and continues until text resumes.
+            Some(code) if !current.is_empty() || prev_blank => current.push(code),
+            _ if line.trim().is_empty() => {
+                if !current.is_empty() {
+                    current.push("");
+                }
+            }
+            _ => flush(&mut current),
+        }
+        prev_blank = line.trim().is_empty();
+    }
+    flush(&mut current);
+    blocks
+}
+
+/// Fill each guideline's `related` list from the rules its markdown links to by anchor or
+/// mentions by ID. Only references that resolve to a parsed rule are kept; duplicates and
+/// self-references are dropped.
+fn link_related(guidelines: &mut [Guideline]) {
+    let link_re = Regex::new(r"\]\(#([^)\s]+)\)").expect("valid regex");
+    let id_re = Regex::new(r"\b[A-Z][A-Za-z]*(?:\.[a-z]+)*\.\d+\b").expect("valid regex");
+
+    let by_anchor: HashMap<String, String> = guidelines
+        .iter()
+        .map(|g| (g.anchor.to_lowercase(), g.id.clone()))
+        .collect();
+    let ids: HashSet<String> = guidelines.iter().map(|g| g.id.clone()).collect();
+
+    for guideline in guidelines.iter_mut() {
+        // Links and mentions interleave in the text; order by position so `related`
+        // follows the order a reader meets them.
+        let mut refs: Vec<(usize, &str)> = link_re
+            .captures_iter(&guideline.raw_markdown)
+            .filter_map(|caps| {
+                let anchor = caps.get(1)?;
+                let id = by_anchor.get(&anchor.as_str().to_lowercase())?;
+                Some((anchor.start(), id.as_str()))
+            })
+            .chain(
+                id_re
+                    .find_iter(&guideline.raw_markdown)
+                    .filter_map(|m| ids.get(m.as_str()).map(|id| (m.start(), id.as_str()))),
+            )
+            .collect();
+        refs.sort_by_key(|&(pos, _)| pos);
+
+        let mut seen: HashSet<&str> = HashSet::new();
+        guideline.related = refs
+            .into_iter()
+            .map(|(_, id)| id)
+            .filter(|id| *id != guideline.id && seen.insert(id))
+            .map(str::to_string)
+            .collect();
+    }
+}
+
+/// Trim and collapse runs of whitespace (tabs, repeated spaces) into single spaces.
+fn normalize_whitespace(s: &str) -> String {
+    s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
 /// Extract the top-level category prefix from a rule ID.
 ///
 /// Examples:
@@ -210,32 +400,35 @@ fn join_section_lines(lines: &[&str]) -> String {
 
 /// Compose the embedding text for a guideline.
 ///
-/// Concatenates the title, reason section, and first example section for
-/// maximum semantic relevance. Truncated to a reasonable length.
-pub fn compose_embedding_text(guideline: &Guideline) -> String {
-    let mut parts = vec![guideline.title.clone()];
+/// Concatenates the title with whichever of the reason section, first example section and
+/// enforcement section `sections` selects. Examples are mostly code, which adds noise for
+/// prose queries. Truncated to a reasonable length.
+pub fn compose_embedding_text(guideline: &Guideline, sections: EmbeddingSections) -> String {
+    let first = |matches: fn(&str) -> bool| {
+        guideline
+            .sections
+            .iter()
+            .find(|section| matches(&section.heading))
+            .map(|section| section.content.clone())
+    };
 
-    // Add the Reason section if present
-    for section in &guideline.sections {
-        if section.heading == "Reason" {
-            parts.push(section.content.clone());
-            break;
-        }
+    let mut parts = vec![guideline.title.clone()];
+    if sections.reason {
+        parts.extend(first(|heading| heading == "Reason"));
     }
-
-    // Add the first Example section if present
-    for section in &guideline.sections {
-        if section.heading.starts_with("Example") {
-            parts.push(section.content.clone());
-            break;
-        }
+    if sections.example {
+        parts.extend(first(|heading| heading.starts_with("Example")));
+    }
+    if sections.enforcement {
+        parts.extend(first(|heading| heading == "Enforcement"));
     }
 
     let text = parts.join(". ");
 
-    // Truncate to ~2000 chars to keep embedding input reasonable
-    if text.len() > 2000 {
-        text[..2000].to_string()
+    // Truncate to 2000 chars to keep embedding input reasonable. Count chars, not bytes:
+    // the prose is full of multi-byte punctuation (’, —) and slicing inside one panics.
+    if text.chars().count() > 2000 {
+        text.chars().take(2000).collect()
     } else {
         text
     }
@@ -244,6 +437,7 @@ pub fn compose_embedding_text(guideline: &Guideline) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::model::CategoryOrder;
 
     #[test]
     fn test_extract_category() {
@@ -325,6 +519,172 @@ Non-const global variables are bad.
         );
     }
 
+    #[test]
+    fn test_parse_normalizes_header_whitespace() {
+        let content = "#\t<a name=\"s-philosophy\"></a>P:  Philosophy \t\n\n\
+###\t<a name=\" rp-direct \"></a>  P.1 :\tExpress  ideas\tdirectly in code  \n\n\
+#####\tReason  \n\nCompilers don't read comments.\n";
+        let (guidelines, categories) = parse_guidelines(content);
+        assert_eq!(guidelines.len(), 1);
+
+        let g = &guidelines[0];
+        assert_eq!(g.id, "P.1");
+        assert_eq!(g.anchor, "rp-direct");
+        assert_eq!(g.title, "Express ideas directly in code");
+        assert_eq!(g.category, "P");
+        assert_eq!(g.sections.len(), 1);
+        assert_eq!(g.sections[0].heading, "Reason");
+        assert_eq!(categories["P"].name, "Philosophy");
+    }
+
+    #[test]
+    fn test_category_document_order() {
+        let content = r#"# <a name="s-philosophy"></a>P: Philosophy
+
+### <a name="rp-direct"></a>P.1: Express ideas directly in code
+
+# <a name="s-interfaces"></a>I: Interfaces
+
+### <a name="ri-explicit"></a>I.1: Make interfaces explicit
+
+# <a name="s-class"></a>C: Classes and class hierarchies
+
+### <a name="rc-org"></a>C.1: Organize related data into structures
+"#;
+        let (_, categories) = parse_guidelines(content);
+        assert_eq!(categories["P"].order, 0);
+        assert_eq!(categories["I"].order, 1);
+        assert_eq!(categories["C"].order, 2);
+
+        let mut list: Vec<Category> = categories.values().cloned().collect();
+        CategoryOrder::Document.sort(&mut list);
+        let prefixes: Vec<&str> = list.iter().map(|c| c.prefix.as_str()).collect();
+        assert_eq!(prefixes, ["P", "I", "C"]);
+
+        CategoryOrder::Key.sort(&mut list);
+        let prefixes: Vec<&str> = list.iter().map(|c| c.prefix.as_str()).collect();
+        assert_eq!(prefixes, ["C", "I", "P"]);
+    }
+
+    #[test]
+    fn test_parse_related_rules() {
+        let content = r#"# <a name="s-class"></a>C: Classes
+
+### <a name="rc-zero"></a>C.20: If you can avoid defining default operations, do
+
+##### Reason
+
+See [the rule of five](#rc-five), C.21 and [C.21](#Rc-five). Unlike C.20, X.99 and e.g. 1.5.
+
+### <a name="rc-five"></a>C.21: Define or delete all copy, move, and destructor functions
+
+##### Note
+
+Compare [C.20](#rc-zero) and [this section](#s-class).
+"#;
+        let (guidelines, _) = parse_guidelines(content);
+        assert_eq!(guidelines[0].related, ["C.21"]);
+        assert_eq!(guidelines[1].related, ["C.20"]);
+    }
+
+    #[test]
+    fn test_enforcement_level() {
+        use EnforcementLevel::{Enforceable, Hard, Partially};
+        let cases = [
+            ("Flag naked `new` and naked `delete`.", Enforceable),
+            ("(Simple) Warn on any use of `goto`.", Enforceable),
+            ("Look for classes with nothing but pure virtual functions.", Enforceable),
+            ("Very hard in general. Flag obvious cases.", Partially),
+            ("Heuristic: flag functions longer than 20 lines.", Partially),
+            ("(Not enforceable) This is a philosophical guideline.", Hard),
+            ("Hard.", Hard),
+            ("???", Hard),
+            // "hard" only counts as a whole word
+            ("Flag hardware-specific casts.", Enforceable),
+        ];
+        for (content, expected) in cases {
+            assert_eq!(enforcement_level(content), expected, "{content}");
+        }
+    }
+
+    #[test]
+    fn test_parse_enforcement() {
+        let content = r#"### <a name="rr-newdelete"></a>R.11: Avoid calling `new` and `delete` explicitly
+
+##### Reason
+
+The pointer returned by `new` should belong to a resource handle.
+
+##### Enforcement
+
+(Simple) Warn on any explicit use of `new` and `delete`.
+
+### <a name="rp-direct"></a>P.1: Express ideas directly in code
+
+##### Reason
+
+Compilers don't read comments.
+"#;
+        let (guidelines, _) = parse_guidelines(content);
+        assert_eq!(guidelines[0].enforcement, EnforcementLevel::Enforceable);
+        assert_eq!(guidelines[1].enforcement, EnforcementLevel::Unknown);
+    }
+
+    #[test]
+    fn test_example_kind() {
+        assert_eq!(example_kind("Example"), Some(ExampleKind::Neutral));
+        assert_eq!(example_kind("Example, bad"), Some(ExampleKind::Bad));
+        assert_eq!(example_kind("Examples; good"), Some(ExampleKind::Good));
+        assert_eq!(example_kind("Example, bad (see C.21)"), Some(ExampleKind::Bad));
+        assert_eq!(example_kind("Example (don't)"), Some(ExampleKind::Neutral));
+        assert_eq!(example_kind("Exceptions"), None);
+        assert_eq!(example_kind("Reason"), None);
+    }
+
+    #[test]
+    fn test_parse_examples() {
+        let content = r#"### <a name="rp-direct"></a>P.1: Express ideas directly in code
+
+##### Reason
+
+    not an example
+
+##### Example, bad
+
+Some text:
+
+    int i = 0;
+
+    while (i < v.size()) {}
+Trailing text.
+
+##### Example, good
+
+```cpp
+auto p = find(begin(v), end(v), val);
+```
+
+##### Example
+
+Prose only.
+"#;
+        let (guidelines, _) = parse_guidelines(content);
+        let examples = &guidelines[0].examples;
+        assert_eq!(
+            examples,
+            &[
+                CodeExample {
+                    kind: ExampleKind::Bad,
+                    code: "int i = 0;\n\nwhile (i < v.size()) {}".to_string(),
+                },
+                CodeExample {
+                    kind: ExampleKind::Good,
+                    code: "auto p = find(begin(v), end(v), val);".to_string(),
+                },
+            ]
+        );
+    }
+
     #[test]
     fn test_compose_embedding_text() {
         let g = Guideline {
@@ -343,13 +703,174 @@ Non-const global variables are bad.
                 },
             ],
             raw_markdown: String::new(),
+            related: Vec::new(),
+            examples: Vec::new(),
+            enforcement: EnforcementLevel::Unknown,
         };
-        let text = compose_embedding_text(&g);
+        let text = compose_embedding_text(&g, EmbeddingSections::default());
         assert!(text.starts_with("Express ideas directly in code"));
         assert!(text.contains("Compilers don't read comments."));
         assert!(text.contains("class Date {};"));
     }
 
+    #[test]
+    fn test_compose_embedding_text_truncates_at_char_boundary() {
+        // "T" + ". " is 3 bytes, so the 2000-byte mark falls inside the first `’` (3 bytes).
+        let reason = format!("{}{}", "a".repeat(1996), "’—".repeat(10));
+        let g = Guideline {
+            id: "P.1".to_string(),
+            anchor: "rp-direct".to_string(),
+            title: "T".to_string(),
+            category: "P".to_string(),
+            sections: vec![GuidelineSection {
+                heading: "Reason".to_string(),
+                content: reason,
+            }],
+            raw_markdown: String::new(),
+            related: Vec::new(),
+            examples: Vec::new(),
+            enforcement: EnforcementLevel::Unknown,
+        };
+        let text = compose_embedding_text(&g, EmbeddingSections::default());
+        assert_eq!(text.chars().count(), 2000);
+        assert!(text.ends_with("a’"));
+    }
+
+    fn fixture(id: &str, title: &str, reason: &str, example: &str, enforcement: &str) -> Guideline {
+        let section = |heading: &str, content: &str| GuidelineSection {
+            heading: heading.to_string(),
+            content: content.to_string(),
+        };
+        Guideline {
+            id: id.to_string(),
+            anchor: String::new(),
+            title: title.to_string(),
+            category: extract_category(id),
+            sections: vec![
+                section("Reason", reason),
+                section("Example, bad", example),
+                section("Enforcement", enforcement),
+            ],
+            raw_markdown: String::new(),
+            related: Vec::new(),
+            examples: Vec::new(),
+            enforcement: EnforcementLevel::Unknown,
+        }
+    }
+
+    #[test]
+    fn test_compose_embedding_text_selects_sections() {
+        let g = fixture("CP.20", "Use RAII", "Avoids leaks.", "mtx.lock();", "Flag lock().");
+        let with = |value| compose_embedding_text(&g, EmbeddingSections::parse(value).unwrap());
+        assert_eq!(with("reason,example"), "Use RAII. Avoids leaks.. mtx.lock();");
+        assert_eq!(with("Enforcement, reason"), "Use RAII. Avoids leaks.. Flag lock().");
+        assert_eq!(with("none"), "Use RAII");
+        assert!(EmbeddingSections::parse("reason,notes").is_none());
+        assert_eq!(
+            EmbeddingSections::parse("reason,example"),
+            Some(EmbeddingSections::default())
+        );
+    }
+
+    /// Rank a small fixture set against prose queries with bag-of-words cosine similarity,
+    /// a stand-in for the embedding model: the code in Example sections shares no words
+    /// with such queries, so it only dilutes the match.
+    #[test]
+    fn test_embedding_sections_retrieval_on_fixtures() {
+        let guidelines = [
+            fixture(
+                "R.11",
+                "Avoid calling new and delete explicitly",
+                "The pointer returned by new should belong to a resource handle that can call \
+                 delete. If the pointer is left in a naked pointer, memory can leak.",
+                "void f(int n) { auto p = new X[n]; // ... delete[] p; }",
+                "Flag naked new and naked delete.",
+            ),
+            fixture(
+                "ES.20",
+                "Always initialize an object",
+                "Avoid used-before-set errors and their associated undefined behavior.",
+                "void use(int arg) { int i; string s; if (arg) i = arg; }",
+                "Flag every uninitialized variable. Don't flag variables of user-defined types \
+                 with default constructors.",
+            ),
+            fixture(
+                "F.16",
+                "For in parameters, pass cheaply-copied types by value and others by reference \
+                 to const",
+                "Both let the caller know that a function will not modify the argument, and both \
+                 allow initialization by rvalues.",
+                "void f1(const string& s); void f2(string s); void f3(int x); \
+                 void f4(const int& x);",
+                "Warn when a parameter being passed by value has a size greater than \
+                 2 * sizeof(void*).",
+            ),
+            fixture(
+                "Con.4",
+                "Use const to define objects with values that do not change after construction",
+                "Prevent surprises from unexpectedly changed object values.",
+                "void f() { const int x = 7; int y = 9; x = 9; y = 8; }",
+                "Look for variables that are never modified after initialization.",
+            ),
+            fixture(
+                "CP.20",
+                "Use RAII, never plain lock()/unlock()",
+                "Avoids nasty errors from forgetting to unlock a mutex on some path.",
+                "mutex mtx; void do_stuff() { mtx.lock(); mtx.unlock(); }",
+                "Flag calls of member lock() and unlock().",
+            ),
+        ];
+        let queries = [
+            ("memory leak from a naked pointer", "R.11"),
+            ("uninitialized variable with undefined behavior", "ES.20"),
+            ("forgetting to unlock a mutex", "CP.20"),
+            ("object value changed unexpectedly", "Con.4"),
+            ("pass cheaply copied parameters by value", "F.16"),
+        ];
+
+        fn words(text: &str) -> HashMap<String, f64> {
+            let mut counts = HashMap::new();
+            for word in text.split(|c: char| !c.is_ascii_alphanumeric()) {
+                if word.len() > 1 {
+                    *counts.entry(word.to_ascii_lowercase()).or_insert(0.0) += 1.0;
+                }
+            }
+            counts
+        }
+        fn cosine(a: &HashMap<String, f64>, b: &HashMap<String, f64>) -> f64 {
+            let dot: f64 = a.iter().map(|(w, x)| x * b.get(w).unwrap_or(&0.0)).sum();
+            let norm = |v: &HashMap<String, f64>| v.values().map(|x| x * x).sum::<f64>().sqrt();
+            dot / (norm(a) * norm(b))
+        }
+        // How far each query's expected rule scores above the best other rule.
+        let margins = |sections: &str| -> Vec<f64> {
+            let sections = EmbeddingSections::parse(sections).unwrap();
+            let docs: HashMap<&str, _> = guidelines
+                .iter()
+                .map(|g| (g.id.as_str(), words(&compose_embedding_text(g, sections))))
+                .collect();
+            queries
+                .iter()
+                .map(|(query, expected)| {
+                    let query = words(query);
+                    let best_other = docs
+                        .iter()
+                        .filter(|(id, _)| *id != expected)
+                        .map(|(_, doc)| cosine(&query, doc))
+                        .fold(f64::MIN, f64::max);
+                    cosine(&query, &docs[expected]) - best_other
+                })
+                .collect()
+        };
+
+        let with_example = margins("reason,example");
+        let without_example = margins("reason");
+        for (i, (query, _)) in queries.iter().enumerate() {
+            assert!(with_example[i] > 0.0 && without_example[i] > 0.0, "{query}");
+            assert!(without_example[i] > with_example[i], "{query}");
+        }
+    }
+
     /// Integration test: parse the real CppCoreGuidelines.md and verify structure.
     ///
     /// This test requires the data file to exist at the expected path (set via env var
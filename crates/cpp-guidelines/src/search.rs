@@ -2,23 +2,39 @@
 ///
 /// Embeds a query using the fastembed model, performs vector search in LanceDB,
 /// and formats results. Caches search results in Redis when available.
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use arrow_array::{Array, Float32Array, RecordBatch, StringArray};
 use tracing::{info, warn};
 
-use crate::cache::GuidelineCache;
+use crate::cache::{CachedSearch, GuidelineCache};
 use crate::model::GuidelineResult;
+use mcp_common::batch;
 use mcp_common::embedding::Embedder;
-use mcp_common::vectordb::VectorDb;
+use mcp_common::error::CommonError;
+use mcp_common::hybrid;
+use mcp_common::mcp_api::SearchMode;
+use mcp_common::summary;
+use mcp_common::vectordb::{DistanceType, VectorDb};
 
 const VECTOR_TABLE_NAME: &str = "guidelines";
-const MAX_SUMMARY_LEN: usize = 300;
+const DISTANCE_TYPE: DistanceType = DistanceType::Cosine;
+/// Columns a keyword search matches the query against.
+const KEYWORD_COLUMNS: [&str; 3] = ["id", "title", "text"];
+/// Upper bound on rows one keyword scan reads; far above any corpus size, so ranking sees
+/// every match.
+const MAX_KEYWORD_ROWS: usize = 2000;
 
 pub struct SearchEngine {
     embedder: Arc<Embedder>,
     vectordb: Arc<VectorDb>,
     cache: Arc<GuidelineCache>,
+    /// Searches fetch at least this many rows so one cached entry per query serves every
+    /// smaller limit (`SEARCH_CACHE_CANONICAL_LIMIT`); 0 fetches exactly the requested limit.
+    canonical_limit: usize,
+    /// Characters of text kept in each result's summary (`SEARCH_SUMMARY_LEN`).
+    summary_len: usize,
 }
 
 impl SearchEngine {
@@ -26,57 +42,278 @@ impl SearchEngine {
         embedder: Arc<Embedder>,
         vectordb: Arc<VectorDb>,
         cache: Arc<GuidelineCache>,
+        canonical_limit: usize,
     ) -> Self {
+        let summary_len = summary::summary_len_from_env();
+
         Self {
             embedder,
             vectordb,
             cache,
+            canonical_limit,
+            summary_len,
+        }
+    }
+
+    /// Search guidelines for the query, ranked according to `mode`.
+    ///
+    /// Returns up to `limit` results, highest score first. Keyword and hybrid rankings are
+    /// computed per call (see [`mcp_common::hybrid`] for how scores combine); hybrid reuses
+    /// the cached semantic ranking.
+    pub async fn search(
+        &self,
+        query: &str,
+        limit: usize,
+        min_score: Option<f32>,
+        mode: SearchMode,
+    ) -> Result<Vec<GuidelineResult>, crate::error::AppError> {
+        match mode {
+            SearchMode::Semantic => self.semantic_search(query, limit, min_score).await,
+            SearchMode::Keyword => self.keyword_search(query, limit, min_score).await,
+            SearchMode::Hybrid => self.hybrid_search(query, limit, min_score).await,
         }
     }
 
     /// Search guidelines by semantic similarity to the query.
     ///
     /// Returns up to `limit` results, ranked by similarity (lowest distance first).
-    /// Results are cached in Redis for subsequent identical queries.
-    pub async fn search(
+    /// Results are cached in Redis per query at the canonical limit, so later requests
+    /// for the same query with an equal or smaller limit are served from cache.
+    async fn semantic_search(
         &self,
         query: &str,
         limit: usize,
+        min_score: Option<f32>,
     ) -> Result<Vec<GuidelineResult>, crate::error::AppError> {
-        // Check cache first
-        if let Some(cached) = self.cache.get_search_results(query, limit).await {
-            info!(query, "search cache hit");
-            return Ok(cached);
+        if let Some(results) = self.cached_results(query, limit, min_score).await {
+            return Ok(results);
         }
 
-        // Embed the query
         let query_embedding = self.embedder.embed_query(query).await?;
+        self.search_embedding(query, &query_embedding, limit, min_score).await
+    }
 
-        // Vector search
+    /// Rank rows containing the query by keyword score alone. Needs no embedding.
+    async fn keyword_search(
+        &self,
+        query: &str,
+        limit: usize,
+        min_score: Option<f32>,
+    ) -> Result<Vec<GuidelineResult>, crate::error::AppError> {
         let batches = self
             .vectordb
-            .search(VECTOR_TABLE_NAME, &query_embedding, limit)
+            .keyword_search(VECTOR_TABLE_NAME, query, &KEYWORD_COLUMNS, MAX_KEYWORD_ROWS)
             .await?;
+        let mut results =
+            extract_results(&batches, min_score, self.summary_len, |id, title, text, _| {
+                hybrid::keyword_score(query, id, title, text)
+            });
+        results.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.id.cmp(&b.id)));
+        results.truncate(limit);
+        Ok(results)
+    }
 
-        // Extract results from record batches
-        let results = extract_search_results(&batches);
+    /// Merge the semantic and keyword rankings into one, de-duplicated by id.
+    async fn hybrid_search(
+        &self,
+        query: &str,
+        limit: usize,
+        min_score: Option<f32>,
+    ) -> Result<Vec<GuidelineResult>, crate::error::AppError> {
+        // Rank over the full canonical fetch; `min_score` applies to the blended score.
+        let fetch_limit = limit.max(self.canonical_limit);
+        let (semantic, keyword) = futures::try_join!(
+            self.semantic_search(query, fetch_limit, None),
+            self.keyword_search(query, fetch_limit, None),
+        )?;
 
-        // Cache the results (fire-and-forget, don't block on cache write)
-        self.cache.set_search_results(query, limit, &results).await;
+        let ranking = hybrid::merge_rankings(&id_scores(&semantic), &id_scores(&keyword));
+        let mut by_id: HashMap<String, GuidelineResult> = semantic
+            .into_iter()
+            .chain(keyword)
+            .map(|r| (r.id.clone(), r))
+            .collect();
+        Ok(ranking
+            .into_iter()
+            .filter(|(_, score)| !min_score.is_some_and(|min| *score < min))
+            .filter_map(|(id, score)| by_id.remove(&id).map(|r| GuidelineResult { score, ..r }))
+            .take(limit)
+            .collect())
+    }
 
-        Ok(results)
+    /// Search several queries at once, returning one result list per query in input order.
+    ///
+    /// Queries already in the cache are served from it. The rest are embedded in a single
+    /// model call and their vector searches run concurrently; a query repeated within the
+    /// batch is embedded and searched once.
+    pub async fn search_batch(
+        &self,
+        queries: &[String],
+        limit: usize,
+        min_score: Option<f32>,
+    ) -> Result<Vec<Vec<GuidelineResult>>, crate::error::AppError> {
+        let mut results: Vec<Option<Vec<GuidelineResult>>> = Vec::with_capacity(queries.len());
+        for query in queries {
+            results.push(self.cached_results(query, limit, min_score).await);
+        }
+
+        batch::fill_distinct(queries, &mut results, |texts| async move {
+            let embeddings = self.embedder.embed_queries(&texts).await?;
+            if embeddings.len() != texts.len() {
+                return Err(CommonError::Embedding(format!(
+                    "embedding count mismatch: expected {}, got {}",
+                    texts.len(),
+                    embeddings.len()
+                ))
+                .into());
+            }
+
+            let searches = texts.iter().zip(&embeddings).map(|(query, embedding)| {
+                self.search_embedding(query, embedding, limit, min_score)
+            });
+            futures::future::try_join_all(searches).await
+        })
+        .await?;
+
+        Ok(results.into_iter().map(Option::unwrap_or_default).collect())
+    }
+
+    /// Find the guidelines nearest to `guideline_id` using its stored embedding.
+    ///
+    /// Returns `None` if the guideline has no row in the index. The guideline itself is
+    /// excluded, and neighbours scoring below `min_score` are dropped, so the list may be
+    /// shorter than `limit` or empty.
+    pub async fn similar(
+        &self,
+        guideline_id: &str,
+        limit: usize,
+        min_score: Option<f32>,
+    ) -> Result<Option<Vec<GuidelineResult>>, crate::error::AppError> {
+        let embedding = self.vectordb.get_embedding(VECTOR_TABLE_NAME, guideline_id).await?;
+        let Some(embedding) = embedding else {
+            return Ok(None);
+        };
+
+        // One extra row since the guideline is its own nearest neighbour
+        let batches = self
+            .vectordb
+            .search(VECTOR_TABLE_NAME, &embedding, limit + 1, DISTANCE_TYPE)
+            .await?;
+        let results = extract_search_results(&batches, DISTANCE_TYPE, min_score, self.summary_len)
+            .into_iter()
+            .filter(|r| r.id != guideline_id)
+            .take(limit)
+            .collect();
+        Ok(Some(results))
+    }
+
+    /// Return cached results for `query` if the cached entry covers `limit`.
+    async fn cached_results(
+        &self,
+        query: &str,
+        limit: usize,
+        min_score: Option<f32>,
+    ) -> Option<Vec<GuidelineResult>> {
+        let cached = self
+            .cache
+            .get_search_results(query, SearchMode::Semantic, DISTANCE_TYPE, min_score)
+            .await?;
+        if !cached.serves(limit) {
+            return None;
+        }
+        info!(query, "search cache hit");
+        Some(cached.results.into_iter().take(limit).collect())
+    }
+
+    /// Run the vector search for an already-embedded query and cache the results.
+    async fn search_embedding(
+        &self,
+        query: &str,
+        query_embedding: &[f32],
+        limit: usize,
+        min_score: Option<f32>,
+    ) -> Result<Vec<GuidelineResult>, crate::error::AppError> {
+        // Vector search at the canonical limit so the cached entry covers smaller limits too
+        let fetch_limit = limit.max(self.canonical_limit);
+        let batches = self
+            .vectordb
+            .search(VECTOR_TABLE_NAME, query_embedding, fetch_limit, DISTANCE_TYPE)
+            .await?;
+
+        // Extract results from record batches
+        let cached = CachedSearch {
+            fetched_limit: fetch_limit,
+            results: extract_search_results(&batches, DISTANCE_TYPE, min_score, self.summary_len),
+        };
+
+        // Cache the full fetch, then trim to what was asked for
+        self.cache
+            .set_search_results(query, SearchMode::Semantic, DISTANCE_TYPE, min_score, &cached)
+            .await;
+        Ok(cached.results.into_iter().take(limit).collect())
     }
 
     /// Returns the LanceDB table name used for guidelines.
     pub fn table_name() -> &'static str {
         VECTOR_TABLE_NAME
     }
+
+    pub fn distance_type() -> DistanceType {
+        DISTANCE_TYPE
+    }
+
+    /// Minimum number of rows each search fetches (and caches) per query.
+    pub fn canonical_limit(&self) -> usize {
+        self.canonical_limit
+    }
+
+    /// Characters of text kept in each result's summary.
+    pub fn summary_len(&self) -> usize {
+        self.summary_len
+    }
+
+    /// Ids of every row in the vector index.
+    pub async fn indexed_ids(&self) -> Result<Vec<String>, crate::error::AppError> {
+        Ok(self.vectordb.list_ids(VECTOR_TABLE_NAME).await?)
+    }
+
+    /// Number of rows in the vector index.
+    pub async fn indexed_count(&self) -> Result<usize, crate::error::AppError> {
+        Ok(self.vectordb.count_rows(VECTOR_TABLE_NAME).await?)
+    }
 }
 
 /// Extract `GuidelineResult` values from LanceDB search result batches.
 ///
 /// Expected columns: id (Utf8), title (Utf8), category (Utf8), text (Utf8), _distance (Float32)
-fn extract_search_results(batches: &[RecordBatch]) -> Vec<GuidelineResult> {
+fn extract_search_results(
+    batches: &[RecordBatch],
+    distance_type: DistanceType,
+    min_score: Option<f32>,
+    summary_len: usize,
+) -> Vec<GuidelineResult> {
+    // Convert distance to a similarity score in [0, 1] according to the metric
+    // the search ran with; lower distance = higher score.
+    extract_results(batches, min_score, summary_len, |_, _, _, distance| {
+        Some(distance_type.score(distance))
+    })
+}
+
+/// `(id, score)` pairs of a ranking, for [`hybrid::merge_rankings`].
+fn id_scores(results: &[GuidelineResult]) -> Vec<(String, f32)> {
+    results.iter().map(|r| (r.id.clone(), r.score)).collect()
+}
+
+/// Extract `GuidelineResult` values from LanceDB result batches, scoring each row with
+/// `score_row(id, title, text, distance)`; rows it scores `None` are skipped.
+///
+/// `_distance` is only present for vector searches and reads as 0.0 otherwise.
+fn extract_results(
+    batches: &[RecordBatch],
+    min_score: Option<f32>,
+    summary_len: usize,
+    score_row: impl Fn(&str, &str, &str, f32) -> Option<f32>,
+) -> Vec<GuidelineResult> {
     let mut results = Vec::new();
 
     for batch in batches {
@@ -102,17 +339,16 @@ fn extract_search_results(batches: &[RecordBatch]) -> Vec<GuidelineResult> {
             let category = category_col.value(row).to_string();
             let text = text_col.value(row);
             let distance: f32 = distance_col.map(|c| c.value(row)).unwrap_or(0.0);
+            let Some(score) = score_row(&id, &title, text, distance) else {
+                continue;
+            };
 
-            // Convert distance to a similarity score (1.0 - normalized distance).
-            // LanceDB returns L2 distance by default; lower is more similar.
-            // We invert so higher score = more similar, clamped to [0, 1].
-            let score: f32 = (1.0_f32 - distance).max(0.0);
+            // Drop weak matches below the caller's threshold (results stay ranked).
+            if min_score.is_some_and(|min| score < min) {
+                continue;
+            }
 
-            let summary = if text.chars().count() > MAX_SUMMARY_LEN {
-                format!("{}...", text.chars().take(MAX_SUMMARY_LEN).collect::<String>())
-            } else {
-                text.to_string()
-            };
+            let summary = summary::summarize(text, summary_len);
 
             results.push(GuidelineResult {
                 id,
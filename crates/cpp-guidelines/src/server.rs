@@ -1,38 +1,84 @@
 /// MCP server implementation for C++ Core Guidelines.
 ///
-/// Exposes four tools:
+/// Exposes sixteen tools:
 /// - `search_guidelines`: Semantic search over guidelines
+/// - `search_batch`: Semantic search for several queries in one call
 /// - `get_guideline`: Look up a specific guideline by rule ID
+/// - `get_guidelines`: Look up several guidelines by rule ID in one call
+/// - `get_guideline_section`: Return one section (e.g. "Enforcement") of a guideline
+/// - `similar_guidelines`: Find guidelines near a given one
+/// - `get_related_guidelines`: List the rules a guideline cross-references
+/// - `get_guideline_examples`: Return a guideline's code examples tagged good/bad/neutral
+/// - `list_categories`: List every category with its rule count
 /// - `list_category`: List all guidelines in a category
 /// - `update_guidelines`: Trigger a re-index from the git repository
-use std::collections::HashMap;
+/// - `verify_index`: Cross-check loaded guidelines against the vector index
+/// - `invalidate_search_cache`: Clear cached search results only
+/// - `export_guidelines`: Export every guideline as JSON or one markdown bundle
+/// - `config_info`: Report the effective (sanitized) configuration
+/// - `get_stats`: Report loaded counts and the indexed commit
+///
+/// Every guideline is also an MCP resource at `guideline://{id}` whose content is its
+/// raw markdown.
+///
+/// Two prompts wrap common review workflows:
+/// - `review_snippet`: Review code against the rules that best match it
+/// - `explain_rule`: Explain one rule, quoting its full text
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
+use std::time::Duration;
 
 use rmcp::{
-    Json, ServerHandler,
+    Json, Peer, RoleServer, ServerHandler,
+    service::RequestContext,
+    handler::server::router::prompt::PromptRouter,
     handler::server::router::tool::ToolRouter,
+    handler::server::tool::ToolCallContext,
     handler::server::wrapper::Parameters,
     model::*,
-    tool, tool_handler, tool_router,
+    prompt, prompt_handler, prompt_router, tool, tool_router,
 };
-use tokio::sync::RwLock;
-use tracing::info;
+use tokio::sync::{RwLock, oneshot};
+use tokio::time::{Instant, MissedTickBehavior};
+use tracing::field::Empty;
+use tracing::{info, warn};
 
 use crate::cache::GuidelineCache;
 use crate::config::Config;
-use crate::model::{Category, Guideline};
+use crate::error::AppError;
+use crate::model::{
+    Category, CategoryOrder, EnforcementLevel, ExampleKind, Guideline, GuidelineResult,
+};
 use crate::search::SearchEngine;
-use crate::update::UpdateService;
-use mcp_common::embedding::Embedder;
+use crate::update::{ReindexGuard, UpdateService};
+use mcp_common::embedding::{EmbedProgress, Embedder};
+use mcp_common::hybrid::best_matching_section;
+use mcp_common::ids::{closest_match, matches_anchor, not_found_message, resolve_id};
 use mcp_common::mcp_api::{
-    CategoryInfo, CategoryListResponse, GetGuidelineParams, GuidelineDetailResponse,
-    GuidelineSearchResult, GuidelineSection as ApiGuidelineSection, GuidelineSummary,
-    ListCategoryParams, SearchGuidelinesParams, SearchGuidelinesResponse, UpdateGuidelinesResponse,
+    BatchQueryResults, CategoriesResponse, CategoryInfo, CategoryListResponse, ConfigInfoResponse,
+    EnforcementLevel as ApiEnforcementLevel,
+    ExampleKind as ApiExampleKind, ExplainRuleArgs, ExportGuidelinesParams,
+    ExportGuidelinesResponse, GetGuidelineParams, GetGuidelineSectionParams,
+    GetGuidelinesParams, GetGuidelinesResponse, GuidelineDetailResponse, GuidelineExample,
+    GuidelineExamplesParams, GuidelineExamplesResponse, GuidelineSearchResult,
+    GuidelineSection as ApiGuidelineSection, GuidelineSummary, InvalidateSearchCacheResponse,
+    ListCategoryParams, MAX_BATCH_IDS, MAX_BATCH_QUERIES, RelatedGuidelinesParams, RelatedGuidelinesResponse,
+    ReviewSnippetArgs, SearchBatchParams, SearchBatchResponse, SearchGuidelinesParams,
+    SearchGuidelinesResponse, SearchMode, SimilarGuidelinesParams, StatsResponse, UpdateGuidelinesResponse, VerifyIndexResponse,
 };
+use mcp_common::render::{RenderFormat, render_detail};
+use mcp_common::spans::input_bytes;
+use mcp_common::tool_timeout::ToolTimeout;
 use mcp_common::vectordb::VectorDb;
 
 // --- MCP Server ---
 
+/// URI prefix of guideline resources: `guideline://{id}`.
+const RESOURCE_URI_PREFIX: &str = "guideline://";
+
+/// Number of rules quoted in a `review_snippet` prompt.
+const REVIEW_RULE_COUNT: usize = 5;
+
 /// Shared application state, protected by RwLock for safe concurrent reads
 /// and exclusive writes during re-indexing.
 pub struct AppState {
@@ -46,7 +92,11 @@ pub struct CppGuidelinesServer {
     search_engine: Arc<SearchEngine>,
     update_service: Arc<UpdateService>,
     cache: Arc<GuidelineCache>,
+    config_info: ConfigInfoResponse,
+    category_order: CategoryOrder,
+    tool_timeout: ToolTimeout,
     tool_router: ToolRouter<CppGuidelinesServer>,
+    prompt_router: PromptRouter<CppGuidelinesServer>,
 }
 
 impl CppGuidelinesServer {
@@ -62,13 +112,33 @@ impl CppGuidelinesServer {
             .into_iter()
             .map(|g| (g.id.clone(), g))
             .collect();
+        let category_order = config.category_order;
 
         let search_engine = Arc::new(SearchEngine::new(
             Arc::clone(&embedder),
             Arc::clone(&vectordb),
             Arc::clone(&cache),
+            config.search_cache_canonical_limit,
         ));
 
+        let config_info = ConfigInfoResponse {
+            repo_path: config.repo_path.clone(),
+            lancedb_path: config.lancedb_path.clone(),
+            table_name: SearchEngine::table_name().to_string(),
+            embedding_model: embedder.model_name().to_string(),
+            embedding_dimension: embedder.dimensions(),
+            distance_metric: SearchEngine::distance_type().as_str().to_string(),
+            search_cache_ttl_secs: cache.search_ttl_secs(),
+            search_cache_canonical_limit: search_engine.canonical_limit(),
+            search_summary_len: search_engine.summary_len(),
+            redis_configured: config.redis_url.is_some(),
+            redis_available: false,
+            auto_pull: config.auto_pull,
+            refresh_secs: config.refresh_interval.map(|period| period.as_secs()),
+            tool_timeout_secs: config.tool_timeout.limit().map(|limit| limit.as_secs()),
+        };
+        let tool_timeout = config.tool_timeout;
+
         let update_service = Arc::new(UpdateService::new(
             config,
             Arc::clone(&embedder),
@@ -86,14 +156,107 @@ impl CppGuidelinesServer {
             search_engine,
             update_service,
             cache,
+            config_info,
+            category_order,
+            tool_timeout,
             tool_router: Self::tool_router(),
+            prompt_router: Self::prompt_router(),
+        }
+    }
+
+    /// Wait until no `update_guidelines` re-index is running. Call on shutdown only: it
+    /// queues for the state write lock, which stalls new tool calls while it waits.
+    pub async fn wait_for_reindex(&self) {
+        let _state = self.state.write().await;
+    }
+
+    /// Commit the loaded guidelines were indexed from: the cached one, falling back to the
+    /// checkout's HEAD.
+    async fn indexed_commit(&self) -> Option<String> {
+        match self.cache.get_repo_commit().await {
+            Some(commit) => Some(commit),
+            None => self.update_service.get_repo_commit().ok(),
+        }
+    }
+
+    /// Run an update under `reindex` and, if it re-indexed, swap the new data into memory.
+    async fn apply_update(
+        &self,
+        reindex: &ReindexGuard,
+        progress: Option<EmbedProgress<'_>>,
+    ) -> Result<UpdateGuidelinesResponse, AppError> {
+        // Held for the whole re-index so `wait_for_reindex` cannot return mid-write.
+        let loaded = self.state.read().await;
+        let (result, new_data) = self
+            .update_service
+            .update(reindex, Some(loaded.guidelines.len()), progress)
+            .await?;
+        drop(loaded);
+
+        // If re-indexed, update the in-memory state
+        if let Some((guidelines, categories)) = new_data {
+            let guideline_count = guidelines.len();
+            let guideline_map: HashMap<String, Guideline> = guidelines
+                .into_iter()
+                .map(|g| (g.id.clone(), g))
+                .collect();
+
+            let mut state = self.state.write().await;
+            state.guidelines = guideline_map;
+            state.categories = categories;
+            info!(guideline_count, "in-memory state updated");
+        }
+
+        let guideline_count = if result.updated {
+            result.guideline_count
+        } else {
+            let state = self.state.read().await;
+            state.guidelines.len()
+        };
+
+        Ok(UpdateGuidelinesResponse {
+            updated: result.updated,
+            commit: result.commit,
+            guideline_count,
+        })
+    }
+
+    /// Check for updates every `period` until `stop` resolves or its sender is dropped.
+    ///
+    /// A tick is skipped while another update holds the re-index lock, and a re-index that
+    /// has started always runs to completion before the loop stops.
+    pub async fn refresh_periodically(&self, period: Duration, mut stop: oneshot::Receiver<()>) {
+        let mut interval = tokio::time::interval_at(Instant::now() + period, period);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            tokio::select! {
+                _ = &mut stop => break,
+                _ = interval.tick() => {}
+            }
+
+            let Some(reindex) = self.update_service.try_lock() else {
+                info!("scheduled update skipped: a re-index is already in progress");
+                continue;
+            };
+            match self.apply_update(&reindex, None).await {
+                Ok(result) if result.updated => info!(
+                    commit = %result.commit,
+                    guideline_count = result.guideline_count,
+                    "scheduled update re-indexed guidelines"
+                ),
+                Ok(result) => {
+                    info!(commit = %result.commit, "scheduled update: guidelines up to date")
+                }
+                Err(e) => warn!(error = %e, "scheduled update failed"),
+            }
         }
     }
 }
 
 #[tool_router]
 impl CppGuidelinesServer {
-    #[tool(description = "Search C++ Core Guidelines by semantic similarity. Returns ranked results matching the query.")]
+    #[tool(description = "Search C++ Core Guidelines by semantic similarity. Returns ranked results matching the query, for example query=\"avoid raw new and delete\". Set mode=\"keyword\" to match exact identifiers or terms such as \"SL.con.1\" or \"noexcept\", or mode=\"hybrid\" to blend keyword and semantic rankings. Pass include_sections=true to get the best-matching section heading (e.g. \"Enforcement\") of each result. Pass enforcement=[\"enforceable\", \"partially\"] to keep only rules a tool can check, judged from their Enforcement sections (levels: enforceable, partially, hard, unknown).")]
+    #[tracing::instrument(name = "tool", skip_all, fields(tool = "search_guidelines", input_bytes = input_bytes(&params), cache = Empty))]
     async fn search_guidelines(
         &self,
         Parameters(params): Parameters<SearchGuidelinesParams>,
@@ -105,29 +268,105 @@ impl CppGuidelinesServer {
 
         let limit = params.limit.unwrap_or(10).min(50) as usize;
 
+        if let Some(min_score) = params.min_score {
+            if !(0.0..=1.0).contains(&min_score) {
+                return Err("min_score must be between 0.0 and 1.0".to_string());
+            }
+        }
+        if params.enforcement.as_ref().is_some_and(Vec::is_empty) {
+            return Err("enforcement must list at least one level".to_string());
+        }
+
+        // The enforcement filter drops results, so rank the full canonical fetch before it.
+        let fetch = match params.enforcement {
+            Some(_) => limit.max(self.search_engine.canonical_limit()),
+            None => limit,
+        };
         let results = self
             .search_engine
-            .search(&query, limit)
+            .search(&query, fetch, params.min_score, params.mode.unwrap_or_default())
             .await
             .map_err(|e| format!("search failed: {e}"))?;
 
-        let normalized: Vec<GuidelineSearchResult> = results
-            .into_iter()
-            .map(|r| GuidelineSearchResult {
-                id: r.id,
-                title: r.title,
-                category: r.category,
-                score: r.score,
-                summary: r.summary,
-            })
-            .collect();
+        let mut normalized: Vec<GuidelineSearchResult> =
+            results.into_iter().map(to_api_search_result).collect();
+
+        if let Some(levels) = &params.enforcement {
+            let state = self.state.read().await;
+            normalized.retain(|result| {
+                state.guidelines.get(&result.id).is_some_and(|guideline| {
+                    levels.contains(&to_api_enforcement(guideline.enforcement))
+                })
+            });
+            normalized.truncate(limit);
+        }
+
+        if params.include_sections.unwrap_or(false) {
+            let state = self.state.read().await;
+            for result in &mut normalized {
+                let Some(guideline) = state.guidelines.get(&result.id) else {
+                    continue;
+                };
+                let sections = guideline
+                    .sections
+                    .iter()
+                    .map(|s| (s.heading.as_str(), s.content.as_str()));
+                result.matched_section =
+                    best_matching_section(&query, sections).map(str::to_string);
+            }
+        }
 
         Ok(Json(SearchGuidelinesResponse {
             results: normalized,
         }))
     }
 
-    #[tool(description = "Get the full content of a specific C++ Core Guideline by ID (e.g. 'P.1', 'ES.20', 'SL.con.1').")]
+    #[tool(description = "Search C++ Core Guidelines for several queries in one call (at most 20). Returns one ranked result set per query, in input order.")]
+    #[tracing::instrument(name = "tool", skip_all, fields(tool = "search_batch", input_bytes = input_bytes(&params), cache = Empty))]
+    async fn search_batch(
+        &self,
+        Parameters(params): Parameters<SearchBatchParams>,
+    ) -> Result<Json<SearchBatchResponse>, String> {
+        if params.queries.is_empty() {
+            return Err("queries must not be empty".to_string());
+        }
+        if params.queries.len() > MAX_BATCH_QUERIES {
+            return Err(format!("at most {MAX_BATCH_QUERIES} queries are allowed per call"));
+        }
+
+        let queries: Vec<String> = params.queries.iter().map(|q| q.trim().to_string()).collect();
+        if let Some(i) = queries.iter().position(|q| q.is_empty()) {
+            return Err(format!("queries[{i}] must not be empty"));
+        }
+
+        let limit = params.limit.unwrap_or(10).min(50) as usize;
+
+        if let Some(min_score) = params.min_score {
+            if !(0.0..=1.0).contains(&min_score) {
+                return Err("min_score must be between 0.0 and 1.0".to_string());
+            }
+        }
+
+        let results = self
+            .search_engine
+            .search_batch(&queries, limit, params.min_score)
+            .await
+            .map_err(|e| format!("search failed: {e}"))?;
+
+        let queries = queries
+            .into_iter()
+            .zip(results)
+            .map(|(query, results)| BatchQueryResults {
+                query,
+                results: results.into_iter().map(to_api_search_result).collect(),
+            })
+            .collect();
+
+        Ok(Json(SearchBatchResponse { queries }))
+    }
+
+    #[tool(description = "Get the full content of a specific C++ Core Guideline by ID (e.g. 'P.1', 'ES.20', 'SL.con.1'). IDs match loosely, so 'p1' or 'ES 20' also work, and an unknown ID fails with the closest matches. The rule's anchor or a documentation link to it (e.g. 'Rp-direct' or '...CppCoreGuidelines#Rp-direct') also works. Pass render=\"html\" or \"text\" for a pre-rendered copy in `rendered`.")]
+    #[tracing::instrument(name = "tool", skip_all, fields(tool = "get_guideline", input_bytes = input_bytes(&params), cache = Empty))]
     async fn get_guideline(
         &self,
         Parameters(params): Parameters<GetGuidelineParams>,
@@ -136,25 +375,211 @@ impl CppGuidelinesServer {
         if guideline_id.is_empty() {
             return Err("guideline_id must not be empty".to_string());
         }
+        let format = params
+            .render
+            .as_deref()
+            .map(RenderFormat::parse)
+            .transpose()?;
 
         // Check cache first
         if let Some(cached) = self.cache.get_guideline(&guideline_id).await {
-            return Ok(Json(to_api_guideline(&cached)));
+            return render_detail(to_api_guideline(&cached), format).map(Json);
         }
 
         // Look up in memory
         let state = self.state.read().await;
-        let guideline = state
-            .guidelines
+        let guideline = find_guideline(&state.guidelines, &guideline_id)?;
+
+        render_detail(to_api_guideline(guideline), format).map(Json)
+    }
+
+    #[tool(description = "Get several C++ Core Guidelines by ID in one call (at most 50), e.g. [\"P.1\", \"ES.20\", \"R.11\"]. Returns the found guidelines keyed by requested ID plus the IDs that were not found, instead of failing on the first unknown one.")]
+    #[tracing::instrument(name = "tool", skip_all, fields(tool = "get_guidelines", input_bytes = input_bytes(&params), cache = Empty))]
+    async fn get_guidelines(
+        &self,
+        Parameters(params): Parameters<GetGuidelinesParams>,
+    ) -> Result<Json<GetGuidelinesResponse>, String> {
+        if params.guideline_ids.is_empty() {
+            return Err("guideline_ids must not be empty".to_string());
+        }
+        if params.guideline_ids.len() > MAX_BATCH_IDS {
+            return Err(format!("at most {MAX_BATCH_IDS} guideline_ids are allowed per call"));
+        }
+
+        let ids: Vec<String> =
+            params.guideline_ids.iter().map(|id| id.trim().to_string()).collect();
+        if let Some(i) = ids.iter().position(|id| id.is_empty()) {
+            return Err(format!("guideline_ids[{i}] must not be empty"));
+        }
+        let format = params
+            .render
+            .as_deref()
+            .map(RenderFormat::parse)
+            .transpose()?;
+
+        // Exact-id hits come from one Redis MGET; the rest fall back to memory under one
+        // read lock for the whole batch.
+        let cached = self.cache.get_guidelines(&ids).await.unwrap_or_default();
+        let state = self.state.read().await;
+        let mut found = BTreeMap::new();
+        let mut not_found = Vec::new();
+        for (i, guideline_id) in ids.into_iter().enumerate() {
+            if found.contains_key(&guideline_id) || not_found.contains(&guideline_id) {
+                continue;
+            }
+            let guideline = cached
+                .get(i)
+                .and_then(Option::as_ref)
+                .or_else(|| lookup_guideline(&state.guidelines, &guideline_id));
+            match guideline {
+                Some(g) => {
+                    let detail = render_detail(to_api_guideline(g), format)?;
+                    found.insert(guideline_id, detail);
+                }
+                None => not_found.push(guideline_id),
+            }
+        }
+
+        Ok(Json(GetGuidelinesResponse { found, not_found }))
+    }
+
+    #[tool(description = "Get a single section of a C++ Core Guideline, such as the 'Reason', 'Example' or 'Enforcement' block of 'P.1', instead of the whole rule. Headings match case-insensitively; an unknown heading fails with the list of available ones.")]
+    #[tracing::instrument(name = "tool", skip_all, fields(tool = "get_guideline_section", input_bytes = input_bytes(&params), cache = Empty))]
+    async fn get_guideline_section(
+        &self,
+        Parameters(params): Parameters<GetGuidelineSectionParams>,
+    ) -> Result<Json<ApiGuidelineSection>, String> {
+        let guideline_id = params.guideline_id.trim().to_string();
+        if guideline_id.is_empty() {
+            return Err("guideline_id must not be empty".to_string());
+        }
+        let section = params.section.trim();
+        if section.is_empty() {
+            return Err("section must not be empty".to_string());
+        }
+
+        if let Some(cached) = self.cache.get_guideline(&guideline_id).await {
+            return find_section(&cached, section).map(Json);
+        }
+
+        let state = self.state.read().await;
+        let guideline = find_guideline(&state.guidelines, &guideline_id)?;
+
+        find_section(guideline, section).map(Json)
+    }
+
+    #[tool(description = "Find the guidelines most similar to a given guideline ID. Use min_score (0.0 to 1.0) to drop weak neighbours; results may be empty when nothing is genuinely similar.")]
+    #[tracing::instrument(name = "tool", skip_all, fields(tool = "similar_guidelines", input_bytes = input_bytes(&params), cache = Empty))]
+    async fn similar_guidelines(
+        &self,
+        Parameters(params): Parameters<SimilarGuidelinesParams>,
+    ) -> Result<Json<SearchGuidelinesResponse>, String> {
+        let guideline_id = params.guideline_id.trim().to_string();
+        if guideline_id.is_empty() {
+            return Err("guideline_id must not be empty".to_string());
+        }
+
+        let limit = params.limit.unwrap_or(10).min(50) as usize;
+
+        if let Some(min_score) = params.min_score {
+            if !(0.0..=1.0).contains(&min_score) {
+                return Err("min_score must be between 0.0 and 1.0".to_string());
+            }
+        }
+
+        let canonical_id = {
+            let state = self.state.read().await;
+            find_guideline(&state.guidelines, &guideline_id)?.id.clone()
+        };
+
+        let results = self
+            .search_engine
+            .similar(&canonical_id, limit, params.min_score)
+            .await
+            .map_err(|e| format!("similar_guidelines failed: {e}"))?
+            .ok_or_else(|| format!("guideline not indexed: {canonical_id}"))?;
+
+        Ok(Json(SearchGuidelinesResponse {
+            results: results.into_iter().map(to_api_search_result).collect(),
+        }))
+    }
+
+    #[tool(description = "List the rules a C++ Core Guideline cross-references, via links or mentions such as 'see C.21' (e.g. for 'C.20'). Returns id and title for each, in order of first appearance.")]
+    #[tracing::instrument(name = "tool", skip_all, fields(tool = "get_related_guidelines", input_bytes = input_bytes(&params), cache = Empty))]
+    async fn get_related_guidelines(
+        &self,
+        Parameters(params): Parameters<RelatedGuidelinesParams>,
+    ) -> Result<Json<RelatedGuidelinesResponse>, String> {
+        let guideline_id = params.guideline_id.trim().to_string();
+        if guideline_id.is_empty() {
+            return Err("guideline_id must not be empty".to_string());
+        }
+
+        let state = self.state.read().await;
+        let guideline = find_guideline(&state.guidelines, &guideline_id)?;
+
+        let related = guideline
+            .related
+            .iter()
+            .filter_map(|id| state.guidelines.get(id))
+            .map(|g| GuidelineSummary {
+                id: g.id.clone(),
+                title: g.title.clone(),
+            })
+            .collect();
+
+        Ok(Json(RelatedGuidelinesResponse {
+            guideline_id: guideline.id.clone(),
+            related,
+        }))
+    }
+
+    #[tool(description = "Get the code examples of a C++ Core Guideline (e.g. 'ES.20', 'R.11'), each tagged good, bad or neutral from its 'Example, good/bad' heading. Pass kind=\"bad\" to get only non-compliant code, e.g. for lint-style prompts.")]
+    #[tracing::instrument(name = "tool", skip_all, fields(tool = "get_guideline_examples", input_bytes = input_bytes(&params), cache = Empty))]
+    async fn get_guideline_examples(
+        &self,
+        Parameters(params): Parameters<GuidelineExamplesParams>,
+    ) -> Result<Json<GuidelineExamplesResponse>, String> {
+        let guideline_id = params.guideline_id.trim().to_string();
+        if guideline_id.is_empty() {
+            return Err("guideline_id must not be empty".to_string());
+        }
+
+        let state = self.state.read().await;
+        let guideline = find_guideline(&state.guidelines, &guideline_id)?;
+
+        let examples = guideline
+            .examples
             .iter()
-            .find(|(id, _)| id.eq_ignore_ascii_case(&guideline_id))
-            .map(|(_, g)| g)
-            .ok_or_else(|| format!("guideline not found: {guideline_id}"))?;
+            .map(|e| GuidelineExample {
+                kind: to_api_example_kind(e.kind),
+                code: e.code.clone(),
+            })
+            .filter(|e| params.kind.is_none_or(|kind| e.kind == kind))
+            .collect();
 
-        Ok(Json(to_api_guideline(guideline)))
+        Ok(Json(GuidelineExamplesResponse {
+            guideline_id: guideline.id.clone(),
+            examples,
+        }))
+    }
+
+    #[tool(description = "List every C++ Core Guidelines category (e.g. 'P' Philosophy, 'ES' Expressions) with its display name and rule count, to plan browsing with list_category.")]
+    #[tracing::instrument(name = "tool", skip_all, fields(tool = "list_categories", cache = Empty))]
+    async fn list_categories(&self) -> Result<Json<CategoriesResponse>, String> {
+        let mut categories: Vec<Category> = match self.cache.get_categories().await {
+            Some(cached) => cached,
+            None => self.state.read().await.categories.values().cloned().collect(),
+        };
+        self.category_order.sort(&mut categories);
+
+        Ok(Json(CategoriesResponse {
+            categories: categories.iter().map(to_api_category).collect(),
+        }))
     }
 
     #[tool(description = "List all C++ Core Guidelines in a specific category. Use category prefixes like 'P' (Philosophy), 'R' (Resource management), 'ES' (Expressions), 'SL' (Standard Library), etc.")]
+    #[tracing::instrument(name = "tool", skip_all, fields(tool = "list_category", input_bytes = input_bytes(&params), cache = Empty))]
     async fn list_category(
         &self,
         Parameters(params): Parameters<ListCategoryParams>,
@@ -171,9 +596,14 @@ impl CppGuidelinesServer {
             .find(|(key, _)| key.eq_ignore_ascii_case(&category_prefix))
             .map(|(key, category)| (key.clone(), category.clone()))
             .ok_or_else(|| {
-                let available: Vec<&str> = state.categories.keys().map(|s| s.as_str()).collect();
+                let mut available: Vec<Category> = state.categories.values().cloned().collect();
+                self.category_order.sort(&mut available);
+                let available: Vec<&str> = available.iter().map(|c| c.prefix.as_str()).collect();
+                let hint = closest_match(&category_prefix, available.iter().copied())
+                    .map(|key| format!("did you mean '{key}'? "))
+                    .unwrap_or_default();
                 format!(
-                    "unknown category: '{category_prefix}'. Available categories: {}",
+                    "{hint}unknown category: '{category_prefix}'. Available categories: {}",
                     available.join(", ")
                 )
             })?;
@@ -188,6 +618,8 @@ impl CppGuidelinesServer {
             })
             .collect();
         guideline_summaries.sort_by(|a, b| a.id.cmp(&b.id));
+        let total = guideline_summaries.len();
+        let guideline_summaries = params.page(guideline_summaries);
 
         let response = CategoryListResponse {
             category: CategoryInfo {
@@ -195,48 +627,273 @@ impl CppGuidelinesServer {
                 display_name: category.name,
                 guideline_count: category.rule_count,
             },
+            total,
             guidelines: guideline_summaries,
         };
 
         Ok(Json(response))
     }
 
-    #[tool(description = "Trigger a re-index of the C++ Core Guidelines from the git repository. Checks for updates and re-parses/re-embeds if the content has changed.")]
-    async fn update_guidelines(&self) -> Result<Json<UpdateGuidelinesResponse>, String> {
+    #[tool(description = "Trigger a re-index of the C++ Core Guidelines from the git repository. Checks for updates and re-parses/re-embeds if the content has changed. Only one re-index runs at a time; a call made while one is in progress fails and can be retried. A re-index that outlasts the tool timeout keeps running and its result is loaded when it finishes. Send a progress token to receive embedding progress as progress notifications.")]
+    #[tracing::instrument(name = "tool", skip_all, fields(tool = "update_guidelines", cache = Empty))]
+    async fn update_guidelines(
+        &self,
+        meta: Meta,
+        peer: Peer<RoleServer>,
+    ) -> Result<Json<UpdateGuidelinesResponse>, String> {
         info!("update_guidelines tool invoked");
 
-        let (result, new_data) = self
-            .update_service
-            .update()
-            .await
+        // Kept until the new data is swapped in, so two updates can neither race on the
+        // LanceDB table nor apply their results out of order.
+        let Some(reindex) = self.update_service.try_lock() else {
+            return Err("a re-index is already in progress; retry once it completes".to_string());
+        };
+
+        // Embedding progress is relayed as progress notifications when the client sent a
+        // progress token, and dropped otherwise.
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<(usize, usize)>();
+        let report = move |done: usize, total: usize| {
+            let _ = tx.send((done, total));
+        };
+        let forward = async {
+            let Some(progress_token) = meta.get_progress_token() else {
+                return;
+            };
+            while let Some((done, total)) = rx.recv().await {
+                let param = ProgressNotificationParam {
+                    progress_token: progress_token.clone(),
+                    progress: done as f64,
+                    total: Some(total as f64),
+                    message: Some(format!("embedded {done} of {total} guidelines")),
+                };
+                if let Err(e) = peer.notify_progress(param).await {
+                    warn!(error = %e, "failed to send progress notification");
+                }
+            }
+        };
+
+        // The update runs as its own task, so a tool timeout or a dropped request cannot stop
+        // it between writing the vector index and swapping the new data into memory; the
+        // result is applied when it completes either way.
+        let server = self.clone();
+        let update = tokio::spawn(async move {
+            // Moved in so the channel closes, ending `forward`, once the update finishes.
+            let report = report;
+            server.apply_update(&reindex, Some(&report)).await
+        });
+        let (result, ()) = tokio::join!(update, forward);
+        let response = result
+            .map_err(|e| format!("update failed: re-index task failed: {e}"))?
             .map_err(|e| format!("update failed: {e}"))?;
+        Ok(Json(response))
+    }
 
-        // If re-indexed, update the in-memory state
-        if let Some((guidelines, categories)) = new_data {
-            let guideline_count = guidelines.len();
-            let guideline_map: HashMap<String, Guideline> = guidelines
-                .into_iter()
-                .map(|g| (g.id.clone(), g))
-                .collect();
+    #[tool(description = "Clear cached search results so the next searches run against the current index, e.g. after re-indexing with changed embedding text. Guideline and category caches are left in place. Returns how many cached searches were removed.")]
+    #[tracing::instrument(name = "tool", skip_all, fields(tool = "invalidate_search_cache", cache = Empty))]
+    async fn invalidate_search_cache(&self) -> Result<Json<InvalidateSearchCacheResponse>, String> {
+        let deleted = self
+            .cache
+            .invalidate_search()
+            .await
+            .ok_or_else(|| "invalidate_search_cache failed: Redis is unavailable or returned an error".to_string())?;
+        info!(deleted, "search cache cleared");
+        Ok(Json(InvalidateSearchCacheResponse { deleted }))
+    }
 
-            let mut state = self.state.write().await;
-            state.guidelines = guideline_map;
-            state.categories = categories;
-            info!(guideline_count, "in-memory state updated");
-        }
+    #[tool(description = "Cross-check the loaded guidelines against the vector index and report ids missing from either side or indexed more than once. Operator-facing consistency check for partial or drifted re-indexes.")]
+    #[tracing::instrument(name = "tool", skip_all, fields(tool = "verify_index", cache = Empty))]
+    async fn verify_index(&self) -> Result<Json<VerifyIndexResponse>, String> {
+        let index_ids = self
+            .search_engine
+            .indexed_ids()
+            .await
+            .map_err(|e| format!("verify_index failed: {e}"))?;
+        let state = self.state.read().await;
+        Ok(Json(VerifyIndexResponse::compare(
+            state.guidelines.keys().cloned(),
+            index_ids,
+        )))
+    }
 
-        let response = UpdateGuidelinesResponse {
-            updated: result.updated,
-            commit: result.commit,
-            guideline_count: if result.updated {
-                result.guideline_count
-            } else {
-                let state = self.state.read().await;
-                state.guidelines.len()
-            },
+    #[tool(description = "Export the loaded guidelines, for offline use or diffing across commits. format=\"json\" (default) returns structured guidelines sorted by id; format=\"markdown\" returns one document of their raw markdown headed by the indexed commit. Page with offset and limit (at least 1): without a limit a page holds as many guidelines as fit in 512 KiB, a limit whose page exceeds 512 KiB is refused, and next_offset gives the offset of the following page. Prefer search or lookup tools for individual rules.")]
+    #[tracing::instrument(name = "tool", skip_all, fields(tool = "export_guidelines", input_bytes = input_bytes(&params), cache = Empty))]
+    async fn export_guidelines(
+        &self,
+        Parameters(params): Parameters<ExportGuidelinesParams>,
+    ) -> Result<Json<ExportGuidelinesResponse>, String> {
+        let commit = self.indexed_commit().await;
+        let state = self.state.read().await;
+        let mut guidelines: Vec<&Guideline> = state.guidelines.values().collect();
+        guidelines.sort_by(|a, b| a.id.cmp(&b.id));
+        ExportGuidelinesResponse::new(
+            &params,
+            "C++ Core Guidelines",
+            commit,
+            guidelines.into_iter().map(to_api_guideline).collect(),
+        )
+        .map(Json)
+    }
+
+    #[tool(description = "Report how many guidelines and categories are loaded, how many rows the vector index holds, the repository commit they were indexed from, Redis status, and cache hit/miss counts for guideline, search and category lookups. Read-only and cheap; does not trigger a re-index.")]
+    #[tracing::instrument(name = "tool", skip_all, fields(tool = "get_stats", cache = Empty))]
+    async fn get_stats(&self) -> Result<Json<StatsResponse>, String> {
+        let (guideline_count, category_count) = {
+            let state = self.state.read().await;
+            (state.guidelines.len(), state.categories.len())
         };
+        Ok(Json(StatsResponse {
+            guideline_count,
+            category_count,
+            commit: self.indexed_commit().await,
+            indexed_count: self.search_engine.indexed_count().await.ok(),
+            redis_available: self.cache.is_available().await,
+            cache: self.cache.metrics(),
+        }))
+    }
 
-        Ok(Json(response))
+    #[tool(description = "Report the server's effective configuration (paths, embedding and search settings, cache TTLs, Redis status). Credentials are never included.")]
+    #[tracing::instrument(name = "tool", skip_all, fields(tool = "config_info", cache = Empty))]
+    async fn config_info(&self) -> Result<Json<ConfigInfoResponse>, String> {
+        let mut info = self.config_info.clone();
+        info.redis_available = self.cache.is_available().await;
+        Ok(Json(info))
+    }
+}
+
+#[prompt_router]
+impl CppGuidelinesServer {
+    #[prompt(name = "review_snippet", description = "Review a C++ snippet against the C++ Core Guidelines. The prompt lists the rules that best match the code so findings can cite rule IDs.")]
+    async fn review_snippet(
+        &self,
+        Parameters(args): Parameters<ReviewSnippetArgs>,
+    ) -> Result<GetPromptResult, ErrorData> {
+        let code = args.code.trim();
+        if code.is_empty() {
+            return Err(ErrorData::invalid_params("code must not be empty", None));
+        }
+
+        let rules = self
+            .search_engine
+            .search(code, REVIEW_RULE_COUNT, None, SearchMode::Semantic)
+            .await
+            .map_err(|e| ErrorData::internal_error(format!("search failed: {e}"), None))?;
+
+        let mut text = String::from(
+            "Review the following C++ code against the C++ Core Guidelines. Cite the rule ID \
+             for every finding and suggest a fix. Use get_guideline to read a rule in full.\n\n",
+        );
+        if !rules.is_empty() {
+            text.push_str("Rules that look most relevant:\n");
+            for rule in &rules {
+                text.push_str(&format!("- {}: {}\n", rule.id, rule.title));
+            }
+            text.push('\n');
+        }
+        text.push_str(&format!("```cpp\n{code}\n```"));
+
+        Ok(GetPromptResult {
+            description: Some("Review C++ code against the C++ Core Guidelines".to_string()),
+            messages: vec![PromptMessage::new_text(PromptMessageRole::User, text)],
+        })
+    }
+
+    #[prompt(name = "explain_rule", description = "Explain one C++ Core Guideline by ID, with its full text included in the prompt.")]
+    async fn explain_rule(
+        &self,
+        Parameters(args): Parameters<ExplainRuleArgs>,
+    ) -> Result<GetPromptResult, ErrorData> {
+        let state = self.state.read().await;
+        let guideline = find_guideline(&state.guidelines, &args.guideline_id)
+            .map_err(|e| ErrorData::invalid_params(e, None))?;
+
+        let text = format!(
+            "Explain C++ Core Guideline {id} ({title}). Say why the rule exists, show a \
+             compliant and a non-compliant example, and describe how to enforce it.\n\n{body}",
+            id = guideline.id,
+            title = guideline.title,
+            body = guideline.raw_markdown,
+        );
+
+        Ok(GetPromptResult {
+            description: Some(format!("Explain {}: {}", guideline.id, guideline.title)),
+            messages: vec![PromptMessage::new_text(PromptMessageRole::User, text)],
+        })
+    }
+}
+
+/// Resource entry for a guideline, read back as its raw markdown.
+fn to_resource(guideline: &Guideline) -> Resource {
+    let mut resource = RawResource::new(
+        format!("{RESOURCE_URI_PREFIX}{}", guideline.id),
+        guideline.id.clone(),
+    );
+    resource.description = Some(guideline.title.clone());
+    resource.mime_type = Some("text/markdown".to_string());
+    resource.no_annotation()
+}
+
+/// Every guideline as a resource, sorted by id.
+fn guideline_resources(guidelines: &HashMap<String, Guideline>) -> Vec<Resource> {
+    let mut guidelines: Vec<&Guideline> = guidelines.values().collect();
+    guidelines.sort_by(|a, b| a.id.cmp(&b.id));
+    guidelines.into_iter().map(to_resource).collect()
+}
+
+/// The raw markdown of the guideline `uri` names, or `resource_not_found` when it names none.
+fn read_guideline_resource(
+    guidelines: &HashMap<String, Guideline>,
+    uri: String,
+) -> Result<ReadResourceResult, ErrorData> {
+    let guideline = uri
+        .strip_prefix(RESOURCE_URI_PREFIX)
+        .and_then(|id| guidelines.get(id))
+        .ok_or_else(|| ErrorData::resource_not_found(format!("unknown resource: {uri}"), None))?;
+    Ok(ReadResourceResult {
+        contents: vec![ResourceContents::text(guideline.raw_markdown.clone(), uri)],
+    })
+}
+
+/// The guideline with the loosely matched id, or else the one `guideline_id` names by anchor
+/// (see [`matches_anchor`]).
+fn lookup_guideline<'a>(
+    guidelines: &'a HashMap<String, Guideline>,
+    guideline_id: &str,
+) -> Option<&'a Guideline> {
+    resolve_id(guideline_id, guidelines.keys().map(String::as_str))
+        .and_then(|id| guidelines.get(id))
+        .or_else(|| {
+            guidelines
+                .values()
+                .find(|g| matches_anchor(guideline_id, &g.anchor))
+        })
+}
+
+/// Look up a guideline by a loosely typed id (see [`mcp_common::ids`]), suggesting the
+/// closest ids in the error when nothing matches.
+fn find_guideline<'a>(
+    guidelines: &'a HashMap<String, Guideline>,
+    guideline_id: &str,
+) -> Result<&'a Guideline, String> {
+    lookup_guideline(guidelines, guideline_id)
+        .ok_or_else(|| not_found_message(guideline_id, guidelines.keys().map(String::as_str)))
+}
+
+fn to_api_search_result(result: GuidelineResult) -> GuidelineSearchResult {
+    GuidelineSearchResult {
+        id: result.id,
+        title: result.title,
+        category: result.category,
+        score: result.score,
+        summary: result.summary,
+        matched_section: None,
+    }
+}
+
+fn to_api_category(category: &Category) -> CategoryInfo {
+    CategoryInfo {
+        key: category.prefix.clone(),
+        display_name: category.name.clone(),
+        guideline_count: category.rule_count,
     }
 }
 
@@ -247,6 +904,7 @@ fn to_api_guideline(guideline: &Guideline) -> GuidelineDetailResponse {
         title: guideline.title.clone(),
         category: guideline.category.clone(),
         raw_markdown: guideline.raw_markdown.clone(),
+        rendered: None,
         sections: Some(
             guideline
                 .sections
@@ -258,16 +916,58 @@ fn to_api_guideline(guideline: &Guideline) -> GuidelineDetailResponse {
                 .collect(),
         ),
         source_file: None,
+        enforcement: Some(to_api_enforcement(guideline.enforcement)),
     }
 }
 
-#[tool_handler]
+fn to_api_example_kind(kind: ExampleKind) -> ApiExampleKind {
+    match kind {
+        ExampleKind::Good => ApiExampleKind::Good,
+        ExampleKind::Bad => ApiExampleKind::Bad,
+        ExampleKind::Neutral => ApiExampleKind::Neutral,
+    }
+}
+
+fn to_api_enforcement(level: EnforcementLevel) -> ApiEnforcementLevel {
+    match level {
+        EnforcementLevel::Enforceable => ApiEnforcementLevel::Enforceable,
+        EnforcementLevel::Partially => ApiEnforcementLevel::Partially,
+        EnforcementLevel::Hard => ApiEnforcementLevel::Hard,
+        EnforcementLevel::Unknown => ApiEnforcementLevel::Unknown,
+    }
+}
+
+/// First section whose heading matches `heading` case-insensitively.
+fn find_section(guideline: &Guideline, heading: &str) -> Result<ApiGuidelineSection, String> {
+    guideline
+        .sections
+        .iter()
+        .find(|s| s.heading.eq_ignore_ascii_case(heading))
+        .map(|s| ApiGuidelineSection {
+            heading: s.heading.clone(),
+            content: s.content.clone(),
+        })
+        .ok_or_else(|| {
+            let available: Vec<&str> =
+                guideline.sections.iter().map(|s| s.heading.as_str()).collect();
+            let available = if available.is_empty() {
+                "(none)".to_string()
+            } else {
+                available.join(", ")
+            };
+            format!("section {heading:?} not found in {}; available: {available}", guideline.id)
+        })
+}
+
+#[prompt_handler]
 impl ServerHandler for CppGuidelinesServer {
     fn get_info(&self) -> ServerInfo {
         ServerInfo {
             protocol_version: ProtocolVersion::V_2025_06_18,
             capabilities: ServerCapabilities::builder()
                 .enable_tools()
+                .enable_prompts()
+                .enable_resources()
                 .build(),
             server_info: Implementation {
                 name: "cpp-guidelines".to_string(),
@@ -280,26 +980,199 @@ impl ServerHandler for CppGuidelinesServer {
                 "C++ Core Guidelines MCP server. Provides semantic search and lookup \
                  over the C++ Core Guidelines (~513 rules). Use search_guidelines for \
                  natural language queries, get_guideline for specific rule lookup by ID, \
-                 list_category for browsing by category, and update_guidelines to \
-                 refresh from the repository."
+                 get_guidelines to fetch several rules at once, \
+                 get_guideline_section for a single block such as Enforcement, \
+                 similar_guidelines for semantically related rules, \
+                 get_related_guidelines for the rules a guideline cross-references, \
+                 get_guideline_examples for its good/bad code examples, \
+                 list_categories to discover categories, list_category for browsing \
+                 by category, update_guidelines to \
+                 refresh from the repository, config_info to inspect the effective \
+                 configuration, verify_index to check the index for drift, export_guidelines \
+                 to snapshot every rule, and get_stats for loaded counts and the indexed commit. The review_snippet and explain_rule \
+                 prompts wrap code review and rule explanation."
                     .to_string(),
             ),
         }
     }
+
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let tool = request.name.clone();
+        let call = self
+            .tool_router
+            .call(ToolCallContext::new(self, request, context));
+        self.tool_timeout
+            .run(&tool, call)
+            .await
+            .unwrap_or_else(|message| Ok(CallToolResult::error(vec![Content::text(message)])))
+    }
+
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, ErrorData> {
+        Ok(ListToolsResult::with_all_items(self.tool_router.list_all()))
+    }
+
+    async fn list_resources(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourcesResult, ErrorData> {
+        let state = self.state.read().await;
+        Ok(ListResourcesResult::with_all_items(guideline_resources(&state.guidelines)))
+    }
+
+    async fn read_resource(
+        &self,
+        ReadResourceRequestParams { uri, .. }: ReadResourceRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, ErrorData> {
+        let state = self.state.read().await;
+        read_guideline_resource(&state.guidelines, uri)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::CppGuidelinesServer;
+    use std::collections::HashMap;
+
+    use rmcp::model::{ErrorCode, ResourceContents};
+
+    use super::{
+        CppGuidelinesServer, find_guideline, find_section, guideline_resources,
+        read_guideline_resource,
+    };
+    use crate::model::{Guideline, GuidelineSection};
+
+    #[test]
+    fn find_section_matches_heading_case_insensitively() {
+        let guideline = Guideline {
+            id: "P.1".to_string(),
+            anchor: "Rp-direct".to_string(),
+            title: "Express ideas directly in code".to_string(),
+            category: "P".to_string(),
+            raw_markdown: String::new(),
+            related: Vec::new(),
+            examples: Vec::new(),
+            enforcement: Default::default(),
+            sections: vec![
+                GuidelineSection {
+                    heading: "Reason".to_string(),
+                    content: "Compilers don't read comments.".to_string(),
+                },
+                GuidelineSection {
+                    heading: "Enforcement".to_string(),
+                    content: "Very hard in general.".to_string(),
+                },
+            ],
+        };
+
+        let section = find_section(&guideline, "enforcement").unwrap();
+        assert_eq!(section.heading, "Enforcement");
+        assert_eq!(section.content, "Very hard in general.");
+
+        let err = find_section(&guideline, "Alternative").unwrap_err();
+        assert!(err.contains("available: Reason, Enforcement"), "{err}");
+    }
+
+    #[test]
+    fn find_guideline_falls_back_to_anchors() {
+        let guideline = |id: &str, anchor: &str| Guideline {
+            id: id.to_string(),
+            anchor: anchor.to_string(),
+            title: String::new(),
+            category: "P".to_string(),
+            raw_markdown: String::new(),
+            related: Vec::new(),
+            examples: Vec::new(),
+            enforcement: Default::default(),
+            sections: Vec::new(),
+        };
+        let guidelines: HashMap<String, Guideline> = [
+            guideline("P.1", "Rp-direct"),
+            guideline("P.2", "Rp-Cplusplus"),
+        ]
+        .into_iter()
+        .map(|g| (g.id.clone(), g))
+        .collect();
+
+        assert_eq!(find_guideline(&guidelines, "p2").unwrap().id, "P.2");
+        assert_eq!(find_guideline(&guidelines, "rp-direct").unwrap().id, "P.1");
+        let link = "https://isocpp.github.io/CppCoreGuidelines/CppCoreGuidelines#Rp-Cplusplus";
+        assert_eq!(find_guideline(&guidelines, link).unwrap().id, "P.2");
+        assert!(find_guideline(&guidelines, "rp-indirect").is_err());
+    }
+
+    #[test]
+    fn guidelines_are_listed_and_read_as_resources() {
+        let guideline = |id: &str, raw_markdown: &str| Guideline {
+            id: id.to_string(),
+            anchor: String::new(),
+            title: format!("{id} title"),
+            category: "P".to_string(),
+            raw_markdown: raw_markdown.to_string(),
+            related: Vec::new(),
+            examples: Vec::new(),
+            enforcement: Default::default(),
+            sections: Vec::new(),
+        };
+        let guidelines: HashMap<String, Guideline> = [
+            guideline("P.2", "### <a name=\"Rp-Cplusplus\"></a>P.2"),
+            guideline("P.1", "### <a name=\"Rp-direct\"></a>P.1"),
+        ]
+        .into_iter()
+        .map(|g| (g.id.clone(), g))
+        .collect();
+
+        let resources = guideline_resources(&guidelines);
+        let listed: Vec<(&str, &str)> =
+            resources.iter().map(|r| (r.uri.as_str(), r.name.as_str())).collect();
+        assert_eq!(listed, [("guideline://P.1", "P.1"), ("guideline://P.2", "P.2")]);
+        assert_eq!(resources[0].description.as_deref(), Some("P.1 title"));
+        assert_eq!(resources[0].mime_type.as_deref(), Some("text/markdown"));
+
+        let read = read_guideline_resource(&guidelines, "guideline://P.1".to_string()).unwrap();
+        match &read.contents[..] {
+            [ResourceContents::TextResourceContents { uri, text, .. }] => {
+                assert_eq!(uri, "guideline://P.1");
+                assert_eq!(text, "### <a name=\"Rp-direct\"></a>P.1");
+            }
+            other => panic!("unexpected contents: {other:?}"),
+        }
+
+        for uri in ["guideline://P.99", "P.1", "file:///P.1"] {
+            let err = read_guideline_resource(&guidelines, uri.to_string()).unwrap_err();
+            assert_eq!(err.code, ErrorCode::RESOURCE_NOT_FOUND, "{uri}");
+            assert!(err.message.contains(uri), "{}", err.message);
+        }
+    }
 
     #[test]
     fn tools_publish_output_schemas() {
         let tools = CppGuidelinesServer::tool_router().list_all();
         for name in [
             "search_guidelines",
+            "search_batch",
             "get_guideline",
+            "get_guidelines",
+            "get_guideline_section",
+            "similar_guidelines",
+            "get_related_guidelines",
+            "get_guideline_examples",
+            "list_categories",
             "list_category",
             "update_guidelines",
+            "config_info",
+            "verify_index",
+            "get_stats",
+            "invalidate_search_cache",
+            "export_guidelines",
         ] {
             let tool = tools
                 .iter()
@@ -311,4 +1184,16 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn prompts_are_listed() {
+        let prompts = CppGuidelinesServer::prompt_router().list_all();
+        for name in ["review_snippet", "explain_rule"] {
+            let prompt = prompts
+                .iter()
+                .find(|p| p.name == name)
+                .unwrap_or_else(|| panic!("missing prompt: {name}"));
+            assert!(prompt.arguments.as_ref().is_some_and(|args| !args.is_empty()));
+        }
+    }
 }
@@ -4,10 +4,12 @@
 /// Can be triggered at startup or on-demand via the `update_guidelines` MCP tool.
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use arrow_array::{ArrayRef, FixedSizeListArray, Float32Array, RecordBatch, StringArray};
 use arrow_schema::{DataType, Field, Schema};
-use tracing::info;
+use sha2::{Digest, Sha256};
+use tracing::{info, warn};
 
 use crate::cache::GuidelineCache;
 use crate::config::Config;
@@ -15,7 +17,7 @@ use crate::error::AppError;
 use crate::model::{Category, Guideline};
 use crate::parser;
 use crate::search::SearchEngine;
-use mcp_common::embedding::Embedder;
+use mcp_common::embedding::{EmbedProgress, Embedder};
 use mcp_common::vectordb::VectorDb;
 
 /// Result of an update operation.
@@ -28,11 +30,18 @@ pub struct UpdateResult {
     pub guideline_count: usize,
 }
 
+/// Schema metadata key recording what the table's vectors were embedded from.
+const EMBEDDING_SETTINGS_KEY: &str = "embedding_settings";
+
+/// Held for the duration of an update, so only one re-index runs at a time.
+pub type ReindexGuard = tokio::sync::OwnedMutexGuard<()>;
+
 pub struct UpdateService {
     config: Config,
     embedder: Arc<Embedder>,
     vectordb: Arc<VectorDb>,
     cache: Arc<GuidelineCache>,
+    reindex_lock: Arc<tokio::sync::Mutex<()>>,
 }
 
 impl UpdateService {
@@ -47,46 +56,95 @@ impl UpdateService {
             embedder,
             vectordb,
             cache,
+            reindex_lock: Arc::new(tokio::sync::Mutex::new(())),
         }
     }
 
+    /// Take the re-index lock, or `None` if another update holds it. Keep the guard until
+    /// the re-indexed data has been swapped into memory.
+    pub fn try_lock(&self) -> Option<ReindexGuard> {
+        Arc::clone(&self.reindex_lock).try_lock_owned().ok()
+    }
+
     /// Get the current git HEAD commit hash from the guidelines repository.
     pub fn get_repo_commit(&self) -> Result<String, AppError> {
-        let output = std::process::Command::new("git")
-            .arg("rev-parse")
-            .arg("HEAD")
-            .current_dir(&self.config.repo_path)
-            .output()
-            .map_err(|e| AppError::Git(format!("failed to run git rev-parse: {e}")))?;
-
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(AppError::Git(format!("git rev-parse failed: {stderr}")));
-        }
+        let repo = git2::Repository::discover(&self.config.repo_path).map_err(|e| {
+            AppError::Git(format!(
+                "failed to open repository at {}: {}",
+                self.config.repo_path,
+                e.message()
+            ))
+        })?;
+        let head = repo
+            .head()
+            .and_then(|head| head.peel_to_commit())
+            .map_err(|e| AppError::Git(format!("failed to resolve HEAD: {}", e.message())))?;
+        Ok(head.id().to_string())
+    }
 
-        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    /// Fetch `origin` and hard-reset the checkout to its default branch, discarding any
+    /// local changes. Only runs when `GUIDELINES_AUTO_PULL` is enabled.
+    async fn pull_repo(&self) -> Result<(), AppError> {
+        let repo_path = self.config.repo_path.clone();
+        tokio::task::spawn_blocking(move || pull_origin(&repo_path))
+            .await
+            .map_err(|e| AppError::Git(format!("spawn_blocking join error: {e}")))?
+            .map_err(|e| AppError::Git(format!("pull from origin failed: {}", e.message())))?;
+        info!("pulled guidelines repository from origin");
+        Ok(())
     }
 
     /// Check if an update is needed by comparing the current commit with the cached one.
     /// Returns `true` if re-indexing should occur.
-    pub async fn needs_update(&self) -> Result<bool, AppError> {
+    ///
+    /// When `expected_rows` is given, a table holding a different number of rows is treated
+    /// as partial or corrupted and also triggers a re-index. So does a table built with a
+    /// different embedding model or `CPP_EMBEDDING_SECTIONS`. With `GUIDELINES_AUTO_PULL`
+    /// enabled, the repository is pulled from upstream before its HEAD is read; a failed
+    /// pull is logged and the local checkout used as is.
+    pub async fn needs_update(&self, expected_rows: Option<usize>) -> Result<bool, AppError> {
+        // Without network (or an `origin`) the local checkout is still servable, so a
+        // failed pull is logged rather than failing the check, and startup with it.
+        if self.config.auto_pull {
+            if let Err(e) = self.pull_repo().await {
+                warn!(error = %e, "pull from origin failed, using the local checkout");
+            }
+        }
         let current_commit = self.get_repo_commit()?;
         let cached_commit = self.cache.get_repo_commit().await;
 
         match cached_commit {
             Some(cached) if cached == current_commit => {
-                // Also check if the LanceDB table exists
-                let table_check = self
-                    .vectordb
-                    .get_by_id(SearchEngine::table_name(), "__nonexistent__")
-                    .await;
-                match table_check {
-                    Ok(_) => Ok(false), // Table exists and commit matches
+                let rows = match self.vectordb.count_rows(SearchEngine::table_name()).await {
+                    Ok(rows) => rows,
                     Err(_) => {
                         info!("LanceDB table missing, re-index needed");
-                        Ok(true)
+                        return Ok(true);
                     }
+                };
+                if let Some(expected) = expected_rows.filter(|&expected| expected != rows) {
+                    info!(rows, expected, "LanceDB table row count mismatch, re-index needed");
+                    return Ok(true);
+                }
+
+                // A different embedding model makes the stored vectors unusable.
+                let stored_dim = self
+                    .vectordb
+                    .table_embedding_dim(SearchEngine::table_name())
+                    .await?;
+                let model_dim = self.embedder.dimensions();
+                if stored_dim != model_dim {
+                    info!(stored_dim, model_dim, "embedding dimension changed, re-index needed");
+                    return Ok(true);
                 }
+
+                // So does a change to what gets embedded (`CPP_EMBEDDING_SECTIONS`, or a
+                // model of the same dimension), recorded in the table's schema metadata.
+                if !self.settings_match().await {
+                    info!("embedding settings changed, re-index needed");
+                    return Ok(true);
+                }
+                Ok(false)
             }
             _ => Ok(true),
         }
@@ -94,21 +152,22 @@ impl UpdateService {
 
     /// Perform a full re-index: parse, embed, store in LanceDB, populate caches.
     ///
+    /// Only guidelines whose content hash changed since the last index are re-embedded and
+    /// upserted, and vanished ones are deleted; the table is rebuilt from scratch when no
+    /// hashes are cached or it no longer matches them.
+    ///
     /// Returns the parsed guidelines and categories for loading into the in-memory map.
     pub async fn full_reindex(
         &self,
+        progress: Option<EmbedProgress<'_>>,
     ) -> Result<(Vec<Guideline>, HashMap<String, Category>, String), AppError> {
         let current_commit = self.get_repo_commit()?;
         info!(commit = %current_commit, "starting full re-index");
 
         // 1. Parse guidelines
-        let content = std::fs::read_to_string(self.config.guidelines_file_path()).map_err(|e| {
-            AppError::Config(format!(
-                "failed to read {}: {e}",
-                self.config.guidelines_file_path().display()
-            ))
-        })?;
-        let (guidelines, categories) = parser::parse_guidelines(&content);
+        let content = self.read_guidelines()?;
+        let (guidelines, categories) =
+            parse_rules(&self.config.guidelines_file_path(), &content)?;
         info!(
             guideline_count = guidelines.len(),
             category_count = categories.len(),
@@ -116,77 +175,259 @@ impl UpdateService {
         );
 
         // 2. Generate embedding texts
-        let embedding_texts: Vec<String> = guidelines
+        let (embedding_texts, hashes) = self.embedding_texts(&guidelines);
+
+        // 3. Embed and store: only changed guidelines when the table matches the last
+        //    indexed set, otherwise everything into a fresh table
+        let previous = self.cache.get_content_hashes().await;
+        let incremental = match &previous {
+            Some(previous) => self.table_matches(previous, &hashes, guidelines.len()).await,
+            None => false,
+        };
+        match previous {
+            Some(previous) if incremental => {
+                self.apply_changes(&guidelines, &embedding_texts, &hashes, &previous, progress)
+                    .await?
+            }
+            _ => {
+                self.rebuild_table(&guidelines, &embedding_texts, progress)
+                    .await?
+            }
+        }
+
+        // 4. Invalidate all caches and repopulate
+        self.cache.invalidate_all().await;
+
+        // Cache individual guidelines, categories and category→rule_id mappings
+        let mut category_list: Vec<_> = categories.values().cloned().collect();
+        self.config.category_order.sort(&mut category_list);
+        self.cache.warmup(&guidelines, &category_list).await;
+
+        // Cache commit hash and the content hashes the table now reflects
+        self.cache.set_repo_commit(&current_commit).await;
+        self.cache.set_content_hashes(&hashes).await;
+
+        info!(
+            commit = %current_commit,
+            guidelines = guidelines.len(),
+            "re-index complete"
+        );
+
+        Ok((guidelines, categories, current_commit))
+    }
+
+    /// Read CppCoreGuidelines.md from the repository.
+    fn read_guidelines(&self) -> Result<String, AppError> {
+        std::fs::read_to_string(self.config.guidelines_file_path()).map_err(|e| {
+            AppError::Config(format!(
+                "failed to read {}: {e}",
+                self.config.guidelines_file_path().display()
+            ))
+        })
+    }
+
+    /// Each guideline's embedding text, and its content hash keyed by rule id.
+    fn embedding_texts(&self, guidelines: &[Guideline]) -> (Vec<String>, HashMap<String, String>) {
+        let texts: Vec<String> = guidelines
             .iter()
-            .map(parser::compose_embedding_text)
+            .map(|g| parser::compose_embedding_text(g, self.config.embedding_sections))
             .collect();
+        let hashes = guidelines
+            .iter()
+            .zip(&texts)
+            .map(|(g, text)| (g.id.clone(), self.content_hash(g, text)))
+            .collect();
+        (texts, hashes)
+    }
+
+    /// Hash identifying what a guideline's row was built from: the embedding model, the
+    /// embedded text and the source markdown.
+    fn content_hash(&self, guideline: &Guideline, embedding_text: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.embedder.model_name().as_bytes());
+        hasher.update(b"|");
+        hasher.update(embedding_text.as_bytes());
+        hasher.update(b"|");
+        hasher.update(guideline.raw_markdown.as_bytes());
+        format!("{:x}", hasher.finalize())
+    }
+
+    /// What the table's vectors are built from: the embedding model and the embedded
+    /// sections. Stored in the table's schema metadata under [`EMBEDDING_SETTINGS_KEY`].
+    fn embedding_settings(&self) -> String {
+        format!("{}|{}", self.embedder.model_name(), self.config.embedding_sections)
+    }
 
-        // 3. Generate embeddings (batched)
-        info!("generating embeddings for {} guidelines", guidelines.len());
-        let embeddings = self.embedder.embed_documents(&embedding_texts).await?;
+    /// Whether the table records the current [`Self::embedding_settings`]. Tables written
+    /// before the settings were recorded never match.
+    async fn settings_match(&self) -> bool {
+        let stored = self
+            .vectordb
+            .table_metadata(SearchEngine::table_name(), EMBEDDING_SETTINGS_KEY)
+            .await
+            .ok()
+            .flatten();
+        stored == Some(self.embedding_settings())
+    }
 
-        if embeddings.len() != guidelines.len() {
+    /// Whether the table can be updated in place: it holds exactly the previously indexed
+    /// rows at the current model's dimension and embedding settings, and the new ids are
+    /// unique.
+    async fn table_matches(
+        &self,
+        previous: &HashMap<String, String>,
+        hashes: &HashMap<String, String>,
+        guideline_count: usize,
+    ) -> bool {
+        let table = SearchEngine::table_name();
+        if hashes.len() != guideline_count {
+            return false;
+        }
+        let rows = self.vectordb.count_rows(table).await.ok();
+        let dim = self.vectordb.table_embedding_dim(table).await.ok();
+        rows == Some(previous.len())
+            && dim == Some(self.embedder.dimensions())
+            && self.settings_match().await
+    }
+
+    /// Embed `texts`, checking that the model returned one vector per text.
+    ///
+    /// Logs every further 10% of texts embedded and forwards each batch to `progress`.
+    async fn embed(
+        &self,
+        texts: &[String],
+        progress: Option<EmbedProgress<'_>>,
+    ) -> Result<Vec<Vec<f32>>, AppError> {
+        info!("generating embeddings for {} guidelines", texts.len());
+        let logged_decile = AtomicUsize::new(0);
+        let report = |done: usize, total: usize| {
+            let decile = done * 10 / total.max(1);
+            if logged_decile.swap(decile, Ordering::Relaxed) != decile {
+                info!(done, total, "embedding {}% complete", decile * 10);
+            }
+            if let Some(progress) = progress {
+                progress(done, total);
+            }
+        };
+        let embeddings = self
+            .embedder
+            .embed_documents_with_progress(texts, Some(&report))
+            .await?;
+        if embeddings.len() != texts.len() {
             return Err(AppError::Common(mcp_common::error::CommonError::Embedding(
                 format!(
                     "embedding count mismatch: expected {}, got {}",
-                    guidelines.len(),
+                    texts.len(),
                     embeddings.len()
                 ),
             )));
         }
+        Ok(embeddings)
+    }
 
-        // 4. Build Arrow RecordBatch for LanceDB
-        let batch = build_record_batch(&guidelines, &embedding_texts, &embeddings)?;
+    /// Embed every guideline into a fresh table, then build its vector index.
+    async fn rebuild_table(
+        &self,
+        guidelines: &[Guideline],
+        embedding_texts: &[String],
+        progress: Option<EmbedProgress<'_>>,
+    ) -> Result<(), AppError> {
+        let embeddings = self.embed(embedding_texts, progress).await?;
+        let batch = build_record_batch(
+            guidelines,
+            embedding_texts,
+            &embeddings,
+            self.embedder.dimensions(),
+            &self.embedding_settings(),
+        )?;
         let schema = batch.schema();
-
-        // 5. Create/replace LanceDB table
         self.vectordb
             .create_or_replace_table(SearchEngine::table_name(), schema, vec![batch])
             .await?;
+        self.build_index().await;
+        Ok(())
+    }
 
-        // 6. Invalidate all caches and repopulate
-        self.cache.invalidate_all().await;
-
-        // Cache individual guidelines
-        for g in &guidelines {
-            self.cache.set_guideline(g).await;
-        }
-
-        // Cache categories
-        let mut category_list: Vec<_> = categories.values().cloned().collect();
-        category_list.sort_by(|a, b| a.prefix.cmp(&b.prefix));
-        self.cache.set_categories(&category_list).await;
-
-        // Cache category→rule_id mappings
-        for prefix in categories.keys() {
-            let mut ids: Vec<String> = guidelines
-                .iter()
-                .filter(|g| &g.category == prefix)
-                .map(|g| g.id.clone())
-                .collect();
-            ids.sort();
-            self.cache.set_category_rule_ids(prefix, &ids).await;
+    /// Retrain the vector index over the table's current rows.
+    ///
+    /// Best-effort: without the index searches still work, just by full scan.
+    async fn build_index(&self) {
+        if let Err(e) = self
+            .vectordb
+            .create_index(SearchEngine::table_name(), "embedding", SearchEngine::distance_type())
+            .await
+        {
+            warn!(error = %e, "vector index creation failed, searches will scan the table");
         }
+    }
 
-        // Cache commit hash
-        self.cache.set_repo_commit(&current_commit).await;
-
+    /// Re-embed and upsert guidelines whose hash changed, and delete rows for guidelines
+    /// that no longer exist.
+    ///
+    /// The index is rebuilt after any change: rows upserted since it was trained are only
+    /// found by a flat scan of the unindexed part, and its partitions drift from the data,
+    /// so recall would fall behind a full re-index as updates accumulate.
+    async fn apply_changes(
+        &self,
+        guidelines: &[Guideline],
+        embedding_texts: &[String],
+        hashes: &HashMap<String, String>,
+        previous: &HashMap<String, String>,
+        progress: Option<EmbedProgress<'_>>,
+    ) -> Result<(), AppError> {
+        let table = SearchEngine::table_name();
+        let changed: Vec<usize> = (0..guidelines.len())
+            .filter(|&i| previous.get(&guidelines[i].id) != hashes.get(&guidelines[i].id))
+            .collect();
+        let removed: Vec<&String> =
+            previous.keys().filter(|id| !hashes.contains_key(*id)).collect();
         info!(
-            commit = %current_commit,
-            guidelines = guidelines.len(),
-            "re-index complete"
+            changed = changed.len(),
+            removed = removed.len(),
+            unchanged = guidelines.len() - changed.len(),
+            "incremental re-index"
         );
 
-        Ok((guidelines, categories, current_commit))
+        if !changed.is_empty() {
+            let changed_guidelines: Vec<Guideline> =
+                changed.iter().map(|&i| guidelines[i].clone()).collect();
+            let texts: Vec<String> =
+                changed.iter().map(|&i| embedding_texts[i].clone()).collect();
+            let embeddings = self.embed(&texts, progress).await?;
+            let batch = build_record_batch(
+                &changed_guidelines,
+                &texts,
+                &embeddings,
+                self.embedder.dimensions(),
+                &self.embedding_settings(),
+            )?;
+            let schema = batch.schema();
+            self.vectordb.upsert_batch(table, schema, vec![batch]).await?;
+        }
+        let modified = !changed.is_empty() || !removed.is_empty();
+        for id in removed {
+            self.vectordb.delete_by_id(table, id).await?;
+        }
+        if modified {
+            self.build_index().await;
+        }
+        Ok(())
     }
 
     /// Run a full update cycle: check if needed, then re-index if so.
+    ///
+    /// Requires the guard from [`Self::try_lock`], so concurrent calls cannot race on
+    /// dropping and recreating the table. `expected_rows` is forwarded to
+    /// [`Self::needs_update`], and `progress` receives per-batch embedding progress.
     pub async fn update(
         &self,
+        _lock: &ReindexGuard,
+        expected_rows: Option<usize>,
+        progress: Option<EmbedProgress<'_>>,
     ) -> Result<(UpdateResult, Option<(Vec<Guideline>, HashMap<String, Category>)>), AppError> {
         let current_commit = self.get_repo_commit()?;
 
-        if !self.needs_update().await? {
+        if !self.needs_update(expected_rows).await? {
             info!(commit = %current_commit, "guidelines up to date, skipping re-index");
             return Ok((
                 UpdateResult {
@@ -198,7 +439,7 @@ impl UpdateService {
             ));
         }
 
-        let (guidelines, categories, commit) = self.full_reindex().await?;
+        let (guidelines, categories, commit) = self.full_reindex(progress).await?;
         let count = guidelines.len();
 
         Ok((
@@ -212,13 +453,57 @@ impl UpdateService {
     }
 }
 
+/// Fetch the default branch of `origin` into the repository containing `repo_path`, then
+/// hard-reset the checkout to it. The branch is asked of the remote instead of read from
+/// `origin/HEAD`, which checkouts not made by `git clone` lack.
+fn pull_origin(repo_path: &str) -> Result<(), git2::Error> {
+    let repo = git2::Repository::discover(repo_path)?;
+    let mut remote = repo.find_remote("origin")?;
+    remote.connect(git2::Direction::Fetch)?;
+    let head = remote.default_branch()?;
+    remote.disconnect()?;
+    let branch = head
+        .as_str()
+        .and_then(|head| head.strip_prefix("refs/heads/"))
+        .ok_or_else(|| git2::Error::from_str("origin reports no default branch"))?
+        .to_string();
+    let tracking = format!("refs/remotes/origin/{branch}");
+    remote.fetch(&[format!("+refs/heads/{branch}:{tracking}")], None, None)?;
+    let target = repo.find_reference(&tracking)?.peel_to_commit()?;
+    repo.reset(target.as_object(), git2::ResetType::Hard, None)
+}
+
+/// Parse the guidelines file read from `path`.
+///
+/// A non-empty file with no recognizable rule headers is almost always the wrong format
+/// (e.g. an HTML export), so it is an error instead of an empty index.
+fn parse_rules(
+    path: &std::path::Path,
+    content: &str,
+) -> Result<(Vec<Guideline>, HashMap<String, Category>), AppError> {
+    let (guidelines, categories) = parser::parse_guidelines(content);
+    if guidelines.is_empty() && !content.trim().is_empty() {
+        return Err(AppError::Config(format!(
+            "{} is not empty but no rules were parsed; expected the markdown source with \
+             `### <a name=\"...\"></a>ID: Title` rule headers (is it an HTML export or \
+             another format?)",
+            path.display()
+        )));
+    }
+    Ok((guidelines, categories))
+}
+
 /// Build an Arrow RecordBatch from parsed guidelines and their embeddings.
+///
+/// `embedding_settings` is recorded in the schema metadata under [`EMBEDDING_SETTINGS_KEY`].
 fn build_record_batch(
     guidelines: &[Guideline],
     texts: &[String],
     embeddings: &[Vec<f32>],
+    embedding_dim: usize,
+    embedding_settings: &str,
 ) -> Result<RecordBatch, AppError> {
-    let embedding_dim = 768i32;
+    let embedding_dim = embedding_dim as i32;
 
     let ids: Vec<&str> = guidelines.iter().map(|g| g.id.as_str()).collect();
     let titles: Vec<&str> = guidelines.iter().map(|g| g.title.as_str()).collect();
@@ -257,7 +542,11 @@ fn build_record_batch(
             DataType::FixedSizeList(Arc::new(Field::new("item", DataType::Float32, true)), embedding_dim),
             false,
         ),
-    ]));
+    ])
+    .with_metadata(HashMap::from([(
+        EMBEDDING_SETTINGS_KEY.to_string(),
+        embedding_settings.to_string(),
+    )])));
 
     RecordBatch::try_new(
         schema,
@@ -275,3 +564,100 @@ fn build_record_batch(
         )))
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or(0);
+        std::env::temp_dir().join(format!("pull-{name}-{}-{nanos}", std::process::id()))
+    }
+
+    /// Commit `contents` as `file` on `branch`.
+    fn commit_file(repo: &git2::Repository, branch: &str, file: &str, contents: &str) {
+        std::fs::write(repo.workdir().unwrap().join(file), contents).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(std::path::Path::new(file)).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::now("test", "test@example.com").unwrap();
+        let reference = format!("refs/heads/{branch}");
+        let parent = repo.find_reference(&reference).ok().and_then(|r| r.peel_to_commit().ok());
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        repo.commit(Some(&reference), &signature, &signature, "update", &tree, &parents)
+            .unwrap();
+    }
+
+    #[test]
+    fn pull_follows_the_remote_default_branch_without_origin_head() {
+        let upstream_dir = temp_dir("upstream");
+        let upstream = git2::Repository::init(&upstream_dir).unwrap();
+        upstream.set_head("refs/heads/trunk").unwrap();
+        commit_file(&upstream, "trunk", "rules.md", "v1");
+
+        // A checkout made with `git init` + `git remote add` has no `origin/HEAD`.
+        let local_dir = temp_dir("local");
+        let local = git2::Repository::init(&local_dir).unwrap();
+        local.remote("origin", upstream_dir.to_str().unwrap()).unwrap();
+
+        commit_file(&upstream, "trunk", "rules.md", "v2");
+        pull_origin(local_dir.to_str().unwrap()).unwrap();
+        assert_eq!(std::fs::read_to_string(local_dir.join("rules.md")).unwrap(), "v2");
+
+        let _ = std::fs::remove_dir_all(&upstream_dir);
+        let _ = std::fs::remove_dir_all(&local_dir);
+    }
+
+    #[test]
+    fn content_without_rule_headers_is_refused() {
+        let path = std::path::Path::new("CppCoreGuidelines.md");
+        let html = "<html><body><h3 id=\"rp-direct\">P.1: Express ideas</h3></body></html>";
+        match parse_rules(path, html) {
+            Err(AppError::Config(message)) => {
+                assert!(message.contains("no rules were parsed"), "{message}");
+                assert!(message.contains("CppCoreGuidelines.md"), "{message}");
+            }
+            other => panic!("expected a config error, got {other:?}"),
+        }
+
+        // An empty file is not mistaken for the wrong format.
+        let (guidelines, _) = parse_rules(path, "  \n").unwrap();
+        assert!(guidelines.is_empty());
+
+        let markdown = "### <a name=\"rp-direct\"></a>P.1: Express ideas directly in code\n\n\
+                        ##### Reason\n\nSay what you mean.\n";
+        let (guidelines, _) = parse_rules(path, markdown).unwrap();
+        assert_eq!(guidelines.len(), 1);
+    }
+
+    #[test]
+    fn record_batches_carry_the_embedding_settings() {
+        let guideline = Guideline {
+            id: "P.1".to_string(),
+            anchor: "rp-direct".to_string(),
+            title: "Express ideas directly in code".to_string(),
+            category: "P".to_string(),
+            sections: Vec::new(),
+            raw_markdown: String::new(),
+            related: Vec::new(),
+            examples: Vec::new(),
+            enforcement: Default::default(),
+        };
+        let batch = build_record_batch(
+            &[guideline],
+            &["text".to_string()],
+            &[vec![0.5, 0.5]],
+            2,
+            "model-a",
+        )
+        .unwrap();
+        assert_eq!(
+            batch.schema().metadata().get(EMBEDDING_SETTINGS_KEY).map(String::as_str),
+            Some("model-a")
+        );
+    }
+}
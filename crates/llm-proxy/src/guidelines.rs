@@ -0,0 +1,164 @@
+/// MCP client for the guideline servers, used by `review_code`.
+///
+/// Each server is reached over its streamable HTTP endpoint (the server's `MCP_LISTEN_ADDR`)
+/// at the URL in its environment variable; servers without one are simply not consulted.
+/// Every search opens a fresh session, so a guideline server can restart or come up after
+/// the proxy without leaving a dead connection behind.
+use rmcp::model::CallToolRequestParams;
+use rmcp::transport::StreamableHttpClientTransport;
+use rmcp::ServiceExt;
+
+use mcp_common::mcp_api::{
+    GuidelineSearchResult, SearchGuidelinesParams, SearchGuidelinesResponse,
+};
+
+/// Environment variable holding the C++ Core Guidelines server URL.
+pub const CPP_GUIDELINES_URL_ENV: &str = "CPP_GUIDELINES_URL";
+/// Environment variable holding the Rust API Guidelines server URL.
+pub const RUST_GUIDELINES_URL_ENV: &str = "RUST_API_GUIDELINES_URL";
+/// Environment variable holding the Node.js best practices server URL.
+pub const NODEJS_GUIDELINES_URL_ENV: &str = "NODEJS_GUIDELINES_URL";
+
+/// Server name, URL variable, and the canonical language names (see [`crate::languages`])
+/// whose code it covers.
+const SERVERS: &[(&str, &str, &[&str])] = &[
+    ("cpp-guidelines", CPP_GUIDELINES_URL_ENV, &["C++", "C"]),
+    ("rust-api-guidelines", RUST_GUIDELINES_URL_ENV, &["Rust"]),
+    (
+        "nodejs-guidelines",
+        NODEJS_GUIDELINES_URL_ENV,
+        &["JavaScript", "TypeScript"],
+    ),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GuidelineServer {
+    pub name: &'static str,
+    languages: &'static [&'static str],
+    url: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct GuidelineClient {
+    servers: Vec<GuidelineServer>,
+}
+
+impl GuidelineClient {
+    /// Read the server URLs; unset or empty variables leave that server out.
+    pub fn from_env() -> Self {
+        Self::new(SERVERS.iter().filter_map(|(name, env, _)| {
+            let url = std::env::var(env).ok()?;
+            Some((*name, url))
+        }))
+    }
+
+    /// Servers by name (`cpp-guidelines`, `rust-api-guidelines`, `nodejs-guidelines`) and
+    /// URL; unknown names and empty URLs are ignored.
+    pub fn new<'a>(urls: impl IntoIterator<Item = (&'a str, String)>) -> Self {
+        let servers = urls
+            .into_iter()
+            .filter_map(|(name, url)| {
+                let url = url.trim();
+                if url.is_empty() {
+                    return None;
+                }
+                let (name, _, languages) = SERVERS.iter().find(|(known, ..)| *known == name)?;
+                Some(GuidelineServer {
+                    name,
+                    languages,
+                    url: url.to_string(),
+                })
+            })
+            .collect();
+        Self { servers }
+    }
+
+    /// Names of the configured servers.
+    pub fn server_names(&self) -> Vec<String> {
+        self.servers.iter().map(|s| s.name.to_string()).collect()
+    }
+
+    /// The configured server covering `language`, a canonical language name.
+    pub fn server_for(&self, language: &str) -> Option<&GuidelineServer> {
+        self.servers
+            .iter()
+            .find(|s| s.languages.contains(&language))
+    }
+
+    /// Call `search_guidelines` on `server` with `query` and return its top `limit` results.
+    pub async fn search(
+        &self,
+        server: &GuidelineServer,
+        query: &str,
+        limit: u32,
+    ) -> Result<Vec<GuidelineSearchResult>, String> {
+        let params = SearchGuidelinesParams {
+            query: query.to_string(),
+            limit: Some(limit),
+            min_score: None,
+            mode: None,
+            include_sections: None,
+            enforcement: None,
+        };
+        let arguments = match serde_json::to_value(params) {
+            Ok(serde_json::Value::Object(arguments)) => Some(arguments),
+            _ => None,
+        };
+
+        let client = ()
+            .serve(StreamableHttpClientTransport::from_uri(server.url.as_str()))
+            .await
+            .map_err(|e| format!("{}: connect failed: {e}", server.name))?;
+        let result = client
+            .call_tool(CallToolRequestParams {
+                meta: None,
+                name: "search_guidelines".into(),
+                arguments,
+                task: None,
+            })
+            .await;
+        let _ = client.cancel().await;
+        let result =
+            result.map_err(|e| format!("{}: search_guidelines failed: {e}", server.name))?;
+
+        if result.is_error == Some(true) {
+            let message = result
+                .content
+                .iter()
+                .filter_map(|c| c.as_text().map(|t| t.text.as_str()))
+                .collect::<Vec<_>>()
+                .join(" ");
+            return Err(format!(
+                "{}: search_guidelines failed: {message}",
+                server.name
+            ));
+        }
+        let structured = result.structured_content.ok_or_else(|| {
+            format!(
+                "{}: search_guidelines returned no results object",
+                server.name
+            )
+        })?;
+        serde_json::from_value::<SearchGuidelinesResponse>(structured)
+            .map(|response| response.results)
+            .map_err(|e| format!("{}: unexpected search_guidelines result: {e}", server.name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn languages_map_to_configured_servers() {
+        let client = GuidelineClient::new([
+            ("cpp-guidelines", "http://cpp-guidelines:7011".to_string()),
+            ("nodejs-guidelines", " ".to_string()),
+            ("go-guidelines", "http://go:7015".to_string()),
+        ]);
+        assert_eq!(client.server_names(), ["cpp-guidelines"]);
+        assert_eq!(client.server_for("C").unwrap().name, "cpp-guidelines");
+        assert!(client.server_for("TypeScript").is_none());
+        assert!(client.server_for("Rust").is_none());
+    }
+}
@@ -0,0 +1,142 @@
+/// Languages `generate_code` recognizes, with their common aliases and an idiom hint.
+///
+/// A recognized language is normalized to its canonical name (`cpp` becomes `C++`) and its
+/// hint is sent as a system message, so the model writes current, idiomatic code instead of
+/// whatever dialect it defaults to. Unrecognized languages are still sent as given.
+
+pub struct Language {
+    /// Canonical name used in the prompt.
+    pub name: &'static str,
+    /// Lowercase spellings accepted besides the name itself.
+    aliases: &'static [&'static str],
+    /// Idioms the generated code should follow.
+    pub hint: &'static str,
+}
+
+const LANGUAGES: &[Language] = &[
+    Language {
+        name: "C++",
+        aliases: &["cpp", "cxx", "cc", "c++20", "cpp20"],
+        hint: "Use modern C++20: RAII and smart pointers instead of owning raw pointers, \
+const-correctness, and standard library containers and algorithms. Avoid C-style casts and \
+manual new/delete.",
+    },
+    Language {
+        name: "C",
+        aliases: &["c17", "c11"],
+        hint: "Write portable C17. Check every allocation and return value, free what you \
+allocate, and avoid undefined behavior.",
+    },
+    Language {
+        name: "Rust",
+        aliases: &["rs"],
+        hint: "Write idiomatic stable Rust (2021 edition): return Result and use ? instead of \
+panicking, borrow rather than clone, and avoid unsafe.",
+    },
+    Language {
+        name: "JavaScript",
+        aliases: &["js", "node", "nodejs", "node.js", "ecmascript"],
+        hint: "Write modern JavaScript (ES2022 modules): const and let instead of var, \
+async/await instead of callbacks, and strict equality.",
+    },
+    Language {
+        name: "TypeScript",
+        aliases: &["ts"],
+        hint: "Write strict-mode TypeScript: explicit types on exported functions, no any, and \
+async/await for asynchronous code.",
+    },
+    Language {
+        name: "Python",
+        aliases: &["py", "python3"],
+        hint: "Write Python 3.11+ following PEP 8, with type hints, f-strings, and context \
+managers for resources.",
+    },
+    Language {
+        name: "Go",
+        aliases: &["golang"],
+        hint: "Write idiomatic, gofmt-formatted Go: return errors instead of panicking, wrap \
+them with context, and prefer the standard library.",
+    },
+    Language {
+        name: "Java",
+        aliases: &[],
+        hint: "Write Java 17: records where they fit, try-with-resources, Optional instead of \
+null returns, and the standard collections.",
+    },
+    Language {
+        name: "C#",
+        aliases: &["csharp", "cs"],
+        hint: "Write C# 12 for .NET 8 with nullable reference types enabled, async/await, and \
+using declarations for disposables.",
+    },
+    Language {
+        name: "Kotlin",
+        aliases: &["kt"],
+        hint: "Write idiomatic Kotlin: val over var, null safety instead of !!, data classes, \
+and coroutines for asynchronous work.",
+    },
+    Language {
+        name: "Swift",
+        aliases: &[],
+        hint: "Write Swift 5.9: value types where possible, guard/if let instead of force \
+unwraps, and async/await.",
+    },
+    Language {
+        name: "Ruby",
+        aliases: &["rb"],
+        hint: "Write idiomatic Ruby 3: blocks and Enumerable methods, frozen string literals, \
+and no monkey-patching.",
+    },
+    Language {
+        name: "Bash",
+        aliases: &["sh", "shell"],
+        hint: "Write a bash script that starts with set -euo pipefail, quotes every expansion, \
+and uses [[ ]] for tests.",
+    },
+    Language {
+        name: "SQL",
+        aliases: &[],
+        hint: "Write standard SQL: explicit JOINs, explicit column lists instead of SELECT *, \
+and bound parameters instead of interpolated values.",
+    },
+];
+
+/// The known language named by `language` (case-insensitive name or alias).
+pub fn lookup(language: &str) -> Option<&'static Language> {
+    let wanted = language.trim().to_lowercase();
+    LANGUAGES
+        .iter()
+        .find(|l| l.name.to_lowercase() == wanted || l.aliases.contains(&wanted.as_str()))
+}
+
+/// Canonical names of every known language, for error messages.
+pub fn known_names() -> Vec<&'static str> {
+    LANGUAGES.iter().map(|l| l.name).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aliases_resolve_to_canonical_names() {
+        assert_eq!(lookup("cpp").unwrap().name, "C++");
+        assert_eq!(lookup(" JS ").unwrap().name, "JavaScript");
+        assert_eq!(lookup("typescript").unwrap().name, "TypeScript");
+        assert_eq!(lookup("C#").unwrap().name, "C#");
+        assert!(lookup("pyton").is_none());
+    }
+
+    #[test]
+    fn names_and_aliases_are_unique() {
+        let mut seen = std::collections::HashSet::new();
+        for language in LANGUAGES {
+            assert!(seen.insert(language.name.to_lowercase()), "{}", language.name);
+            for alias in language.aliases {
+                assert_eq!(*alias, alias.to_lowercase(), "aliases must be lowercase");
+                assert!(seen.insert(alias.to_string()), "duplicate alias {alias}");
+            }
+        }
+        assert_eq!(known_names().len(), LANGUAGES.len());
+    }
+}
@@ -1,19 +1,27 @@
+mod guidelines;
+mod languages;
+mod model_cache;
 mod rate_limit;
 mod server;
+mod telemetry;
+mod tool_error;
 
 use std::sync::Arc;
 
 use rmcp::{ServiceExt, transport::stdio};
 use rmcp::transport::streamable_http_server::{
-    StreamableHttpService, session::local::LocalSessionManager,
+    StreamableHttpServerConfig, StreamableHttpService, session::local::LocalSessionManager,
 };
 use tokio::net::TcpListener;
 use tracing::info;
 use tracing_subscriber::EnvFilter;
+use tracing_subscriber::fmt::format::FmtSpan;
 
 use mcp_common::llm_state::{ConversationStore, UsageTracker};
 use mcp_common::openai::{OpenAiClient, OpenAiClientConfig};
 use mcp_common::redis::RedisCache;
+use mcp_common::shutdown::shutdown_signal;
+use mcp_common::tool_timeout::ToolTimeout;
 
 use server::LlmProxyServer;
 
@@ -25,6 +33,7 @@ async fn main() -> anyhow::Result<()> {
         )
         .with_writer(std::io::stderr)
         .with_ansi(false)
+        .with_span_events(FmtSpan::CLOSE)
         .init();
 
     info!("starting llm-proxy MCP server");
@@ -49,28 +58,54 @@ async fn main() -> anyhow::Result<()> {
     let convos = ConversationStore::new(RedisCache::new(redis_url.as_deref()));
     let usage = UsageTracker::new(RedisCache::new(redis_url.as_deref()));
 
-    let limiter = rate_limit::RateLimiter::from_env();
+    let limiter = rate_limit::RateLimiter::from_env(RedisCache::new(redis_url.as_deref()));
 
-    let server = LlmProxyServer::new(openai, convos, usage, limiter);
+    let tool_timeout = ToolTimeout::from_env().map_err(anyhow::Error::msg)?;
+    let models = model_cache::ModelCache::from_env();
+    let guidelines = guidelines::GuidelineClient::from_env();
+    let server = LlmProxyServer::new(
+        openai,
+        convos,
+        usage,
+        limiter,
+        models,
+        guidelines,
+        tool_timeout,
+    );
 
     if let Ok(addr) = std::env::var("MCP_LISTEN_ADDR") {
+        // Cancelling the service's token on shutdown ends open SSE streams; otherwise a
+        // connected client would keep the graceful shutdown from ever finishing.
+        let config = StreamableHttpServerConfig::default();
+        let cancel = config.cancellation_token.clone();
         let server_for_factory = server.clone();
         let http_service = StreamableHttpService::new(
             move || Ok(server_for_factory.clone()),
             LocalSessionManager::default().into(),
-            Default::default(),
+            config,
         );
-        let router = axum::Router::new().fallback_service(http_service);
+        let metrics = telemetry::install()?;
+        let router = telemetry::router(metrics).fallback_service(http_service);
         let listener = TcpListener::bind(&addr).await?;
         info!(listen_addr = %addr, "MCP server ready, serving HTTP/SSE");
-        axum::serve(listener, router).await?;
+        axum::serve(listener, router)
+            .with_graceful_shutdown(async move {
+                shutdown_signal().await;
+                cancel.cancel();
+            })
+            .await?;
     } else {
         info!("MCP server ready, serving on stdio");
         let service = server.serve(stdio()).await.inspect_err(|e| {
             tracing::error!(error = %e, "MCP server error");
         })?;
+        let cancel = service.cancellation_token();
+        tokio::spawn(async move {
+            shutdown_signal().await;
+            cancel.cancel();
+        });
         service.waiting().await?;
-        info!("MCP server shut down");
     }
+    info!("MCP server shut down");
     Ok(())
 }
@@ -0,0 +1,170 @@
+/// In-process copy of the upstream model list, from `MODELS_CACHE_TTL_SECS`.
+///
+/// The models a local host serves rarely change, so `list_models` answers from here while
+/// the stored list is younger than the TTL instead of calling `GET /v1/models` every time.
+/// Each replica keeps its own copy; a stale list lasts at most one TTL.
+///
+/// With `VALIDATE_MODEL=1` the same list is used to reject unknown model ids before a chat
+/// or embedding request is sent, instead of failing upstream after the full retry cycle.
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use mcp_common::openai::ModelListResponse;
+
+/// Environment variable holding the TTL in whole seconds.
+pub const MODELS_CACHE_TTL_ENV: &str = "MODELS_CACHE_TTL_SECS";
+
+/// Environment variable that turns on model id validation (`1` or `true`).
+pub const VALIDATE_MODEL_ENV: &str = "VALIDATE_MODEL";
+
+/// TTL used when `MODELS_CACHE_TTL_SECS` is unset or invalid.
+const DEFAULT_TTL: Duration = Duration::from_secs(60);
+
+#[derive(Clone)]
+pub struct ModelCache {
+    /// Zero disables caching.
+    ttl: Duration,
+    /// Check requested model ids against the list before calling upstream.
+    validate: bool,
+    entry: Arc<Mutex<Option<(Instant, ModelListResponse)>>>,
+}
+
+impl ModelCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            validate: false,
+            entry: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Read `MODELS_CACHE_TTL_SECS` (default 60; `0` disables caching) and `VALIDATE_MODEL`
+    /// (off by default, since some gateways accept aliases missing from `/v1/models`).
+    pub fn from_env() -> Self {
+        let ttl = std::env::var(MODELS_CACHE_TTL_ENV)
+            .ok()
+            .and_then(|s| s.trim().parse::<u64>().ok())
+            .map_or(DEFAULT_TTL, Duration::from_secs);
+        let validate = matches!(
+            std::env::var(VALIDATE_MODEL_ENV)
+                .ok()
+                .as_deref()
+                .map(str::trim),
+            Some("1" | "true")
+        );
+        Self::new(ttl).with_validation(validate)
+    }
+
+    pub fn with_validation(mut self, validate: bool) -> Self {
+        self.validate = validate;
+        self
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    pub fn validates(&self) -> bool {
+        self.validate
+    }
+
+    /// The stored list, if it is younger than the TTL.
+    pub fn get(&self) -> Option<ModelListResponse> {
+        let entry = self.entry.lock().unwrap_or_else(|e| e.into_inner());
+        entry
+            .as_ref()
+            .filter(|(stored, _)| stored.elapsed() < self.ttl)
+            .map(|(_, models)| models.clone())
+    }
+
+    /// Whether `model` is in the last stored list, however old.
+    pub fn is_listed(&self, model: &str) -> bool {
+        let entry = self.entry.lock().unwrap_or_else(|e| e.into_inner());
+        entry
+            .as_ref()
+            .is_some_and(|(_, models)| models.data.iter().any(|m| m.id == model))
+    }
+
+    /// Store a freshly fetched list; a no-op when caching is disabled.
+    pub fn set(&self, models: &ModelListResponse) {
+        if self.ttl.is_zero() {
+            return;
+        }
+        let mut entry = self.entry.lock().unwrap_or_else(|e| e.into_inner());
+        *entry = Some((Instant::now(), models.clone()));
+    }
+}
+
+/// Fail with the available ids when `model` is not in `models`.
+pub fn check_model(models: &ModelListResponse, model: &str) -> Result<(), String> {
+    if models.data.iter().any(|m| m.id == model) {
+        return Ok(());
+    }
+    let ids: Vec<&str> = models.data.iter().map(|m| m.id.as_str()).collect();
+    Err(format!(
+        "unknown model: {model}. Available models: {}",
+        if ids.is_empty() {
+            "none".to_string()
+        } else {
+            ids.join(", ")
+        }
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn models(ids: &[&str]) -> ModelListResponse {
+        serde_json::from_value(serde_json::json!({
+            "object": "list",
+            "data": ids.iter().map(|id| serde_json::json!({ "id": id })).collect::<Vec<_>>(),
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn serves_the_stored_list_until_it_expires() {
+        let cache = ModelCache::new(Duration::from_millis(20));
+        assert!(cache.get().is_none());
+
+        cache.set(&models(&["llama3:8b"]));
+        assert_eq!(cache.get().unwrap().data[0].id, "llama3:8b");
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(cache.get().is_none());
+    }
+
+    #[test]
+    fn unknown_models_list_the_available_ids() {
+        let list = models(&["llama3:8b", "nomic-embed-text"]);
+        assert!(check_model(&list, "llama3:8b").is_ok());
+        assert_eq!(
+            check_model(&list, "llama3:7b").unwrap_err(),
+            "unknown model: llama3:7b. Available models: llama3:8b, nomic-embed-text"
+        );
+        assert_eq!(
+            check_model(&models(&[]), "llama3:8b").unwrap_err(),
+            "unknown model: llama3:8b. Available models: none"
+        );
+    }
+
+    #[test]
+    fn listed_models_outlive_the_ttl() {
+        let cache = ModelCache::new(Duration::from_millis(20));
+        assert!(!cache.is_listed("llama3:8b"));
+
+        cache.set(&models(&["llama3:8b"]));
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(cache.get().is_none());
+        assert!(cache.is_listed("llama3:8b"));
+        assert!(!cache.is_listed("llama3:7b"));
+    }
+
+    #[test]
+    fn zero_ttl_never_stores() {
+        let cache = ModelCache::new(Duration::ZERO);
+        cache.set(&models(&["llama3:8b"]));
+        assert!(cache.get().is_none());
+    }
+}
@@ -1,9 +1,38 @@
+use std::collections::{BTreeMap, HashMap};
 use std::time::{Duration, Instant};
 
+use mcp_common::redis::RedisCache;
 use tokio::sync::Mutex;
+use tracing::warn;
+
+/// Default cap on how long `RateLimitMode::Wait` blocks a single call.
+const DEFAULT_MAX_WAIT_MS: u64 = 5_000;
+
+/// What happens to a call that arrives when no token is available.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitMode {
+    /// Fail the call immediately with a retry hint.
+    Reject,
+    /// Sleep until a token frees up, failing only once the wait would exceed `max_wait`.
+    Wait,
+}
 
 #[derive(Clone)]
 pub struct RateLimiter {
+    /// Bucket for models without their own entry; `None` leaves them unlimited.
+    global: Option<Bucket>,
+    per_model: HashMap<String, Bucket>,
+    mode: RateLimitMode,
+    max_wait: Duration,
+    /// Shared bucket store so all replicas draw from one allowance; the in-process buckets
+    /// are used when this is `None` or Redis is unreachable.
+    redis: Option<RedisCache>,
+}
+
+#[derive(Clone)]
+struct Bucket {
+    /// Redis key of the shared bucket.
+    key: String,
     rps: u32,
     state: std::sync::Arc<Mutex<State>>,
 }
@@ -15,22 +44,156 @@ struct State {
 }
 
 impl RateLimiter {
-    pub fn from_env() -> Option<Self> {
+    /// Reads `RATE_LIMIT_RPS` (unset or 0 disables the global bucket),
+    /// `RATE_LIMIT_RPS_PER_MODEL` (`model:rps` pairs separated by commas),
+    /// `RATE_LIMIT_MODE` (`reject` or `wait`, default `reject`),
+    /// `RATE_LIMIT_MAX_WAIT_MS` (default 5000) and `RATE_LIMIT_BACKEND` (`local` or `redis`,
+    /// default `local`). Returns `None` when no limit is configured.
+    pub fn from_env(redis: RedisCache) -> Option<Self> {
         let rps = std::env::var("RATE_LIMIT_RPS")
             .ok()
             .and_then(|s| s.parse::<u32>().ok())
-            .filter(|&n| n > 0)?;
+            .filter(|&n| n > 0);
+
+        let per_model = std::env::var("RATE_LIMIT_RPS_PER_MODEL")
+            .ok()
+            .map(|s| parse_per_model(&s))
+            .unwrap_or_default();
+
+        if rps.is_none() && per_model.is_empty() {
+            return None;
+        }
+
+        let mode = match std::env::var("RATE_LIMIT_MODE").ok().as_deref().map(str::trim) {
+            Some(m) if m.eq_ignore_ascii_case("wait") => RateLimitMode::Wait,
+            _ => RateLimitMode::Reject,
+        };
+
+        let max_wait = std::env::var("RATE_LIMIT_MAX_WAIT_MS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .map(Duration::from_millis)
+            .unwrap_or_else(|| Duration::from_millis(DEFAULT_MAX_WAIT_MS));
+
+        let limiter = Self::new(rps, per_model, mode, max_wait);
+        match std::env::var("RATE_LIMIT_BACKEND").ok().as_deref().map(str::trim) {
+            Some(b) if b.eq_ignore_ascii_case("redis") => Some(limiter.with_redis(redis)),
+            _ => Some(limiter),
+        }
+    }
+
+    pub fn new(
+        rps: Option<u32>,
+        per_model: HashMap<String, u32>,
+        mode: RateLimitMode,
+        max_wait: Duration,
+    ) -> Self {
+        Self {
+            global: rps.map(|rps| Bucket::new("llm_proxy:ratelimit:global".to_string(), rps)),
+            per_model: per_model
+                .into_iter()
+                .map(|(model, rps)| {
+                    let key = format!("llm_proxy:ratelimit:model:{model}");
+                    (model, Bucket::new(key, rps))
+                })
+                .collect(),
+            mode,
+            max_wait,
+            redis: None,
+        }
+    }
+
+    /// Keep bucket state in Redis so the limit holds across replicas.
+    pub fn with_redis(mut self, redis: RedisCache) -> Self {
+        self.redis = Some(redis);
+        self
+    }
+
+    /// Where bucket state lives: `redis` or `local`.
+    pub fn backend(&self) -> &'static str {
+        if self.redis.is_some() {
+            "redis"
+        } else {
+            "local"
+        }
+    }
+
+    /// Configured global requests per second, if any.
+    pub fn rps(&self) -> Option<u32> {
+        self.global.as_ref().map(|b| b.rps)
+    }
+
+    /// Configured per-model requests per second, sorted by model.
+    pub fn per_model_rps(&self) -> BTreeMap<String, u32> {
+        self.per_model
+            .iter()
+            .map(|(model, bucket)| (model.clone(), bucket.rps))
+            .collect()
+    }
+
+    /// Take a token from the bucket for `model` (or the global bucket for unlisted models and
+    /// calls not tied to a model), either rejecting or waiting when none is available.
+    pub async fn check(&self, model: Option<&str>) -> Result<(), String> {
+        let per_model = model.and_then(|m| self.per_model.get_key_value(m));
+        let (bucket, source) = match (per_model, &self.global) {
+            (Some((model, bucket)), _) => {
+                (bucket, format!("RATE_LIMIT_RPS_PER_MODEL {model}={}", bucket.rps))
+            }
+            (None, Some(bucket)) => (bucket, format!("RATE_LIMIT_RPS={}", bucket.rps)),
+            (None, None) => return Ok(()),
+        };
+
+        let deadline = Instant::now() + self.max_wait;
+        loop {
+            let wait = match self.acquire(bucket).await {
+                Ok(()) => return Ok(()),
+                Err(wait) => wait,
+            };
+
+            if self.mode == RateLimitMode::Reject {
+                return Err(format!(
+                    "rate limit exceeded ({source}): try again in ~{}ms",
+                    wait.as_millis()
+                ));
+            }
+            if Instant::now() + wait > deadline {
+                return Err(format!(
+                    "rate limit exceeded ({source}): no capacity within {}ms",
+                    self.max_wait.as_millis()
+                ));
+            }
+            tokio::time::sleep(wait).await;
+        }
+    }
 
-        Some(Self {
+    /// Take a token from the shared Redis bucket, falling back to the in-process one when
+    /// Redis is not configured or unreachable.
+    async fn acquire(&self, bucket: &Bucket) -> Result<(), Duration> {
+        if let Some(redis) = &self.redis {
+            match redis.take_token(&bucket.key, bucket.rps).await {
+                Some(0) => return Ok(()),
+                Some(wait_ms) => return Err(Duration::from_millis(wait_ms)),
+                None => {}
+            }
+        }
+        bucket.try_acquire().await
+    }
+}
+
+impl Bucket {
+    fn new(key: String, rps: u32) -> Self {
+        Self {
+            key,
             rps,
             state: std::sync::Arc::new(Mutex::new(State {
                 tokens: rps as f64,
                 last: Instant::now(),
             })),
-        })
+        }
     }
 
-    pub async fn check(&self) -> Result<(), String> {
+    /// Take a token if one is available; otherwise return how long until one will be.
+    async fn try_acquire(&self) -> Result<(), Duration> {
         let mut state = self.state.lock().await;
         let now = Instant::now();
         let elapsed = now.duration_since(state.last);
@@ -44,12 +207,131 @@ impl RateLimiter {
             return Ok(());
         }
 
-        let wait = Duration::from_secs_f64((1.0 - state.tokens) / self.rps as f64);
-        Err(format!(
-            "rate limit exceeded (RATE_LIMIT_RPS={}): try again in ~{}ms",
-            self.rps,
-            wait.as_millis()
-        ))
+        Err(Duration::from_secs_f64((1.0 - state.tokens) / self.rps as f64))
+    }
+}
+
+/// Parse `model:rps` pairs separated by commas. The rate follows the last `:` so model ids
+/// with tags (`llama3:8b:5`) work. Malformed or zero entries are skipped with a warning.
+fn parse_per_model(value: &str) -> HashMap<String, u32> {
+    let mut limits = HashMap::new();
+    for entry in value.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let parsed = entry.rsplit_once(':').and_then(|(model, rps)| {
+            let model = model.trim();
+            let rps = rps.trim().parse::<u32>().ok().filter(|&n| n > 0)?;
+            (!model.is_empty()).then(|| (model.to_string(), rps))
+        });
+        match parsed {
+            Some((model, rps)) => {
+                limits.insert(model, rps);
+            }
+            None => warn!(entry, "ignoring malformed RATE_LIMIT_RPS_PER_MODEL entry"),
+        }
     }
+    limits
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn reject_mode_fails_when_exhausted() {
+        let limiter = RateLimiter::new(Some(1), HashMap::new(), RateLimitMode::Reject, Duration::from_secs(5));
+        assert!(limiter.check(None).await.is_ok());
+        let err = limiter.check(None).await.unwrap_err();
+        assert!(err.contains("try again"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn wait_mode_waits_for_a_token() {
+        let limiter = RateLimiter::new(Some(20), HashMap::new(), RateLimitMode::Wait, Duration::from_secs(1));
+        for _ in 0..20 {
+            limiter.check(None).await.unwrap();
+        }
+        let start = Instant::now();
+        limiter.check(None).await.unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(20));
+    }
+
+    #[tokio::test]
+    async fn wait_mode_gives_up_past_max_wait() {
+        let limiter = RateLimiter::new(Some(1), HashMap::new(), RateLimitMode::Wait, Duration::from_millis(10));
+        assert!(limiter.check(None).await.is_ok());
+        let err = limiter.check(None).await.unwrap_err();
+        assert!(err.contains("no capacity within 10ms"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn per_model_buckets_are_independent_of_global() {
+        let per_model = HashMap::from([("big:70b".to_string(), 1)]);
+        let limiter =
+            RateLimiter::new(Some(5), per_model, RateLimitMode::Reject, Duration::from_secs(5));
+
+        assert!(limiter.check(Some("big:70b")).await.is_ok());
+        let err = limiter.check(Some("big:70b")).await.unwrap_err();
+        assert!(err.contains("RATE_LIMIT_RPS_PER_MODEL big:70b=1"), "{err}");
+
+        // Unlisted models and model-less calls share the global bucket.
+        for _ in 0..3 {
+            limiter.check(Some("tiny")).await.unwrap();
+        }
+        limiter.check(None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn unlisted_models_are_unlimited_without_global() {
+        let per_model = HashMap::from([("big".to_string(), 1)]);
+        let limiter = RateLimiter::new(None, per_model, RateLimitMode::Reject, Duration::ZERO);
+        for _ in 0..10 {
+            limiter.check(Some("tiny")).await.unwrap();
+        }
+    }
+
+    #[test]
+    fn parses_per_model_limits() {
+        let limits = parse_per_model(" llama3:5, qwen:1 ,llama3:8b:2,bad,zero:0,:3,");
+        assert_eq!(
+            limits,
+            HashMap::from([
+                ("llama3".to_string(), 5),
+                ("qwen".to_string(), 1),
+                ("llama3:8b".to_string(), 2),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn redis_backend_falls_back_to_local_bucket() {
+        let limiter =
+            RateLimiter::new(Some(1), HashMap::new(), RateLimitMode::Reject, Duration::ZERO)
+                .with_redis(RedisCache::new(None));
+        assert_eq!(limiter.backend(), "redis");
+        assert!(limiter.check(None).await.is_ok());
+        assert!(limiter.check(None).await.is_err());
+    }
+
+    /// Two limiters sharing Redis behave like one. Needs a live Redis (`REDIS_URL`).
+    #[tokio::test]
+    async fn redis_backend_shares_allowance_across_instances() {
+        let Ok(url) = std::env::var("REDIS_URL") else {
+            return;
+        };
+        let redis = RedisCache::new(Some(&url));
+        if !redis.is_available().await {
+            return;
+        }
+        let model = format!("test-{}", std::process::id());
+        let per_model = HashMap::from([(model.clone(), 2)]);
+        let make = || {
+            RateLimiter::new(None, per_model.clone(), RateLimitMode::Reject, Duration::ZERO)
+                .with_redis(redis.clone())
+        };
+        let (a, b) = (make(), make());
+
+        assert!(a.check(Some(&model)).await.is_ok());
+        assert!(b.check(Some(&model)).await.is_ok());
+        assert!(a.check(Some(&model)).await.is_err());
+        assert!(b.check(Some(&model)).await.is_err());
+    }
+}
@@ -1,19 +1,43 @@
+use std::collections::BTreeMap;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use rmcp::{
-    Json, ServerHandler,
+    Json, Peer, RoleServer, ServerHandler,
+    service::RequestContext,
     handler::server::router::tool::ToolRouter,
+    handler::server::tool::ToolCallContext,
     handler::server::wrapper::Parameters,
     model::*,
-    tool, tool_handler, tool_router,
+    tool, tool_router,
 };
 use schemars::JsonSchema;
 use serde::Deserialize;
+use tracing::field::Empty;
+use tracing::warn;
 
-use mcp_common::llm_state::{ConversationId, ConversationStore, UsageStats, UsageTracker};
-use mcp_common::openai::{ChatCompletionRequest, Message, ModelListResponse, OpenAiClient};
+use mcp_common::llm_state::{
+    ConversationId, ConversationList, ConversationSnapshot, ConversationStore, UsageStats,
+    UsageTracker,
+};
+use mcp_common::mcp_api::GuidelineSearchResult;
+use mcp_common::openai::{
+    ChatCompletionMessage, ChatCompletionRequest, ChatCompletionUsage, EmbeddingRequest, Message,
+    ModelListResponse, OpenAiClient, ResponseFormat, ToolCall, ToolDefinition,
+};
+use mcp_common::spans::{input_bytes, record_cache};
+use mcp_common::tokens;
+use mcp_common::tool_timeout::ToolTimeout;
 
+use crate::guidelines::GuidelineClient;
+use crate::languages;
+use crate::model_cache::{self, ModelCache};
 use crate::rate_limit::RateLimiter;
+use crate::telemetry;
+use crate::tool_error::ToolError;
+
+/// How long `health_check` waits for the upstream before reporting it down.
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
 
 #[derive(Clone)]
 pub struct LlmProxyServer {
@@ -21,6 +45,9 @@ pub struct LlmProxyServer {
     convos: ConversationStore,
     usage: UsageTracker,
     limiter: Option<RateLimiter>,
+    models: ModelCache,
+    guidelines: GuidelineClient,
+    tool_timeout: ToolTimeout,
     tool_router: ToolRouter<LlmProxyServer>,
 }
 
@@ -30,79 +57,519 @@ impl LlmProxyServer {
         convos: ConversationStore,
         usage: UsageTracker,
         limiter: Option<RateLimiter>,
+        models: ModelCache,
+        guidelines: GuidelineClient,
+        tool_timeout: ToolTimeout,
     ) -> Self {
         Self {
             openai,
             convos,
             usage,
             limiter,
+            models,
+            guidelines,
+            tool_timeout,
             tool_router: Self::tool_router(),
         }
     }
 
-    async fn gate(&self) -> Result<(), String> {
+    async fn gate(&self, model: Option<&str>) -> Result<(), ToolError> {
         if let Some(limiter) = &self.limiter {
-            limiter.check().await?;
+            limiter
+                .check(model)
+                .await
+                .inspect_err(|_| {
+                    telemetry::record_rate_limited(model.map(|m| self.metric_label(m)))
+                })?;
         }
         Ok(())
     }
 
-    async fn run_chat(&self, model: &str, messages: Vec<Message>) -> Result<String, String> {
-        self.gate().await?;
+    /// `model` as a metric label: its own name when the upstream lists it or
+    /// `MODEL_PRICING` prices it, [`telemetry::OTHER_MODEL`] otherwise.
+    fn metric_label<'a>(&self, model: &'a str) -> &'a str {
+        if self.models.is_listed(model) || self.usage.has_pricing(model) {
+            model
+        } else {
+            telemetry::OTHER_MODEL
+        }
+    }
 
-        let request = ChatCompletionRequest {
-            model: model.to_string(),
-            messages,
-            temperature: None,
-            max_tokens: None,
-            stream: None,
+    /// Fetch the model list from upstream and store it in the cache. Without `retry` a
+    /// failed fetch returns at once instead of going through the client's retries.
+    async fn fetch_models(&self, retry: bool) -> Result<ModelListResponse, ToolError> {
+        let models = if retry {
+            self.openai.list_models().await
+        } else {
+            self.openai.list_models_once().await
         };
-        let response = self
-            .openai
-            .chat_completions(request, None)
-            .await
-            .map_err(|e| format!("chat failed: {e}"))?;
+        let models = models.map_err(|e| ToolError::upstream("list_models failed", &e))?;
+        self.models.set(&models);
+        Ok(models)
+    }
 
-        let text = response
+    /// Take the rate-limit token for a request to `model`, then validate the model id.
+    ///
+    /// A model-list fetch on a validation cache miss rides on the request's own token, so
+    /// it is limited without charging the caller twice.
+    async fn admit(&self, model: &str) -> Result<(), ToolError> {
+        self.gate(Some(model)).await?;
+        self.check_model(model).await
+    }
+
+    /// With `VALIDATE_MODEL` on, reject a model id the upstream host does not list. When one
+    /// attempt can't fetch the list the request goes ahead and fails (or not) upstream.
+    async fn check_model(&self, model: &str) -> Result<(), ToolError> {
+        if !self.models.validates() {
+            return Ok(());
+        }
+        let models = match self.models.get() {
+            Some(models) => models,
+            None => match self.fetch_models(false).await {
+                Ok(models) => models,
+                Err(e) => {
+                    warn!(error = %e, model, "model validation skipped");
+                    return Ok(());
+                }
+            },
+        };
+        model_cache::check_model(&models, model).map_err(ToolError::from)
+    }
+
+    /// Send a chat completion and return the first choice's message.
+    async fn complete(
+        &self,
+        request: ChatCompletionRequest,
+    ) -> Result<ChatCompletionMessage, ToolError> {
+        self.admit(&request.model).await?;
+
+        let model = request.model.clone();
+        let response = self.openai.chat_completions(request, None).await;
+        telemetry::record_chat(self.metric_label(&model), response.is_ok());
+        let response = response.map_err(|e| ToolError::upstream("chat failed", &e))?;
+
+        telemetry::record_tokens(self.metric_label(&model), response.usage.as_ref());
+        self.usage.record(&model, response.usage.as_ref()).await;
+        response
             .choices
-            .get(0)
-            .and_then(|c| c.message.content.as_ref())
-            .map(|s| s.to_string())
-            .ok_or_else(|| "chat failed: missing choices[0].message.content".to_string())?;
+            .into_iter()
+            .next()
+            .map(|c| c.message)
+            .ok_or_else(|| ToolError::from("chat failed: missing choices[0]"))
+    }
+
+    async fn run_chat(&self, request: ChatCompletionRequest) -> Result<String, ToolError> {
+        self.complete(request)
+            .await?
+            .content
+            .ok_or_else(|| ToolError::from("chat failed: missing choices[0].message.content"))
+    }
 
-        self.usage.record(model, response.usage.as_ref()).await;
+    /// Like `run_chat`, but streams the completion and forwards each content piece to the
+    /// client as a progress notification carrying the piece in `message`.
+    async fn run_chat_streaming(
+        &self,
+        mut request: ChatCompletionRequest,
+        peer: Peer<RoleServer>,
+        progress_token: ProgressToken,
+    ) -> Result<String, ToolError> {
+        self.admit(&request.model).await?;
+
+        let model = request.model.clone();
+        request.stream = Some(true);
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<String>();
+        let completion = self.openai.chat_completions_streaming(request, None, move |piece| {
+            let _ = tx.send(piece.to_string());
+        });
+        let forward = async {
+            let mut progress = 0.0;
+            while let Some(piece) = rx.recv().await {
+                progress += 1.0;
+                let param = ProgressNotificationParam {
+                    progress_token: progress_token.clone(),
+                    progress,
+                    total: None,
+                    message: Some(piece),
+                };
+                if let Err(e) = peer.notify_progress(param).await {
+                    warn!(error = %e, "failed to send progress notification");
+                }
+            }
+        };
+        let (result, ()) = tokio::join!(completion, forward);
+        telemetry::record_chat(self.metric_label(&model), result.is_ok());
+        let text = result.map_err(|e| ToolError::upstream("chat failed", &e))?;
+
+        // Streamed responses carry no usage block; count the request only.
+        self.usage.record(&model, None).await;
         Ok(text)
     }
 }
 
-#[derive(Debug, Deserialize, JsonSchema)]
+/// The model named by a raw chat body. Streaming bodies are refused: the reply would be SSE,
+/// not the JSON object the tool returns.
+fn raw_chat_model(body: &serde_json::Value) -> Result<String, String> {
+    let Some(body) = body.as_object() else {
+        return Err("body must be a JSON object".to_string());
+    };
+    let model = body
+        .get("model")
+        .and_then(|m| m.as_str())
+        .map(str::trim)
+        .filter(|m| !m.is_empty())
+        .ok_or("body.model must be a non-empty string")?;
+    if body.get("stream").and_then(|s| s.as_bool()) == Some(true) {
+        return Err("body.stream is not supported by raw_chat".to_string());
+    }
+    Ok(model.to_string())
+}
+
+/// Best-effort read of the `usage` block of a raw chat response.
+fn raw_chat_usage(response: &serde_json::Value) -> Option<ChatCompletionUsage> {
+    serde_json::from_value(response.get("usage")?.clone()).ok()
+}
+
+fn chat_request(
+    model: &str,
+    messages: Vec<Message>,
+    options: SamplingOptions,
+) -> ChatCompletionRequest {
+    ChatCompletionRequest {
+        model: model.to_string(),
+        messages,
+        temperature: options.temperature,
+        max_tokens: options.max_tokens,
+        top_p: options.top_p,
+        stop: options.stop,
+        frequency_penalty: options.frequency_penalty,
+        presence_penalty: options.presence_penalty,
+        stream: None,
+        tools: None,
+        tool_choice: None,
+        response_format: None,
+    }
+}
+
+/// Request JSON output from the model when `json` is set.
+fn apply_json_mode(request: &mut ChatCompletionRequest, json: Option<bool>) {
+    if json == Some(true) {
+        request.response_format = Some(ResponseFormat::JsonObject);
+    }
+}
+
+/// Fail fast when JSON mode was requested but the model's reply doesn't parse.
+fn check_json_reply(text: &str) -> Result<(), String> {
+    serde_json::from_str::<serde_json::Value>(text)
+        .map(|_| ())
+        .map_err(|e| format!("model returned invalid JSON: {e}"))
+}
+
+/// The code inside `text` when the whole reply is one fenced block (```` ```lang ... ``` ````),
+/// without the fence lines; any other reply is returned unchanged.
+fn strip_code_fences(text: &str) -> &str {
+    let Some(rest) = text.trim().strip_prefix("```") else {
+        return text;
+    };
+    let Some((info, body)) = rest.split_once('\n') else {
+        return text;
+    };
+    if info.contains('`') {
+        return text;
+    }
+    let Some(body) = body.strip_suffix("```") else {
+        return text;
+    };
+    if !(body.is_empty() || body.ends_with('\n')) {
+        return text;
+    }
+    if body.lines().any(|line| line.trim_start().starts_with("```")) {
+        return text;
+    }
+    body.trim_end_matches(['\n', '\r'])
+}
+
+/// Number of guidelines folded into a `review_code` system prompt.
+const REVIEW_RULE_COUNT: u32 = 5;
+
+/// System prompt for `review_code`, listing the guidelines that best match the code.
+fn review_system_prompt(language: &str, rules: &[GuidelineSearchResult]) -> String {
+    let mut prompt = format!(
+        "You are an expert {language} code reviewer. Review the code the user sends for \
+correctness, safety, performance and idiomatic style. For each finding, quote the code, explain \
+the problem and suggest a fix. Order findings by severity and say so plainly if the code looks \
+fine."
+    );
+    if !rules.is_empty() {
+        prompt.push_str(
+            " Cite the guideline ID for every finding that one of these guidelines covers.\n\n\
+Guidelines that look most relevant:\n",
+        );
+        for rule in rules {
+            prompt.push_str(&format!("- {}: {}", rule.id, rule.title));
+            let summary = rule.summary.trim();
+            if !summary.is_empty() {
+                prompt.push_str(&format!(" ({summary})"));
+            }
+            prompt.push('\n');
+        }
+    }
+    prompt
+}
+
+/// `code` in a fenced block tagged with `language`. The fence is one backtick longer than
+/// the longest run of backticks in the code (and at least three), so fences inside the code
+/// cannot close it early.
+fn fence_code(language: &str, code: &str) -> String {
+    let longest = code.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let fence = "`".repeat(longest.max(2) + 1);
+    format!("{fence}{}\n{code}\n{fence}", language.to_lowercase())
+}
+
+/// Heading that introduces the summary `summarize_conversation` folds into the system message.
+const SUMMARY_HEADING: &str = "Summary of the conversation so far:";
+
+/// Split a conversation into its original system prompt, the summary left by an earlier
+/// `summarize_conversation` (if any), and the messages after the leading system message.
+fn split_summary(messages: &[Message]) -> (Option<&str>, Option<&str>, &[Message]) {
+    let (system, rest) = match messages.split_first() {
+        Some((first, rest)) if first.role == "system" => (first.content.as_str(), rest),
+        _ => return (None, None, messages),
+    };
+    match system.split_once(SUMMARY_HEADING) {
+        Some((prompt, summary)) => {
+            let prompt = prompt.trim();
+            ((!prompt.is_empty()).then_some(prompt), Some(summary.trim()), rest)
+        }
+        None => (Some(system), None, rest),
+    }
+}
+
+/// The instruction asking a model to summarize `messages`, building on `previous` when the
+/// conversation has been summarized before.
+fn summary_prompt(previous: Option<&str>, messages: &[Message]) -> String {
+    let mut prompt = String::from(
+        "Summarize the conversation below concisely. Keep the facts, decisions and open \
+questions needed to continue it. Reply with the summary only.\n\n",
+    );
+    if let Some(previous) = previous {
+        prompt.push_str(&format!("EARLIER SUMMARY:\n{previous}\n\n"));
+    }
+    prompt.push_str("CONVERSATION:\n");
+    for message in messages {
+        prompt.push_str(&format!("{}: {}", message.role, message.content));
+        let calls: Vec<&str> = message
+            .tool_calls
+            .iter()
+            .flatten()
+            .map(|call| call.function.name.as_str())
+            .collect();
+        if !calls.is_empty() {
+            prompt.push_str(&format!(" [called: {}]", calls.join(", ")));
+        }
+        prompt.push('\n');
+    }
+    prompt
+}
+
+/// The single system message that replaces a summarized history.
+fn summary_message(system_prompt: Option<&str>, summary: &str) -> Message {
+    let content = match system_prompt {
+        Some(prompt) => format!("{prompt}\n\n{SUMMARY_HEADING}\n{summary}"),
+        None => format!("{SUMMARY_HEADING}\n{summary}"),
+    };
+    Message::new("system", content)
+}
+
+#[derive(Debug, Default, serde::Serialize, Deserialize, JsonSchema)]
+struct ListModelsParams {
+    /// Fetch the list from the upstream host even if a cached copy is still fresh.
+    refresh: Option<bool>,
+}
+
+#[derive(Debug, serde::Serialize, Deserialize, JsonSchema)]
 struct AskModelParams {
+    /// Model ID as reported by `list_models`, such as "llama3:8b".
     model: String,
+    /// User prompt, sent as a single user message.
     prompt: String,
+    /// Stream partial output as MCP progress notifications while the model generates.
+    /// Requires the request to carry a progress token; otherwise the reply is returned
+    /// only once complete.
+    #[serde(default)]
+    stream: bool,
+    #[serde(flatten)]
+    sampling: SamplingOptions,
+    /// Ask the model for a JSON object (`response_format: json_object`). The reply must
+    /// parse as JSON or the call fails.
+    json: Option<bool>,
 }
 
-#[derive(Debug, Deserialize, JsonSchema)]
+#[derive(Debug, serde::Serialize, Deserialize, JsonSchema)]
 struct ChatModelParams {
+    /// Model ID as reported by `list_models`, such as "llama3:8b".
     model: String,
+    /// OpenAI-style messages, e.g. `[{ "role": "user", "content": "Hi" }]`.
     messages: Vec<Message>,
+    #[serde(flatten)]
+    sampling: SamplingOptions,
+    /// Tools the model may call, in the OpenAI `tools` format.
+    tools: Option<Vec<ToolDefinition>>,
+    /// "none", "auto", "required", or `{ "type": "function", "function": { "name": ... } }`.
+    tool_choice: Option<serde_json::Value>,
+    /// Ask the model for a JSON object (`response_format: json_object`). The reply must
+    /// parse as JSON or the call fails.
+    json: Option<bool>,
+}
+
+/// Optional sampling controls forwarded to the upstream chat completion.
+#[derive(Debug, Clone, Default, serde::Serialize, Deserialize, JsonSchema)]
+struct SamplingOptions {
+    /// Sampling temperature (0.0 to 2.0). Lower values are more deterministic.
+    temperature: Option<f32>,
+    /// Maximum number of tokens to generate.
+    max_tokens: Option<u32>,
+    /// Nucleus sampling: only tokens within this cumulative probability (0.0 to 1.0) are
+    /// considered.
+    top_p: Option<f32>,
+    /// Up to 4 sequences at which generation stops, such as "```" to end after a code block.
+    stop: Option<Vec<String>>,
+    /// Penalty (-2.0 to 2.0) on tokens in proportion to how often they already appeared.
+    frequency_penalty: Option<f32>,
+    /// Penalty (-2.0 to 2.0) on tokens that already appeared at all.
+    presence_penalty: Option<f32>,
+}
+
+/// Most stop sequences the OpenAI API accepts per request.
+const MAX_STOP_SEQUENCES: usize = 4;
+
+impl SamplingOptions {
+    fn validate(&self) -> Result<(), String> {
+        check_range("temperature", self.temperature, 0.0, 2.0)?;
+        if self.max_tokens == Some(0) {
+            return Err("max_tokens must be greater than 0".to_string());
+        }
+        check_range("top_p", self.top_p, 0.0, 1.0)?;
+        check_range("frequency_penalty", self.frequency_penalty, -2.0, 2.0)?;
+        check_range("presence_penalty", self.presence_penalty, -2.0, 2.0)?;
+        if let Some(stop) = &self.stop {
+            if stop.is_empty() || stop.len() > MAX_STOP_SEQUENCES {
+                return Err(format!(
+                    "stop must list between 1 and {MAX_STOP_SEQUENCES} sequences"
+                ));
+            }
+            if stop.iter().any(|s| s.is_empty()) {
+                return Err("stop sequences must not be empty".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+fn check_range(name: &str, value: Option<f32>, min: f32, max: f32) -> Result<(), String> {
+    match value {
+        Some(v) if !(min..=max).contains(&v) => {
+            Err(format!("{name} must be between {min:.1} and {max:.1}"))
+        }
+        _ => Ok(()),
+    }
 }
 
-#[derive(Debug, Deserialize, JsonSchema)]
+#[derive(Debug, serde::Serialize, Deserialize, JsonSchema)]
+struct RawChatParams {
+    /// Request body for `POST /v1/chat/completions`, forwarded unchanged. Must be an object
+    /// with a `model`; any other OpenAI fields (`seed`, `logit_bias`, `stop`, ...) are passed
+    /// through. `stream: true` is not supported.
+    body: serde_json::Value,
+}
+
+#[derive(Debug, serde::Serialize, Deserialize, JsonSchema)]
+struct EmbedTextParams {
+    /// Embedding model ID as reported by `list_models`.
+    model: String,
+    /// Texts to embed; one vector is returned per input, in the same order.
+    input: Vec<String>,
+}
+
+#[derive(Debug, serde::Serialize, Deserialize, JsonSchema)]
+struct EstimateTokensParams {
+    /// Model the prompt is intended for (echoed back; the heuristic is model-independent).
+    model: String,
+    /// Plain prompt text. Provide either `text` or `messages`.
+    text: Option<String>,
+    /// Chat messages, counted with per-message framing overhead.
+    messages: Option<Vec<Message>>,
+}
+
+#[derive(Debug, serde::Serialize, Deserialize, JsonSchema)]
+struct ReviewCodeParams {
+    /// Code to review; also used as the query for the guidelines included in the prompt.
+    code: String,
+    /// Language of the code, such as "cpp", "rust" or "typescript".
+    language: String,
+    /// Model ID as reported by `list_models`, such as "llama3:8b".
+    model: String,
+}
+
+#[derive(Debug, serde::Serialize, Deserialize, JsonSchema)]
 struct GenerateCodeParams {
+    /// What the code should do, such as "parse an ISO 8601 date into a struct".
     specification: String,
+    /// Target language, such as "rust" or "typescript". Common aliases ("cpp", "js", "py")
+    /// are recognized and get language-specific idiom guidance.
     language: String,
+    /// Fail on languages not in the known list instead of sending them as given. Defaults to
+    /// false.
+    strict_language: Option<bool>,
+    /// Model ID as reported by `list_models`, such as "llama3:8b".
     model: String,
+    #[serde(flatten)]
+    sampling: SamplingOptions,
+    /// Unwrap a reply that is a single fenced code block, dropping the fence and language
+    /// line. Defaults to true.
+    strip_fences: Option<bool>,
+}
+
+#[derive(Debug, serde::Serialize, Deserialize, JsonSchema)]
+struct StartConversationParams {
+    /// Optional system prompt, stored as the first message of the conversation.
+    #[serde(default)]
+    system: Option<String>,
 }
 
-#[derive(Debug, Deserialize, JsonSchema)]
+#[derive(Debug, serde::Serialize, Deserialize, JsonSchema)]
 struct ContinueConversationParams {
+    /// ID returned by `start_conversation`.
     conversation_id: ConversationId,
+    /// Model ID as reported by `list_models`, such as "llama3:8b".
     model: String,
+    /// Next user prompt, appended to the stored history.
     prompt: String,
 }
 
-#[derive(Debug, Deserialize, JsonSchema)]
+#[derive(Debug, serde::Serialize, Deserialize, JsonSchema)]
+struct ForkConversationParams {
+    /// ID of the conversation to copy; it is left unchanged.
+    conversation_id: ConversationId,
+}
+
+#[derive(Debug, serde::Serialize, Deserialize, JsonSchema)]
+struct SummarizeConversationParams {
+    /// ID returned by `start_conversation`.
+    conversation_id: ConversationId,
+    /// Model that writes the summary, as reported by `list_models`.
+    model: String,
+}
+
+#[derive(Debug, serde::Serialize, Deserialize, JsonSchema)]
 struct EndConversationParams {
+    /// ID returned by `start_conversation`.
+    conversation_id: ConversationId,
+}
+
+#[derive(Debug, serde::Serialize, Deserialize, JsonSchema)]
+struct GetConversationParams {
+    /// ID returned by `start_conversation`.
     conversation_id: ConversationId,
 }
 
@@ -111,89 +578,333 @@ struct StartConversationResponse {
     conversation_id: ConversationId,
 }
 
+#[derive(Debug, serde::Serialize, JsonSchema)]
+struct GetConversationResponse {
+    conversation_id: ConversationId,
+    /// Incremented on every stored turn.
+    version: u64,
+    messages: Vec<Message>,
+}
+
+#[derive(Debug, serde::Serialize, JsonSchema)]
+struct SummarizeConversationResponse {
+    conversation_id: ConversationId,
+    summary: String,
+    /// Number of messages (after the system prompt) folded into the summary.
+    summarized_messages: usize,
+}
+
+#[derive(Debug, serde::Serialize, JsonSchema)]
+struct ReviewCodeResponse {
+    /// The model's review.
+    text: String,
+    /// Guideline server consulted, such as "cpp-guidelines"; `None` when none is configured
+    /// for the language or it could not be reached.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    guidelines_server: Option<String>,
+    /// Guidelines included in the prompt, best match first.
+    rules: Vec<ReviewRule>,
+}
+
+#[derive(Debug, serde::Serialize, JsonSchema)]
+struct ReviewRule {
+    id: String,
+    title: String,
+}
+
 #[derive(Debug, serde::Serialize, JsonSchema)]
 struct TextResponse {
     text: String,
 }
 
+#[derive(Debug, serde::Serialize, JsonSchema)]
+struct EmbedTextResponse {
+    model: String,
+    embeddings: Vec<Vec<f32>>,
+}
+
+#[derive(Debug, serde::Serialize, JsonSchema)]
+struct ChatModelResponse {
+    /// Assistant text; empty when the model only requested tool calls.
+    text: String,
+    /// Tool calls requested by the model. Run them, append the results as `tool` messages
+    /// (with `tool_call_id`), and call `chat_model` again to continue.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_calls: Option<Vec<ToolCall>>,
+}
+
+#[derive(Debug, serde::Serialize, JsonSchema)]
+struct RawChatResponse {
+    /// The upstream response body, unchanged.
+    response: serde_json::Value,
+}
+
+#[derive(Debug, serde::Serialize, JsonSchema)]
+struct EstimateTokensResponse {
+    model: String,
+    tokens: usize,
+    /// `true` only when counted with the model's tokenizer; heuristic counts are `false`.
+    exact: bool,
+    /// How the count was produced, e.g. "chars_div_4".
+    method: String,
+}
+
 #[derive(Debug, serde::Serialize, JsonSchema)]
 struct OkResponse {
     ok: bool,
 }
 
+/// Sanitized view of the proxy's effective configuration.
+#[derive(Debug, serde::Serialize, JsonSchema)]
+struct ConfigInfoResponse {
+    /// Upstream base URL with any credentials stripped.
+    base_url: String,
+    timeout_ms: u64,
+    max_retries: u32,
+    retry_initial_backoff_ms: u64,
+    retry_max_backoff_ms: u64,
+    /// `exponential`, `equal_jitter` or `full_jitter` (`OPENAI_BACKOFF_STRATEGY`).
+    retry_backoff_strategy: String,
+    max_error_body_bytes: usize,
+    stream_max_buffer_bytes: usize,
+    /// Whether `OPENAI_API_KEY` is set. The key itself is never returned.
+    api_key_configured: bool,
+    organization: Option<String>,
+    /// Names of `OPENAI_EXTRA_HEADERS` entries, sorted; values are never returned.
+    extra_header_names: Vec<String>,
+    proxy_configured: bool,
+    /// 0 when the circuit breaker is disabled.
+    circuit_breaker_failures: u32,
+    circuit_breaker_cooldown_ms: u64,
+    /// `None` when there is no global rate limit.
+    rate_limit_rps: Option<u32>,
+    /// Per-model overrides from `RATE_LIMIT_RPS_PER_MODEL`.
+    rate_limit_rps_per_model: BTreeMap<String, u32>,
+    /// `redis` (shared across replicas) or `local`; `None` when rate limiting is disabled.
+    rate_limit_backend: Option<String>,
+    /// `None` when conversation history is unbounded.
+    convo_max_messages: Option<usize>,
+    /// How long `list_models` serves a cached list; 0 when caching is disabled.
+    models_cache_ttl_secs: u64,
+    /// Whether model ids are checked against `list_models` before chat and embedding calls.
+    validate_model: bool,
+    /// Guideline servers `review_code` can consult, by name.
+    guideline_servers: Vec<String>,
+    /// `None` when tool calls are unbounded (`TOOL_TIMEOUT_SECS` unset or 0).
+    tool_timeout_secs: Option<u64>,
+    redis_available: bool,
+}
+
+#[derive(Debug, serde::Serialize, JsonSchema)]
+struct HealthCheckResponse {
+    /// `GET /v1/models` on the upstream host succeeded.
+    upstream_ok: bool,
+    redis_ok: bool,
+    /// Round-trip time of the upstream probe, including any retries.
+    latency_ms: u64,
+}
+
 #[tool_router]
 impl LlmProxyServer {
-    #[tool(description = "List models available from the local OpenAI-compatible host (GET /v1/models).")]
-    async fn list_models(&self) -> Result<Json<ModelListResponse>, String> {
-        self.gate().await?;
-        let models = self
-            .openai
-            .list_models()
-            .await
-            .map_err(|e| format!("list_models failed: {e}"))?;
-        Ok(Json(models))
+    #[tool(description = "List models available from the local OpenAI-compatible host (GET /v1/models). The list is cached for MODELS_CACHE_TTL_SECS; set refresh=true to fetch it again.")]
+    #[tracing::instrument(name = "tool", skip_all, fields(tool = "list_models", input_bytes = input_bytes(&params), cache = Empty))]
+    async fn list_models(
+        &self,
+        Parameters(params): Parameters<ListModelsParams>,
+    ) -> Result<Json<ModelListResponse>, ToolError> {
+        if params.refresh != Some(true) {
+            let cached = self.models.get();
+            record_cache(cached.is_some());
+            if let Some(models) = cached {
+                return Ok(Json(models));
+            }
+        }
+
+        self.gate(None).await?;
+        Ok(Json(self.fetch_models(true).await?))
     }
 
-    #[tool(description = "Run a single-turn prompt against a chosen local model ID (POST /v1/chat/completions). Returns the final assistant text.")]
+    #[tool(description = "Run a single-turn prompt against a chosen local model ID (POST /v1/chat/completions). Returns the final assistant text. Set stream=true and send a progress token to receive partial text as progress notifications while it generates.")]
+    #[tracing::instrument(name = "tool", skip_all, fields(tool = "ask_model", input_bytes = input_bytes(&params)))]
     async fn ask_model(
         &self,
         Parameters(params): Parameters<AskModelParams>,
-    ) -> Result<Json<TextResponse>, String> {
+        meta: Meta,
+        peer: Peer<RoleServer>,
+    ) -> Result<Json<TextResponse>, ToolError> {
         let prompt = params.prompt.trim().to_string();
         if prompt.is_empty() {
-            return Err("prompt must not be empty".to_string());
+            return Err("prompt must not be empty".into());
         }
         let model = params.model.trim().to_string();
         if model.is_empty() {
-            return Err("model must not be empty".to_string());
-        }
-        let reply = self
-            .run_chat(
-                &model,
-                vec![Message {
-                    role: "user".to_string(),
-                    content: prompt,
-                }],
-            )
-            .await?;
+            return Err("model must not be empty".into());
+        }
+        params.sampling.validate()?;
+        let mut request = chat_request(&model, vec![Message::new("user", prompt)], params.sampling);
+        apply_json_mode(&mut request, params.json);
+        let reply = match meta.get_progress_token() {
+            Some(token) if params.stream => self.run_chat_streaming(request, peer, token).await?,
+            _ => self.run_chat(request).await?,
+        };
+        if params.json == Some(true) {
+            check_json_reply(&reply)?;
+        }
         Ok(Json(TextResponse { text: reply }))
     }
 
-    #[tool(description = "Run a multi-message chat against a chosen local model ID (POST /v1/chat/completions). Returns the final assistant text.")]
+    #[tool(description = "Run a multi-message chat against a chosen local model ID (POST /v1/chat/completions). Returns the final assistant text. Pass OpenAI-style tools/tool_choice to let the model request tool calls; they are returned in tool_calls.")]
+    #[tracing::instrument(name = "tool", skip_all, fields(tool = "chat_model", input_bytes = input_bytes(&params)))]
     async fn chat_model(
         &self,
         Parameters(params): Parameters<ChatModelParams>,
-    ) -> Result<Json<TextResponse>, String> {
+    ) -> Result<Json<ChatModelResponse>, ToolError> {
         let model = params.model.trim().to_string();
         if model.is_empty() {
-            return Err("model must not be empty".to_string());
+            return Err("model must not be empty".into());
         }
         if params.messages.is_empty() {
-            return Err("messages must not be empty".to_string());
+            return Err("messages must not be empty".into());
         }
-        let reply = self.run_chat(&model, params.messages).await?;
-        Ok(Json(TextResponse { text: reply }))
+        params.sampling.validate()?;
+        if params.tool_choice.is_some() && params.tools.as_ref().is_none_or(Vec::is_empty) {
+            return Err("tool_choice requires tools".into());
+        }
+
+        let mut request = chat_request(&model, params.messages, params.sampling);
+        request.tools = params.tools;
+        request.tool_choice = params.tool_choice;
+        apply_json_mode(&mut request, params.json);
+        let message = self.complete(request).await?;
+        if message.content.is_none() && message.tool_calls.is_none() {
+            return Err("chat failed: missing choices[0].message.content".into());
+        }
+        if params.json == Some(true) && message.tool_calls.is_none() {
+            check_json_reply(message.content.as_deref().unwrap_or_default())?;
+        }
+
+        Ok(Json(ChatModelResponse {
+            text: message.content.unwrap_or_default(),
+            tool_calls: message.tool_calls,
+        }))
+    }
+
+    #[tool(description = "Escape hatch: forward an arbitrary JSON body to POST /v1/chat/completions and return the upstream JSON response unchanged. Use it for OpenAI parameters the other tools do not expose (seed, logit_bias, ...). Rate limits and usage tracking still apply; streaming is not supported.")]
+    #[tracing::instrument(name = "tool", skip_all, fields(tool = "raw_chat", input_bytes = input_bytes(&params)))]
+    async fn raw_chat(
+        &self,
+        Parameters(params): Parameters<RawChatParams>,
+    ) -> Result<Json<RawChatResponse>, ToolError> {
+        let model = raw_chat_model(&params.body)?;
+        self.admit(&model).await?;
+
+        let response = self.openai.chat_completions_raw(params.body, None).await;
+        telemetry::record_chat(self.metric_label(&model), response.is_ok());
+        let response = response.map_err(|e| ToolError::upstream("raw_chat failed", &e))?;
+
+        let usage = raw_chat_usage(&response);
+        telemetry::record_tokens(self.metric_label(&model), usage.as_ref());
+        self.usage.record(&model, usage.as_ref()).await;
+        Ok(Json(RawChatResponse { response }))
     }
 
-    #[tool(description = "Generate code for a given specification. The caller chooses the model. Returns code-only output unless the specification explicitly asks otherwise.")]
+    #[tool(description = "Compute embeddings for a list of texts with a chosen local model ID (POST /v1/embeddings). Returns one vector per input, in input order.")]
+    #[tracing::instrument(name = "tool", skip_all, fields(tool = "embed_text", input_bytes = input_bytes(&params)))]
+    async fn embed_text(
+        &self,
+        Parameters(params): Parameters<EmbedTextParams>,
+    ) -> Result<Json<EmbedTextResponse>, ToolError> {
+        let model = params.model.trim().to_string();
+        if model.is_empty() {
+            return Err("model must not be empty".into());
+        }
+        if params.input.is_empty() {
+            return Err("input must not be empty".into());
+        }
+        let input_count = params.input.len();
+
+        self.admit(&model).await?;
+        let mut response = self
+            .openai
+            .embeddings(EmbeddingRequest {
+                model: model.clone(),
+                input: params.input,
+            })
+            .await
+            .map_err(|e| ToolError::upstream("embed_text failed", &e))?;
+
+        if response.data.len() != input_count {
+            return Err(format!(
+                "embed_text failed: expected {input_count} embeddings, got {}",
+                response.data.len()
+            )
+            .into());
+        }
+        response.data.sort_by_key(|d| d.index);
+
+        self.usage.record(&model, response.usage.as_ref()).await;
+        Ok(Json(EmbedTextResponse {
+            model,
+            embeddings: response.data.into_iter().map(|d| d.embedding).collect(),
+        }))
+    }
+
+    #[tool(description = "Estimate the prompt token count of text or a message list before sending it to a model. Reports whether the count is exact or a heuristic estimate.")]
+    #[tracing::instrument(name = "tool", skip_all, fields(tool = "estimate_tokens", input_bytes = input_bytes(&params)))]
+    async fn estimate_tokens(
+        &self,
+        Parameters(params): Parameters<EstimateTokensParams>,
+    ) -> Result<Json<EstimateTokensResponse>, ToolError> {
+        let model = params.model.trim().to_string();
+        if model.is_empty() {
+            return Err("model must not be empty".into());
+        }
+        let tokens = match (params.text, params.messages) {
+            (Some(text), None) => tokens::estimate_text_tokens(&text),
+            (None, Some(messages)) => tokens::estimate_message_tokens(&messages),
+            _ => return Err("provide exactly one of text or messages".into()),
+        };
+        Ok(Json(EstimateTokensResponse {
+            model,
+            tokens,
+            exact: false,
+            method: tokens::HEURISTIC_METHOD.to_string(),
+        }))
+    }
+
+    #[tool(description = "Generate code for a given specification. The caller chooses the model. Returns code-only output unless the specification explicitly asks otherwise; a reply wrapped in a single fenced code block is unwrapped unless strip_fences=false.")]
+    #[tracing::instrument(name = "tool", skip_all, fields(tool = "generate_code", input_bytes = input_bytes(&params)))]
     async fn generate_code(
         &self,
         Parameters(params): Parameters<GenerateCodeParams>,
-    ) -> Result<Json<TextResponse>, String> {
+    ) -> Result<Json<TextResponse>, ToolError> {
         let model = params.model.trim().to_string();
         if model.is_empty() {
-            return Err("model must not be empty".to_string());
+            return Err("model must not be empty".into());
         }
 
         let language = params.language.trim().to_string();
         if language.is_empty() {
-            return Err("language must not be empty".to_string());
+            return Err("language must not be empty".into());
         }
+        let known = languages::lookup(&language);
+        if known.is_none() {
+            if params.strict_language == Some(true) {
+                return Err(format!(
+                    "unknown language: {language}. Known languages: {}",
+                    languages::known_names().join(", ")
+                )
+                .into());
+            }
+            warn!(language, "unknown generate_code language, sending as given");
+        }
+        let language = known.map_or(language, |l| l.name.to_string());
 
         let specification = params.specification.trim().to_string();
         if specification.is_empty() {
-            return Err("specification must not be empty".to_string());
+            return Err("specification must not be empty".into());
         }
+        params.sampling.validate()?;
 
         let instruction = format!(
             "Write complete, properly formatted {language} code to satisfy the specification. \
@@ -201,78 +912,297 @@ Return only the code (no explanation) unless the specification explicitly reques
 SPECIFICATION:\n{specification}"
         );
 
-        let reply = self
-            .run_chat(
-                &model,
-                vec![Message {
-                    role: "user".to_string(),
-                    content: instruction,
-                }],
-            )
-            .await?;
+        let mut messages = Vec::with_capacity(2);
+        if let Some(known) = known {
+            messages.push(Message::new(
+                "system",
+                format!("You are an expert {} programmer. {}", known.name, known.hint),
+            ));
+        }
+        messages.push(Message::new("user", instruction));
+        let request = chat_request(&model, messages, params.sampling);
+        let reply = self.run_chat(request).await?;
+        if params.strip_fences.unwrap_or(true) {
+            return Ok(Json(TextResponse {
+                text: strip_code_fences(&reply).to_string(),
+            }));
+        }
         Ok(Json(TextResponse { text: reply }))
     }
 
-    #[tool(description = "Start a Redis-backed conversation and return a conversation_id.")]
-    async fn start_conversation(&self) -> Result<Json<StartConversationResponse>, String> {
-        let id = self.convos.start().await;
+    #[tool(description = "Review a code snippet with the chosen model. For C++, C, Rust, JavaScript and TypeScript, the best-matching rules from the configured guideline server (C++ Core Guidelines, Rust API Guidelines, Node.js best practices) are added to the prompt so findings cite rule IDs. Returns the review and the rules used.")]
+    #[tracing::instrument(name = "tool", skip_all, fields(tool = "review_code", input_bytes = input_bytes(&params)))]
+    async fn review_code(
+        &self,
+        Parameters(params): Parameters<ReviewCodeParams>,
+    ) -> Result<Json<ReviewCodeResponse>, ToolError> {
+        let model = params.model.trim().to_string();
+        if model.is_empty() {
+            return Err("model must not be empty".into());
+        }
+        let code = params.code.trim();
+        if code.is_empty() {
+            return Err("code must not be empty".into());
+        }
+        let language = params.language.trim();
+        if language.is_empty() {
+            return Err("language must not be empty".into());
+        }
+        let language = languages::lookup(language).map_or(language, |l| l.name);
+
+        let mut guidelines_server = None;
+        let mut rules = Vec::new();
+        if let Some(server) = self.guidelines.server_for(language) {
+            match self.guidelines.search(server, code, REVIEW_RULE_COUNT).await {
+                Ok(found) => {
+                    guidelines_server = Some(server.name.to_string());
+                    rules = found;
+                }
+                Err(e) => warn!(error = %e, "guideline search failed, reviewing without rules"),
+            }
+        }
+
+        let messages = vec![
+            Message::new("system", review_system_prompt(language, &rules)),
+            Message::new("user", fence_code(language, code)),
+        ];
+        let request = chat_request(&model, messages, SamplingOptions::default());
+        let text = self.run_chat(request).await?;
+        Ok(Json(ReviewCodeResponse {
+            text,
+            guidelines_server,
+            rules: rules
+                .into_iter()
+                .map(|r| ReviewRule {
+                    id: r.id,
+                    title: r.title,
+                })
+                .collect(),
+        }))
+    }
+
+    #[tool(description = "Start a Redis-backed conversation and return a conversation_id. An optional system prompt is stored as the first message and kept for every later turn.")]
+    #[tracing::instrument(name = "tool", skip_all, fields(tool = "start_conversation", input_bytes = input_bytes(&params)))]
+    async fn start_conversation(
+        &self,
+        Parameters(params): Parameters<StartConversationParams>,
+    ) -> Result<Json<StartConversationResponse>, ToolError> {
+        let id = self.convos.start(params.system.as_deref()).await;
         Ok(Json(StartConversationResponse { conversation_id: id }))
     }
 
-    #[tool(description = "Continue a Redis-backed conversation by appending a user prompt, calling the chosen model, appending the assistant reply, and returning the reply text.")]
+    #[tool(description = "Continue a Redis-backed conversation by appending a user prompt, calling the chosen model, appending the assistant reply, and returning the reply text. Fails with a conflict error if another call updated the same conversation concurrently. History is bounded: when CONVO_MAX_MESSAGES is set, only the system prompt and the most recent messages up to that cap are kept.")]
+    #[tracing::instrument(name = "tool", skip_all, fields(tool = "continue_conversation", input_bytes = input_bytes(&params)))]
     async fn continue_conversation(
         &self,
         Parameters(params): Parameters<ContinueConversationParams>,
-    ) -> Result<Json<TextResponse>, String> {
+    ) -> Result<Json<TextResponse>, ToolError> {
         let model = params.model.trim().to_string();
         if model.is_empty() {
-            return Err("model must not be empty".to_string());
+            return Err("model must not be empty".into());
         }
         let prompt = params.prompt.trim().to_string();
         if prompt.is_empty() {
-            return Err("prompt must not be empty".to_string());
+            return Err("prompt must not be empty".into());
         }
 
-        let mut messages = self
+        let ConversationSnapshot {
+            version,
+            mut messages,
+        } = self
             .convos
-            .get_messages(&params.conversation_id)
+            .load(&params.conversation_id)
             .await
             .ok_or_else(|| format!("unknown conversation_id: {}", params.conversation_id))?;
-        messages.push(Message {
-            role: "user".to_string(),
-            content: prompt,
-        });
+        messages.push(Message::new("user", prompt));
 
-        let reply = self.run_chat(&model, messages.clone()).await?;
+        let request = chat_request(&model, messages.clone(), SamplingOptions::default());
+        let reply = self.run_chat(request).await?;
 
-        messages.push(Message {
-            role: "assistant".to_string(),
-            content: reply.clone(),
-        });
-        if !self.convos.set_messages(&params.conversation_id, &messages).await {
-            return Err("failed to persist conversation state".to_string());
-        }
+        // Rejects the turn if another call appended to this conversation while the model ran.
+        messages.push(Message::new("assistant", reply.clone()));
+        self.convos
+            .set_messages(&params.conversation_id, version, &messages)
+            .await
+            .map_err(|e| e.to_string())?;
 
         Ok(Json(TextResponse { text: reply }))
     }
 
+    #[tool(description = "Get the stored message history of a Redis-backed conversation, including its system prompt.")]
+    #[tracing::instrument(name = "tool", skip_all, fields(tool = "get_conversation", input_bytes = input_bytes(&params)))]
+    async fn get_conversation(
+        &self,
+        Parameters(params): Parameters<GetConversationParams>,
+    ) -> Result<Json<GetConversationResponse>, ToolError> {
+        let snapshot = self
+            .convos
+            .load(&params.conversation_id)
+            .await
+            .ok_or_else(|| format!("unknown conversation_id: {}", params.conversation_id))?;
+        Ok(Json(GetConversationResponse {
+            conversation_id: params.conversation_id,
+            version: snapshot.version,
+            messages: snapshot.messages,
+        }))
+    }
+
+    #[tool(description = "Fork a Redis-backed conversation: copy its stored history, including the system prompt, under a new conversation_id and return it. The original is left unchanged, so both can be continued independently.")]
+    #[tracing::instrument(name = "tool", skip_all, fields(tool = "fork_conversation", input_bytes = input_bytes(&params)))]
+    async fn fork_conversation(
+        &self,
+        Parameters(params): Parameters<ForkConversationParams>,
+    ) -> Result<Json<StartConversationResponse>, ToolError> {
+        let id = self
+            .convos
+            .fork(&params.conversation_id)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(Json(StartConversationResponse { conversation_id: id }))
+    }
+
+    #[tool(description = "List the Redis-backed conversations currently stored, with each one's message count and seconds until it expires, soonest to expire first. Use it to find and end stale sessions.")]
+    #[tracing::instrument(name = "tool", skip_all, fields(tool = "list_conversations"))]
+    async fn list_conversations(&self) -> Result<Json<ConversationList>, ToolError> {
+        Ok(Json(self.convos.list().await))
+    }
+
+    #[tool(description = "Compact a Redis-backed conversation: the chosen model summarizes the stored history, which is then replaced by a single system message holding the original system prompt followed by the summary. Returns the summary text. Fails with a conflict error if the conversation changed while the summary was written.")]
+    #[tracing::instrument(name = "tool", skip_all, fields(tool = "summarize_conversation", input_bytes = input_bytes(&params)))]
+    async fn summarize_conversation(
+        &self,
+        Parameters(params): Parameters<SummarizeConversationParams>,
+    ) -> Result<Json<SummarizeConversationResponse>, ToolError> {
+        let model = params.model.trim().to_string();
+        if model.is_empty() {
+            return Err("model must not be empty".into());
+        }
+
+        let ConversationSnapshot { version, messages } = self
+            .convos
+            .load(&params.conversation_id)
+            .await
+            .ok_or_else(|| format!("unknown conversation_id: {}", params.conversation_id))?;
+        let (system_prompt, previous, rest) = split_summary(&messages);
+        if rest.is_empty() {
+            return Err("conversation has no messages to summarize".into());
+        }
+
+        let request = chat_request(
+            &model,
+            vec![Message::new("user", summary_prompt(previous, rest))],
+            SamplingOptions::default(),
+        );
+        let summary = self.run_chat(request).await?.trim().to_string();
+        if summary.is_empty() {
+            return Err("model returned an empty summary".into());
+        }
+
+        // Rejects the summary if another call appended to this conversation while the model ran.
+        self.convos
+            .set_messages(
+                &params.conversation_id,
+                version,
+                &[summary_message(system_prompt, &summary)],
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(Json(SummarizeConversationResponse {
+            conversation_id: params.conversation_id,
+            summary,
+            summarized_messages: rest.len(),
+        }))
+    }
+
     #[tool(description = "End a Redis-backed conversation and delete its stored message history.")]
+    #[tracing::instrument(name = "tool", skip_all, fields(tool = "end_conversation", input_bytes = input_bytes(&params)))]
     async fn end_conversation(
         &self,
         Parameters(params): Parameters<EndConversationParams>,
-    ) -> Result<Json<OkResponse>, String> {
+    ) -> Result<Json<OkResponse>, ToolError> {
         self.convos.end(&params.conversation_id).await;
         Ok(Json(OkResponse { ok: true }))
     }
 
     #[tool(description = "Get usage stats aggregated per model (requests + tokens when reported by upstream).")]
-    async fn get_usage_stats(&self) -> Result<Json<UsageStats>, String> {
+    #[tracing::instrument(name = "tool", skip_all, fields(tool = "get_usage_stats"))]
+    async fn get_usage_stats(&self) -> Result<Json<UsageStats>, ToolError> {
         let stats = self.usage.get_usage_stats().await;
         Ok(Json(stats))
     }
+
+    #[tool(description = "Check whether the upstream model host and Redis are reachable. Probes GET /v1/models once, for at most 5 seconds, without retries and without counting against rate limits, usage or the circuit breaker, so it is safe for readiness checks.")]
+    #[tracing::instrument(name = "tool", skip_all, fields(tool = "health_check"))]
+    async fn health_check(&self) -> Result<Json<HealthCheckResponse>, ToolError> {
+        let upstream = async {
+            let start = Instant::now();
+            let probe = tokio::time::timeout(HEALTH_CHECK_TIMEOUT, self.openai.probe_models());
+            let ok = match probe.await {
+                Ok(Ok(_)) => true,
+                Ok(Err(e)) => {
+                    warn!(error = %e, "health check: upstream unreachable");
+                    false
+                }
+                Err(_) => {
+                    warn!(
+                        timeout_ms = HEALTH_CHECK_TIMEOUT.as_millis() as u64,
+                        "health check: upstream timed out"
+                    );
+                    false
+                }
+            };
+            (ok, start.elapsed().as_millis() as u64)
+        };
+        let ((upstream_ok, latency_ms), redis_ok) =
+            tokio::join!(upstream, self.usage.redis_available());
+        Ok(Json(HealthCheckResponse {
+            upstream_ok,
+            redis_ok,
+            latency_ms,
+        }))
+    }
+
+    #[tool(description = "Report the proxy's effective configuration (upstream URL, timeouts, retry and rate-limit settings, Redis status). Credentials are never included.")]
+    #[tracing::instrument(name = "tool", skip_all, fields(tool = "config_info"))]
+    async fn config_info(&self) -> Result<Json<ConfigInfoResponse>, ToolError> {
+        let config = self.openai.config();
+        Ok(Json(ConfigInfoResponse {
+            base_url: config.redacted_base_url(),
+            timeout_ms: config.default_timeout.as_millis() as u64,
+            max_retries: config.max_retries,
+            retry_initial_backoff_ms: config.initial_backoff.as_millis() as u64,
+            retry_max_backoff_ms: config.max_backoff.as_millis() as u64,
+            retry_backoff_strategy: config.backoff_strategy.as_str().to_string(),
+            max_error_body_bytes: config.max_error_body_bytes,
+            stream_max_buffer_bytes: config.max_stream_buffer_bytes,
+            api_key_configured: config.api_key.is_some(),
+            organization: config.organization.clone(),
+            extra_header_names: {
+                let mut names: Vec<String> = config.default_headers.keys().cloned().collect();
+                names.sort();
+                names
+            },
+            proxy_configured: config.proxy.is_some(),
+            circuit_breaker_failures: config.circuit_breaker_failures,
+            circuit_breaker_cooldown_ms: config.circuit_breaker_cooldown.as_millis() as u64,
+            rate_limit_rps: self.limiter.as_ref().and_then(RateLimiter::rps),
+            rate_limit_rps_per_model: self
+                .limiter
+                .as_ref()
+                .map(RateLimiter::per_model_rps)
+                .unwrap_or_default(),
+            rate_limit_backend: self.limiter.as_ref().map(|l| l.backend().to_string()),
+            convo_max_messages: self.convos.max_messages(),
+            models_cache_ttl_secs: self.models.ttl().as_secs(),
+            validate_model: self.models.validates(),
+            guideline_servers: self.guidelines.server_names(),
+            tool_timeout_secs: self.tool_timeout.limit().map(|limit| limit.as_secs()),
+            redis_available: self.usage.redis_available().await,
+        }))
+    }
 }
 
-#[tool_handler]
 impl ServerHandler for LlmProxyServer {
     fn get_info(&self) -> ServerInfo {
         ServerInfo {
@@ -287,18 +1217,249 @@ impl ServerHandler for LlmProxyServer {
             },
             instructions: Some(
                 "Local LLM proxy MCP server. Use list_models to discover local models, then call \
-ask_model/chat_model/generate_code with an explicit model ID. For multi-turn workflows, use \
-start_conversation/continue_conversation/end_conversation. Usage counters are available via \
-get_usage_stats."
+ask_model/chat_model/generate_code/review_code/embed_text with an explicit model ID; raw_chat forwards an \
+arbitrary chat completion body for parameters the other tools lack, and estimate_tokens \
+approximates prompt size before sending. For multi-turn workflows, use \
+start_conversation/continue_conversation/get_conversation/end_conversation, \
+fork_conversation to branch from a shared history, summarize_conversation to compact a long \
+history, and list_conversations to find stale ones. Usage counters are available via \
+get_usage_stats, health_check probes upstream and Redis reachability, and config_info reports \
+the effective configuration."
                     .to_string(),
             ),
         }
     }
+
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let tool = request.name.clone();
+        let call = self
+            .tool_router
+            .call(ToolCallContext::new(self, request, context));
+        self.tool_timeout
+            .run(&tool, call)
+            .await
+            .unwrap_or_else(|message| Ok(CallToolResult::error(vec![Content::text(message)])))
+    }
+
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, ErrorData> {
+        Ok(ListToolsResult::with_all_items(self.tool_router.list_all()))
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::LlmProxyServer;
+    use super::{
+        AskModelParams, LlmProxyServer, Message, ResponseFormat, SamplingOptions,
+        apply_json_mode, chat_request, check_json_reply, fence_code, raw_chat_model,
+        raw_chat_usage, review_system_prompt, split_summary, strip_code_fences, summary_message,
+        summary_prompt,
+    };
+    use std::time::Duration;
+
+    use crate::model_cache::ModelCache;
+
+    #[test]
+    fn sampling_options_validate_temperature_range() {
+        let ok = SamplingOptions {
+            temperature: Some(2.0),
+            max_tokens: Some(64),
+            ..Default::default()
+        };
+        assert!(ok.validate().is_ok());
+
+        let too_hot = SamplingOptions {
+            temperature: Some(2.5),
+            ..Default::default()
+        };
+        assert_eq!(
+            too_hot.validate().unwrap_err(),
+            "temperature must be between 0.0 and 2.0"
+        );
+
+        let negative = SamplingOptions {
+            temperature: Some(-0.1),
+            ..Default::default()
+        };
+        assert!(negative.validate().is_err());
+    }
+
+    #[test]
+    fn sampling_options_validate_top_p_penalties_and_stop() {
+        let ok = SamplingOptions {
+            top_p: Some(0.9),
+            stop: Some(vec!["```".to_string()]),
+            frequency_penalty: Some(-2.0),
+            presence_penalty: Some(1.5),
+            ..Default::default()
+        };
+        assert!(ok.validate().is_ok());
+        let request = chat_request("m", vec![], ok);
+        assert_eq!(request.stop.as_deref(), Some(&["```".to_string()][..]));
+        assert_eq!(request.top_p, Some(0.9));
+
+        let wide = SamplingOptions {
+            top_p: Some(1.5),
+            ..Default::default()
+        };
+        assert_eq!(wide.validate().unwrap_err(), "top_p must be between 0.0 and 1.0");
+        let penalized = SamplingOptions {
+            presence_penalty: Some(-2.5),
+            ..Default::default()
+        };
+        assert_eq!(
+            penalized.validate().unwrap_err(),
+            "presence_penalty must be between -2.0 and 2.0"
+        );
+
+        let stops = |stop: &[&str]| SamplingOptions {
+            stop: Some(stop.iter().map(|s| s.to_string()).collect()),
+            ..Default::default()
+        };
+        assert!(stops(&[]).validate().is_err());
+        assert!(stops(&["a", "b", "c", "d", "e"]).validate().is_err());
+        assert_eq!(
+            stops(&["a", ""]).validate().unwrap_err(),
+            "stop sequences must not be empty"
+        );
+    }
+
+    #[test]
+    fn json_mode_sets_response_format_and_checks_reply() {
+        let mut request = chat_request("m", vec![], SamplingOptions::default());
+        apply_json_mode(&mut request, None);
+        assert_eq!(request.response_format, None);
+        apply_json_mode(&mut request, Some(true));
+        assert_eq!(request.response_format, Some(ResponseFormat::JsonObject));
+
+        assert!(check_json_reply(r#"{"ok": true}"#).is_ok());
+        let err = check_json_reply("Sure! Here is the JSON: {").unwrap_err();
+        assert!(err.starts_with("model returned invalid JSON"), "{err}");
+    }
+
+    #[test]
+    fn single_fenced_blocks_are_unwrapped() {
+        assert_eq!(
+            strip_code_fences("```cpp\nint main() {\n    return 0;\n}\n```\n"),
+            "int main() {\n    return 0;\n}"
+        );
+        assert_eq!(strip_code_fences("  ```\nlet x = 1;\n```"), "let x = 1;");
+        assert_eq!(strip_code_fences("```rust\n```"), "");
+
+        for unchanged in [
+            "fn main() {}",
+            "Here you go:\n```rust\nfn main() {}\n```",
+            "```rust\nfn a() {}\n```\n\n```rust\nfn b() {}\n```",
+            "```rust\nfn main() {}",
+            "```inline```",
+            "```rust\nfn main() {} ```",
+        ] {
+            assert_eq!(strip_code_fences(unchanged), unchanged);
+        }
+    }
+
+    #[test]
+    fn review_prompts_list_the_matched_guidelines() {
+        use mcp_common::mcp_api::GuidelineSearchResult;
+
+        let plain = review_system_prompt("C++", &[]);
+        assert!(plain.starts_with("You are an expert C++ code reviewer."));
+        assert!(!plain.contains("Guidelines"));
+
+        let rule = GuidelineSearchResult {
+            id: "R.11".to_string(),
+            title: "Avoid calling new and delete explicitly".to_string(),
+            category: "R".to_string(),
+            score: 0.8,
+            summary: "Use make_unique instead.".to_string(),
+            matched_section: None,
+        };
+        let prompt = review_system_prompt("C++", &[rule]);
+        assert!(prompt.ends_with(
+            "Guidelines that look most relevant:\n\
+- R.11: Avoid calling new and delete explicitly (Use make_unique instead.)\n"
+        ));
+    }
+
+    #[test]
+    fn fences_outlast_backtick_runs_in_the_code() {
+        assert_eq!(fence_code("Rust", "fn main() {}"), "```rust\nfn main() {}\n```");
+        let markdown = "Example:\n```cpp\nint x;\n```";
+        assert_eq!(
+            fence_code("Markdown", markdown),
+            format!("````markdown\n{markdown}\n````")
+        );
+    }
+
+    #[test]
+    fn summaries_keep_the_system_prompt_and_build_on_earlier_ones() {
+        let messages = vec![
+            Message::new("system", "Be terse."),
+            Message::new("user", "Plan a trip"),
+            Message::new("assistant", "Where to?"),
+        ];
+        let (prompt, previous, rest) = split_summary(&messages);
+        assert_eq!((prompt, previous, rest.len()), (Some("Be terse."), None, 2));
+        assert!(summary_prompt(previous, rest)
+            .ends_with("CONVERSATION:\nuser: Plan a trip\nassistant: Where to?\n"));
+
+        let compacted = vec![
+            summary_message(prompt, "Trip planning, no destination yet."),
+            Message::new("user", "Lisbon"),
+        ];
+        let (prompt, previous, rest) = split_summary(&compacted);
+        assert_eq!(prompt, Some("Be terse."));
+        assert_eq!(previous, Some("Trip planning, no destination yet."));
+        assert!(summary_prompt(previous, rest).contains(
+            "EARLIER SUMMARY:\nTrip planning, no destination yet.\n\nCONVERSATION:\nuser: Lisbon\n"
+        ));
+
+        let untitled = [summary_message(None, "Nothing yet.")];
+        let (prompt, previous, rest) = split_summary(&untitled);
+        assert_eq!((prompt, previous, rest.len()), (None, Some("Nothing yet."), 0));
+        let (prompt, previous, rest) = split_summary(&messages[1..]);
+        assert_eq!((prompt, previous, rest.len()), (None, None, 2));
+    }
+
+    #[test]
+    fn raw_chat_bodies_need_a_model_and_no_streaming() {
+        use serde_json::json;
+
+        let body = json!({ "model": " llama3:8b ", "messages": [], "seed": 7 });
+        assert_eq!(raw_chat_model(&body).unwrap(), "llama3:8b");
+        assert!(raw_chat_model(&json!([])).is_err());
+        assert!(raw_chat_model(&json!({ "messages": [] })).is_err());
+        assert_eq!(
+            raw_chat_model(&json!({ "model": "m", "stream": true })).unwrap_err(),
+            "body.stream is not supported by raw_chat"
+        );
+
+        let usage = raw_chat_usage(&json!({ "usage": { "prompt_tokens": 5 } })).unwrap();
+        assert_eq!(usage.prompt_tokens, Some(5));
+        assert!(raw_chat_usage(&json!({ "usage": "n/a" })).is_none());
+        assert!(raw_chat_usage(&json!({})).is_none());
+    }
+
+    #[test]
+    fn ask_model_params_accept_sampling_fields() {
+        let params: AskModelParams = serde_json::from_value(serde_json::json!({
+            "model": "m",
+            "prompt": "p",
+            "temperature": 0.2,
+            "max_tokens": 32
+        }))
+        .unwrap();
+        assert_eq!(params.sampling.temperature, Some(0.2));
+        assert_eq!(params.sampling.max_tokens, Some(32));
+        assert!(!params.stream);
+    }
 
     #[test]
     fn tools_publish_output_schemas() {
@@ -307,11 +1468,21 @@ mod tests {
             "list_models",
             "ask_model",
             "chat_model",
+            "raw_chat",
             "generate_code",
+            "review_code",
+            "embed_text",
+            "estimate_tokens",
             "start_conversation",
             "continue_conversation",
+            "get_conversation",
+            "fork_conversation",
+            "list_conversations",
+            "summarize_conversation",
             "end_conversation",
             "get_usage_stats",
+            "health_check",
+            "config_info",
         ] {
             let tool = tools
                 .iter()
@@ -323,4 +1494,90 @@ mod tests {
             );
         }
     }
+
+    /// A server whose upstream refuses connections, limited to one request per second.
+    fn unreachable_server(models: crate::model_cache::ModelCache) -> LlmProxyServer {
+        use std::collections::HashMap;
+        use std::sync::Arc;
+
+        use mcp_common::llm_state::{ConversationStore, UsageTracker};
+        use mcp_common::openai::{OpenAiClient, OpenAiClientConfig};
+        use mcp_common::redis::RedisCache;
+
+        use mcp_common::tool_timeout::ToolTimeout;
+
+        use crate::guidelines::GuidelineClient;
+        use crate::rate_limit::{RateLimitMode, RateLimiter};
+
+        let addr = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let mut config = OpenAiClientConfig::from_env();
+        config.base_url = format!("http://{addr}/v1");
+        config.max_retries = 0;
+        let limiter =
+            RateLimiter::new(Some(1), HashMap::new(), RateLimitMode::Reject, Duration::ZERO);
+        LlmProxyServer::new(
+            Arc::new(OpenAiClient::new(config).unwrap()),
+            ConversationStore::new(RedisCache::new(None)),
+            UsageTracker::new(RedisCache::new(None)),
+            Some(limiter),
+            models,
+            GuidelineClient::default(),
+            ToolTimeout::default(),
+        )
+    }
+
+    /// The probe reports a down upstream instead of failing, and bypasses the rate limiter.
+    #[tokio::test]
+    async fn health_check_reports_unreachable_upstream() {
+        let server = unreachable_server(ModelCache::new(Duration::ZERO));
+        server.gate(None).await.unwrap();
+        assert!(server.gate(None).await.is_err());
+
+        let health = server.health_check().await.unwrap().0;
+        assert!(!health.upstream_ok);
+        assert!(!health.redis_ok);
+    }
+
+    /// With `RATE_LIMIT_RPS=1` and nothing cached, validating the model does not spend a
+    /// second token, and an unavailable model list skips validation.
+    #[tokio::test]
+    async fn model_validation_on_a_cold_cache_takes_one_token() {
+        let models = ModelCache::new(Duration::ZERO).with_validation(true);
+        let server = unreachable_server(models);
+        server.admit("llama3:8b").await.unwrap();
+        assert!(server.admit("llama3:8b").await.is_err());
+    }
+
+    #[test]
+    fn metric_labels_only_name_listed_models() {
+        let server = unreachable_server(ModelCache::new(Duration::from_secs(60)));
+        assert_eq!(server.metric_label("llama3:8b"), "other");
+
+        let models = serde_json::from_value(serde_json::json!({
+            "object": "list",
+            "data": [{ "id": "llama3:8b" }],
+        }))
+        .unwrap();
+        server.models.set(&models);
+        assert_eq!(server.metric_label("llama3:8b"), "llama3:8b");
+        assert_eq!(server.metric_label("made-up-model"), "other");
+    }
+
+    #[test]
+    fn tool_params_are_described() {
+        for tool in LlmProxyServer::tool_router().list_all() {
+            let Some(properties) = tool.input_schema.get("properties").and_then(|p| p.as_object())
+            else {
+                continue;
+            };
+            for (param, schema) in properties {
+                let description = schema.get("description").and_then(|d| d.as_str());
+                assert!(
+                    description.is_some_and(|d| !d.is_empty()),
+                    "{}.{param} should have a description",
+                    tool.name
+                );
+            }
+        }
+    }
 }
@@ -0,0 +1,91 @@
+/// Prometheus metrics for the HTTP transport.
+///
+/// Call sites record through the `metrics` facade unconditionally. Only HTTP mode installs
+/// a recorder ([`install`]) and mounts [`router`]'s `/metrics` route; in stdio mode no
+/// recorder exists and every record is a no-op.
+///
+/// Exported series:
+/// - `llm_proxy_chat_requests_total{model, outcome}`: chat completions, `ok` or `error`
+/// - `llm_proxy_tokens_total{model, kind}`: reported `prompt` and `completion` tokens
+/// - `llm_proxy_rate_limited_total{model}`: calls rejected by the rate limiter
+/// - `openai_request_duration_seconds{endpoint, outcome}`: upstream latency per attempt
+///
+/// The `model` label only names models from the fetched model list or `MODEL_PRICING`;
+/// any other id a client sends is counted under [`OTHER_MODEL`], so clients cannot create
+/// new series at will.
+use std::future::ready;
+use std::time::Duration;
+
+use axum::{Router, routing::get};
+use metrics::counter;
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+
+use mcp_common::openai::{ChatCompletionUsage, UPSTREAM_LATENCY_METRIC};
+
+/// `model` label for ids that are neither listed upstream nor priced.
+pub const OTHER_MODEL: &str = "other";
+
+const CHAT_REQUESTS_METRIC: &str = "llm_proxy_chat_requests_total";
+const TOKENS_METRIC: &str = "llm_proxy_tokens_total";
+const RATE_LIMITED_METRIC: &str = "llm_proxy_rate_limited_total";
+
+/// Upper bounds in seconds of the upstream latency buckets; local models answer in
+/// anywhere from milliseconds to minutes.
+const LATENCY_BUCKETS: [f64; 10] = [0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 120.0];
+
+/// How often histogram samples are drained into their buckets between scrapes.
+const UPKEEP_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Install the global Prometheus recorder and start its upkeep task. Call once, from
+/// within the Tokio runtime.
+pub fn install() -> anyhow::Result<PrometheusHandle> {
+    let handle = PrometheusBuilder::new()
+        .set_buckets_for_metric(
+            Matcher::Full(UPSTREAM_LATENCY_METRIC.to_string()),
+            &LATENCY_BUCKETS,
+        )?
+        .install_recorder()?;
+
+    let upkeep = handle.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(UPKEEP_INTERVAL);
+        loop {
+            interval.tick().await;
+            upkeep.run_upkeep();
+        }
+    });
+    Ok(handle)
+}
+
+/// Router serving the recorder's current state in Prometheus text format at `/metrics`.
+pub fn router(handle: PrometheusHandle) -> Router {
+    Router::new().route("/metrics", get(move || ready(handle.render())))
+}
+
+/// Count one chat completion for `model`.
+pub fn record_chat(model: &str, ok: bool) {
+    let outcome = if ok { "ok" } else { "error" };
+    counter!(CHAT_REQUESTS_METRIC, "model" => model.to_string(), "outcome" => outcome)
+        .increment(1);
+}
+
+/// Add a completion's reported prompt and completion tokens to `model`'s totals.
+pub fn record_tokens(model: &str, usage: Option<&ChatCompletionUsage>) {
+    let Some(usage) = usage else {
+        return;
+    };
+    if let Some(prompt) = usage.prompt_tokens {
+        counter!(TOKENS_METRIC, "model" => model.to_string(), "kind" => "prompt")
+            .increment(prompt);
+    }
+    if let Some(completion) = usage.completion_tokens {
+        counter!(TOKENS_METRIC, "model" => model.to_string(), "kind" => "completion")
+            .increment(completion);
+    }
+}
+
+/// Count a call the rate limiter turned away; calls without a model are labelled `""`.
+pub fn record_rate_limited(model: Option<&str>) {
+    counter!(RATE_LIMITED_METRIC, "model" => model.unwrap_or_default().to_string())
+        .increment(1);
+}
@@ -0,0 +1,141 @@
+/// Error returned by the proxy's tools.
+///
+/// Every error reaches the client as its text message, as before. Failures reported by the
+/// upstream host also carry a JSON object with the HTTP status and the host's own message,
+/// so a caller can tell a rejected request (say, a 400 for an over-long context) from a busy
+/// or failing host (429, 503) without parsing the text.
+use rmcp::model::{Content, IntoContents};
+use serde::Serialize;
+
+use mcp_common::openai::OpenAiClientError;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ToolError {
+    pub message: String,
+    /// Present when the upstream host was reached, or could not be.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upstream: Option<UpstreamDetail>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UpstreamDetail {
+    /// HTTP status of the upstream response; `None` when no response arrived (connection
+    /// failure, timeout, open circuit) or it could not be read.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    /// The upstream's error message, or its raw (size-capped) error body.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Whether the same request may succeed if sent again later.
+    pub retryable: bool,
+    /// The `X-Request-Id` sent upstream, for finding the request in the host's logs.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+impl ToolError {
+    /// An upstream failure, with the text message `{context}: {err}`.
+    pub fn upstream(context: &str, err: &OpenAiClientError) -> Self {
+        let (status, message) = match err {
+            OpenAiClientError::Upstream {
+                status, message, ..
+            } => (Some(status.as_u16()), Some(message.clone())),
+            OpenAiClientError::UpstreamBody { status, body, .. } => {
+                (Some(status.as_u16()), Some(body.clone()))
+            }
+            _ => (None, None),
+        };
+        Self {
+            message: format!("{context}: {err}"),
+            upstream: Some(UpstreamDetail {
+                status,
+                message,
+                retryable: err.is_retryable(),
+                request_id: err.request_id().map(str::to_string),
+            }),
+        }
+    }
+}
+
+impl From<String> for ToolError {
+    fn from(message: String) -> Self {
+        Self {
+            message,
+            upstream: None,
+        }
+    }
+}
+
+impl From<&str> for ToolError {
+    fn from(message: &str) -> Self {
+        message.to_string().into()
+    }
+}
+
+impl std::fmt::Display for ToolError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl IntoContents for ToolError {
+    fn into_contents(self) -> Vec<Content> {
+        let mut contents = vec![Content::text(self.message.clone())];
+        if self.upstream.is_some() {
+            contents.extend(Content::json(&self).ok());
+        }
+        contents
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mcp_common::openai::StatusCode;
+
+    use super::*;
+
+    #[test]
+    fn upstream_errors_carry_status_and_message() {
+        let err = OpenAiClientError::Upstream {
+            status: StatusCode::BAD_REQUEST,
+            message: "context length exceeded".to_string(),
+            retry_after: None,
+            request_id: "0123abcd".to_string(),
+        };
+        let error = ToolError::upstream("chat failed", &err);
+        assert!(error
+            .message
+            .starts_with("chat failed: upstream returned error: status=400"));
+
+        let contents = error.into_contents();
+        assert_eq!(contents.len(), 2);
+        let detail: serde_json::Value =
+            serde_json::from_str(&contents[1].as_text().unwrap().text).unwrap();
+        assert_eq!(detail["upstream"]["status"], 400);
+        assert_eq!(detail["upstream"]["message"], "context length exceeded");
+        assert_eq!(detail["upstream"]["retryable"], false);
+        assert_eq!(detail["upstream"]["request_id"], "0123abcd");
+
+        let busy = OpenAiClientError::UpstreamBody {
+            status: StatusCode::SERVICE_UNAVAILABLE,
+            body: "loading model".to_string(),
+            retry_after: None,
+            request_id: "0123abcd".to_string(),
+        };
+        let upstream = ToolError::upstream("chat failed", &busy).upstream.unwrap();
+        assert_eq!((upstream.status, upstream.retryable), (Some(503), true));
+
+        let down = ToolError::upstream("chat failed", &OpenAiClientError::StreamEnded);
+        assert_eq!(down.upstream.unwrap().status, None);
+    }
+
+    #[test]
+    fn other_errors_are_plain_text() {
+        let contents = ToolError::from("model must not be empty").into_contents();
+        assert_eq!(contents.len(), 1);
+        assert_eq!(
+            contents[0].as_text().unwrap().text,
+            "model must not be empty"
+        );
+    }
+}
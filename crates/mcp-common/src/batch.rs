@@ -0,0 +1,95 @@
+/// Collapsing repeated queries in `search_batch`.
+///
+/// A batch may name the same query more than once. Each distinct query that is not already
+/// answered (from the search cache) is embedded and searched once, and every position that
+/// asked for it gets a copy of the results.
+use std::future::Future;
+
+/// Fill the `None` entries of `results` (one per query) by calling `fetch` once with the
+/// distinct queries behind them, in order of first appearance.
+///
+/// `fetch` returns one result per query it was given, in the same order. Entries that are
+/// already `Some` are left alone, and `fetch` is not called when nothing is missing.
+pub async fn fill_distinct<T, E, F, Fut>(
+    queries: &[String],
+    results: &mut [Option<T>],
+    fetch: F,
+) -> Result<(), E>
+where
+    T: Clone,
+    F: FnOnce(Vec<String>) -> Fut,
+    Fut: Future<Output = Result<Vec<T>, E>>,
+{
+    let mut distinct: Vec<String> = Vec::new();
+    let mut slots: Vec<(usize, usize)> = Vec::new();
+    for (i, query) in queries.iter().enumerate() {
+        if results[i].is_some() {
+            continue;
+        }
+        let d = match distinct.iter().position(|q| q == query) {
+            Some(d) => d,
+            None => {
+                distinct.push(query.clone());
+                distinct.len() - 1
+            }
+        };
+        slots.push((i, d));
+    }
+    if distinct.is_empty() {
+        return Ok(());
+    }
+
+    let fetched = fetch(distinct).await?;
+    for (i, d) in slots {
+        results[i] = fetched.get(d).cloned();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn repeated_queries_are_fetched_once_and_shared() {
+        let queries: Vec<String> =
+            ["raii", "noexcept", "raii", "cached", "noexcept"].map(String::from).to_vec();
+        let mut results = vec![None, None, None, Some("from cache".to_string()), None];
+        let mut calls = Vec::new();
+
+        fill_distinct(&queries, &mut results, |texts| {
+            calls.push(texts.clone());
+            let found = texts.iter().map(|q| format!("results for {q}")).collect();
+            async move { Ok::<_, String>(found) }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(calls, [vec!["raii", "noexcept"]]);
+        assert_eq!(
+            results,
+            [
+                Some("results for raii".to_string()),
+                Some("results for noexcept".to_string()),
+                Some("results for raii".to_string()),
+                Some("from cache".to_string()),
+                Some("results for noexcept".to_string()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn nothing_is_fetched_when_every_query_is_answered() {
+        let queries = vec!["raii".to_string()];
+        let mut results = vec![Some(1)];
+        let mut called = false;
+        fill_distinct(&queries, &mut results, |_| {
+            called = true;
+            async { Ok::<Vec<i32>, String>(Vec::new()) }
+        })
+        .await
+        .unwrap();
+        assert!(!called);
+        assert_eq!(results, [Some(1)]);
+    }
+}
@@ -4,28 +4,143 @@
 /// `tokio::task::spawn_blocking`. The `Embedder` is `!Send` due to the inner ONNX runtime,
 /// so it is wrapped in `Arc` and accessed only from blocking tasks.
 ///
-/// The nomic-embed-text-v1.5 model uses task-prefixed inputs:
+/// The model is chosen with `EMBEDDING_MODEL` (default nomic-embed-text-v1.5). Nomic models
+/// use task-prefixed inputs; the others embed text as-is:
 /// - Documents: "search_document: {text}"
 /// - Queries: "search_query: {text}"
+///
+/// Query embeddings can be cached in Redis (see [`Embedder::with_query_cache`]) so repeated
+/// searches skip the model entirely.
 use std::sync::Arc;
 
+use sha2::{Digest, Sha256};
+use tracing::warn;
+
 use crate::error::CommonError;
+use crate::redis::RedisCache;
+
+/// Default for `EMBED_BATCH_SIZE`; small to bound peak memory during ONNX inference.
+const DEFAULT_EMBED_BATCH_SIZE: usize = 4;
+
+/// Default for `EMBEDDING_CACHE_TTL_SECS`.
+const DEFAULT_QUERY_CACHE_TTL_SECS: u64 = 7 * 86_400;
+
+/// Embedded in place of a blank document. A zero vector would make cosine distance NaN, so
+/// blank documents get a real (if meaningless) embedding instead.
+const BLANK_DOCUMENT_PLACEHOLDER: &str = "(empty document)";
+
+/// A supported embedding model and how its inputs are formatted.
+#[derive(Debug, Clone)]
+pub struct EmbeddingModelSpec {
+    /// Canonical name, e.g. "nomic-embed-text-v1.5"; also accepted by `EMBEDDING_MODEL`.
+    pub name: &'static str,
+    model: fastembed::EmbeddingModel,
+    pub dimensions: usize,
+    /// Whether inputs take the nomic `search_document: ` / `search_query: ` prefixes.
+    pub task_prefixes: bool,
+}
+
+const fn spec(
+    name: &'static str,
+    model: fastembed::EmbeddingModel,
+    dimensions: usize,
+    task_prefixes: bool,
+) -> EmbeddingModelSpec {
+    EmbeddingModelSpec {
+        name,
+        model,
+        dimensions,
+        task_prefixes,
+    }
+}
+
+/// Models selectable via `EMBEDDING_MODEL`. The first entry is the default.
+const MODELS: &[EmbeddingModelSpec] = &[
+    spec("nomic-embed-text-v1.5", fastembed::EmbeddingModel::NomicEmbedTextV15, 768, true),
+    spec("nomic-embed-text-v1.5-q", fastembed::EmbeddingModel::NomicEmbedTextV15Q, 768, true),
+    spec("nomic-embed-text-v1", fastembed::EmbeddingModel::NomicEmbedTextV1, 768, true),
+    spec("all-minilm-l6-v2", fastembed::EmbeddingModel::AllMiniLML6V2, 384, false),
+    spec("all-minilm-l12-v2", fastembed::EmbeddingModel::AllMiniLML12V2, 384, false),
+    spec("bge-small-en-v1.5", fastembed::EmbeddingModel::BGESmallENV15, 384, false),
+    spec("bge-base-en-v1.5", fastembed::EmbeddingModel::BGEBaseENV15, 768, false),
+    spec("bge-large-en-v1.5", fastembed::EmbeddingModel::BGELargeENV15, 1024, false),
+];
+
+impl EmbeddingModelSpec {
+    /// Look up a model by canonical name or fastembed variant name (`AllMiniLML6V2`),
+    /// ignoring case.
+    pub fn from_name(name: &str) -> Option<&'static Self> {
+        let name = name.trim();
+        MODELS.iter().find(|m| {
+            m.name.eq_ignore_ascii_case(name)
+                || format!("{:?}", m.model).eq_ignore_ascii_case(name)
+        })
+    }
+
+    fn document_input(&self, text: &str) -> String {
+        if self.task_prefixes {
+            format!("search_document: {text}")
+        } else {
+            text.to_string()
+        }
+    }
+
+    fn query_input(&self, query: &str) -> String {
+        if self.task_prefixes {
+            format!("search_query: {query}")
+        } else {
+            query.to_string()
+        }
+    }
+}
+
+/// Progress callback for [`Embedder::embed_documents_with_progress`], called with
+/// `(documents_done, documents_total)` after each batch.
+pub type EmbedProgress<'a> = &'a (dyn Fn(usize, usize) + Send + Sync);
 
 /// Wraps fastembed's `TextEmbedding` model for generating vector embeddings.
 ///
 /// The inner model is not `Send`, so all operations are dispatched to a blocking thread.
 pub struct Embedder {
     model: Arc<fastembed::TextEmbedding>,
+    spec: &'static EmbeddingModelSpec,
+    /// Documents per model call when indexing (`EMBED_BATCH_SIZE`).
+    batch_size: usize,
+    /// Redis cache for query vectors with its TTL; `None` embeds every query.
+    query_cache: Option<(RedisCache, u64)>,
 }
 
 impl Embedder {
-    /// Initialize the embedding model (nomic-embed-text-v1.5).
+    /// Initialize the model named by `EMBEDDING_MODEL` (default nomic-embed-text-v1.5).
     ///
-    /// This downloads the model on first run (~300MB). The download happens synchronously
-    /// inside a blocking task.
+    /// This downloads the model on first run (~300MB for nomic). The download happens
+    /// synchronously inside a blocking task. An unknown model name is an error rather than
+    /// a silent fallback, since the stored index depends on the model.
     pub async fn new() -> Result<Self, CommonError> {
-        let model = tokio::task::spawn_blocking(|| {
-            let options = fastembed::InitOptions::new(fastembed::EmbeddingModel::NomicEmbedTextV15)
+        let spec = match std::env::var("EMBEDDING_MODEL") {
+            Ok(name) if !name.trim().is_empty() => {
+                EmbeddingModelSpec::from_name(&name).ok_or_else(|| {
+                    let known: Vec<&str> = MODELS.iter().map(|m| m.name).collect();
+                    CommonError::Embedding(format!(
+                        "unknown EMBEDDING_MODEL '{name}'; supported: {}",
+                        known.join(", ")
+                    ))
+                })?
+            }
+            _ => &MODELS[0],
+        };
+
+        // Larger batches index faster at the cost of peak memory.
+        let batch_size = match std::env::var("EMBED_BATCH_SIZE") {
+            Ok(value) => parse_batch_size(&value).unwrap_or_else(|| {
+                warn!(value, "EMBED_BATCH_SIZE must be a whole number >= 1, using default");
+                DEFAULT_EMBED_BATCH_SIZE
+            }),
+            Err(_) => DEFAULT_EMBED_BATCH_SIZE,
+        };
+
+        let model = tokio::task::spawn_blocking(move || {
+            let options = fastembed::InitOptions::new(spec.model.clone())
                 .with_show_download_progress(true);
             fastembed::TextEmbedding::try_new(options)
         })
@@ -35,46 +150,296 @@ impl Embedder {
 
         Ok(Self {
             model: Arc::new(model),
+            spec,
+            batch_size,
+            query_cache: None,
         })
     }
 
+    /// Cache query embeddings in Redis for `EMBEDDING_CACHE_TTL_SECS` (default 7 days;
+    /// 0 disables the cache). Lookups degrade to embedding when Redis is down.
+    pub fn with_query_cache(mut self, redis: RedisCache) -> Self {
+        let ttl_secs = std::env::var("EMBEDDING_CACHE_TTL_SECS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_QUERY_CACHE_TTL_SECS);
+        self.query_cache = (ttl_secs > 0).then_some((redis, ttl_secs));
+        self
+    }
+
+    /// Canonical name of the loaded model, for diagnostics.
+    pub fn model_name(&self) -> &'static str {
+        self.spec.name
+    }
+
     /// Embed documents for indexing.
     ///
-    /// The nomic-embed-text model expects document inputs prefixed with "search_document: ".
-    /// This method adds the prefix automatically.
+    /// Nomic models expect document inputs prefixed with "search_document: "; this method
+    /// adds the prefix automatically when the loaded model uses it.
     ///
-    /// Documents are processed in small batches to bound peak memory during ONNX inference.
+    /// Documents are processed in batches of `EMBED_BATCH_SIZE` (default 4) to bound peak
+    /// memory during ONNX inference.
+    ///
+    /// Blank documents are embedded as a fixed placeholder text so the result still lines up
+    /// with `texts` and every row has a usable vector. Every vector the model returns is
+    /// checked to have [`Self::dimensions`] finite values, and the first one that does not
+    /// fails the call with its document index.
     pub async fn embed_documents(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, CommonError> {
-        let prefixed: Vec<String> = texts
-            .iter()
-            .map(|t| format!("search_document: {t}"))
-            .collect();
-        let model = Arc::clone(&self.model);
-        tokio::task::spawn_blocking(move || model.embed(prefixed, Some(4)))
-            .await
-            .map_err(|e| CommonError::Embedding(format!("spawn_blocking join error: {e}")))?
-            .map_err(|e| CommonError::Embedding(format!("document embedding failed: {e}")))
+        self.embed_documents_with_progress(texts, None).await
+    }
+
+    /// Like [`Self::embed_documents`], but calls `progress(done, total)` after each batch
+    /// so long re-indexes can report how far they have got.
+    pub async fn embed_documents_with_progress(
+        &self,
+        texts: &[String],
+        progress: Option<EmbedProgress<'_>>,
+    ) -> Result<Vec<Vec<f32>>, CommonError> {
+        let mut embeddings = Vec::with_capacity(texts.len());
+        for chunk in texts.chunks(self.batch_size.max(1)) {
+            let start = embeddings.len();
+            let prefixed: Vec<String> = (start..)
+                .zip(chunk)
+                .map(|(index, text)| self.spec.document_input(document_text(index, text)))
+                .collect();
+            let model = Arc::clone(&self.model);
+            let batch_size = self.batch_size;
+            let batch =
+                tokio::task::spawn_blocking(move || model.embed(prefixed, Some(batch_size)))
+                    .await
+                    .map_err(|e| CommonError::Embedding(format!("spawn_blocking join error: {e}")))?
+                    .map_err(|e| {
+                        CommonError::Embedding(format!("document embedding failed: {e}"))
+                    })?;
+            let batch = check_batch(start, chunk.len(), batch, self.spec.dimensions)?;
+            embeddings.extend(batch);
+            if let Some(progress) = progress {
+                progress(embeddings.len(), texts.len());
+            }
+        }
+        Ok(embeddings)
     }
 
     /// Embed a single query for search.
     ///
-    /// The nomic-embed-text model expects query inputs prefixed with "search_query: ".
-    /// This method adds the prefix automatically.
+    /// Nomic models expect query inputs prefixed with "search_query: "; this method adds
+    /// the prefix automatically when the loaded model uses it.
     pub async fn embed_query(&self, query: &str) -> Result<Vec<f32>, CommonError> {
-        let prefixed = vec![format!("search_query: {query}")];
+        if let Some(cached) = self.cached_query(query).await {
+            return Ok(cached);
+        }
+
+        let prefixed = vec![self.spec.query_input(query)];
         let model = Arc::clone(&self.model);
         let mut results =
             tokio::task::spawn_blocking(move || model.embed(prefixed, None))
                 .await
                 .map_err(|e| CommonError::Embedding(format!("spawn_blocking join error: {e}")))?
                 .map_err(|e| CommonError::Embedding(format!("query embedding failed: {e}")))?;
-        results
+        let embedding = results
             .pop()
-            .ok_or_else(|| CommonError::Embedding("empty embedding result".to_string()))
+            .ok_or_else(|| CommonError::Embedding("empty embedding result".to_string()))?;
+        self.cache_query(query, &embedding).await;
+        Ok(embedding)
+    }
+
+    /// Embed several queries in one model call.
+    ///
+    /// Like `embed_query`, each input gets the query prefix if any and cached vectors are
+    /// reused; only the misses go to the model. Returns one vector per query, in input order.
+    pub async fn embed_queries(&self, queries: &[String]) -> Result<Vec<Vec<f32>>, CommonError> {
+        let mut results: Vec<Option<Vec<f32>>> = Vec::with_capacity(queries.len());
+        for query in queries {
+            results.push(self.cached_query(query).await);
+        }
+        let misses: Vec<usize> = (0..queries.len()).filter(|&i| results[i].is_none()).collect();
+
+        if !misses.is_empty() {
+            let prefixed: Vec<String> = misses
+                .iter()
+                .map(|&i| self.spec.query_input(&queries[i]))
+                .collect();
+            let model = Arc::clone(&self.model);
+            let embedded = tokio::task::spawn_blocking(move || model.embed(prefixed, None))
+                .await
+                .map_err(|e| CommonError::Embedding(format!("spawn_blocking join error: {e}")))?
+                .map_err(|e| CommonError::Embedding(format!("query embedding failed: {e}")))?;
+            if embedded.len() != misses.len() {
+                return Err(CommonError::Embedding(format!(
+                    "embedding count mismatch: expected {}, got {}",
+                    misses.len(),
+                    embedded.len()
+                )));
+            }
+            for (i, embedding) in misses.into_iter().zip(embedded) {
+                self.cache_query(&queries[i], &embedding).await;
+                results[i] = Some(embedding);
+            }
+        }
+
+        Ok(results.into_iter().map(Option::unwrap_or_default).collect())
+    }
+
+    async fn cached_query(&self, query: &str) -> Option<Vec<f32>> {
+        let (redis, _) = self.query_cache.as_ref()?;
+        let key = query_cache_key(self.spec.name, query);
+        let json = redis.get(&key).await?;
+        serde_json::from_str::<Vec<f32>>(&json)
+            .inspect_err(|e| warn!(error = %e, key, "cached query embedding is corrupt"))
+            .ok()
+            .filter(|v| v.len() == self.spec.dimensions)
+    }
+
+    async fn cache_query(&self, query: &str, embedding: &[f32]) {
+        let Some((redis, ttl_secs)) = &self.query_cache else {
+            return;
+        };
+        if let Ok(json) = serde_json::to_string(embedding) {
+            let key = query_cache_key(self.spec.name, query);
+            redis.set_with_ttl(&key, &json, *ttl_secs).await;
+        }
     }
 
     /// Returns the dimensionality of the embedding vectors (768 for nomic-embed-text-v1.5).
     pub fn dimensions(&self) -> usize {
-        768
+        self.spec.dimensions
+    }
+}
+
+/// The text to embed for document number `index`: the document itself, or
+/// [`BLANK_DOCUMENT_PLACEHOLDER`] when it is blank.
+fn document_text(index: usize, text: &str) -> &str {
+    if text.trim().is_empty() {
+        warn!(index, "document text is empty, embedding a placeholder");
+        BLANK_DOCUMENT_PLACEHOLDER
+    } else {
+        text
+    }
+}
+
+/// Check the model returned one `dimensions`-long, finite vector for each of the `count`
+/// documents starting at number `start`.
+fn check_batch(
+    start: usize,
+    count: usize,
+    batch: Vec<Vec<f32>>,
+    dimensions: usize,
+) -> Result<Vec<Vec<f32>>, CommonError> {
+    if batch.len() != count {
+        return Err(CommonError::Embedding(format!(
+            "embedding count mismatch for documents {start}..{}: expected {count}, got {}",
+            start + count,
+            batch.len()
+        )));
+    }
+
+    for (index, embedding) in (start..).zip(&batch) {
+        if embedding.len() != dimensions {
+            return Err(CommonError::Embedding(format!(
+                "document {index}: embedding has {} dimensions, expected {dimensions}",
+                embedding.len()
+            )));
+        }
+        if !embedding.iter().all(|x| x.is_finite()) {
+            return Err(CommonError::Embedding(format!(
+                "document {index}: embedding contains non-finite values"
+            )));
+        }
+    }
+    Ok(batch)
+}
+
+fn parse_batch_size(value: &str) -> Option<usize> {
+    value.trim().parse::<usize>().ok().filter(|&n| n >= 1)
+}
+
+/// Redis key for a query vector: `sha256(model|query)`, so switching models never serves
+/// vectors from another embedding space.
+fn query_cache_key(model_name: &str, query: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(model_name.as_bytes());
+    hasher.update(b"|");
+    hasher.update(query.as_bytes());
+    format!("embedding:query:{:x}", hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blank_documents_embed_a_placeholder() {
+        assert_eq!(document_text(0, "raii"), "raii");
+        assert_eq!(document_text(1, " \n\t"), BLANK_DOCUMENT_PLACEHOLDER);
+        assert!(!BLANK_DOCUMENT_PLACEHOLDER.trim().is_empty());
+    }
+
+    #[tokio::test]
+    #[ignore = "downloads the default embedding model"]
+    async fn blank_documents_get_finite_vectors() {
+        let embedder = Embedder::new().await.unwrap();
+        let texts = vec!["Prefer RAII".to_string(), "   ".to_string()];
+        let embeddings = embedder.embed_documents(&texts).await.unwrap();
+        assert_eq!(embeddings.len(), 2);
+        let blank = &embeddings[1];
+        assert_eq!(blank.len(), embedder.dimensions());
+        assert!(blank.iter().all(|x| x.is_finite()));
+        assert!(blank.iter().any(|&x| x != 0.0));
+    }
+
+    #[test]
+    fn batches_are_checked_for_count_dimensions_and_finite_values() {
+        let batch = vec![vec![1.0, 2.0], vec![0.5, 0.5], vec![3.0, 4.0]];
+        assert_eq!(check_batch(4, 3, batch.clone(), 2).unwrap(), batch);
+
+        let message = |result: Result<Vec<Vec<f32>>, CommonError>| result.unwrap_err().to_string();
+        let short = check_batch(4, 2, vec![vec![1.0, 2.0], vec![3.0]], 2);
+        assert!(message(short).contains("document 5: embedding has 1 dimensions, expected 2"));
+        let nan = check_batch(4, 2, vec![vec![f32::NAN, 2.0], vec![3.0, 4.0]], 2);
+        assert!(message(nan).contains("document 4: embedding contains non-finite values"));
+        let missing = check_batch(4, 3, vec![vec![1.0, 2.0]], 2);
+        assert!(message(missing).contains("documents 4..7: expected 3, got 1"));
+    }
+
+    #[test]
+    fn batch_size_must_be_positive() {
+        assert_eq!(parse_batch_size(" 32 "), Some(32));
+        assert_eq!(parse_batch_size("1"), Some(1));
+        assert_eq!(parse_batch_size("0"), None);
+        assert_eq!(parse_batch_size("-4"), None);
+        assert_eq!(parse_batch_size("lots"), None);
+    }
+
+    #[test]
+    fn query_cache_key_depends_on_model_and_query() {
+        let key = query_cache_key("nomic-embed-text-v1.5", "raii");
+        assert!(key.starts_with("embedding:query:"));
+        assert_eq!(key, query_cache_key("nomic-embed-text-v1.5", "raii"));
+        assert_ne!(key, query_cache_key("all-minilm-l6-v2", "raii"));
+        assert_ne!(key, query_cache_key("nomic-embed-text-v1.5", "raii "));
+    }
+
+    #[test]
+    fn looks_up_models_by_name_or_variant() {
+        let mini = EmbeddingModelSpec::from_name("AllMiniLML6V2").unwrap();
+        assert_eq!(mini.name, "all-minilm-l6-v2");
+        assert_eq!(mini.dimensions, 384);
+        assert_eq!(
+            EmbeddingModelSpec::from_name(" Nomic-Embed-Text-v1.5 ").unwrap().dimensions,
+            768
+        );
+        assert!(EmbeddingModelSpec::from_name("gpt-embed").is_none());
+        assert_eq!(MODELS[0].name, "nomic-embed-text-v1.5");
+    }
+
+    #[test]
+    fn only_nomic_models_get_task_prefixes() {
+        let nomic = EmbeddingModelSpec::from_name("nomic-embed-text-v1.5").unwrap();
+        assert_eq!(nomic.document_input("x"), "search_document: x");
+        assert_eq!(nomic.query_input("x"), "search_query: x");
+
+        let bge = EmbeddingModelSpec::from_name("bge-small-en-v1.5").unwrap();
+        assert_eq!(bge.document_input("x"), "x");
+        assert_eq!(bge.query_input("x"), "x");
     }
 }
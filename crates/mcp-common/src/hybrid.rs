@@ -0,0 +1,131 @@
+/// Keyword scoring and score blending for hybrid search.
+///
+/// Keyword matching is a case-insensitive substring test of the whole query against a
+/// row's id, title and embedded text, scored by where it hits:
+/// - the id equals the query: 1.0
+/// - the id or title contains the query: 0.8
+/// - only the text contains the query: 0.6
+///
+/// Hybrid search blends both rankings as
+/// `(1 - KEYWORD_WEIGHT) * semantic + KEYWORD_WEIGHT * keyword`, where a side that did not
+/// return the row contributes 0. An exact identifier hit therefore outranks rows that only
+/// match semantically, while rows found by both searches rise above rows found by one.
+use std::collections::HashMap;
+
+/// Weight of the keyword score in a hybrid score; the semantic score gets the rest.
+pub const KEYWORD_WEIGHT: f32 = 0.5;
+
+/// Keyword score of a row for `query`, or `None` if the query does not occur in it.
+pub fn keyword_score(query: &str, id: &str, title: &str, text: &str) -> Option<f32> {
+    let needle = query.trim().to_lowercase();
+    if needle.is_empty() {
+        return None;
+    }
+    let id = id.to_lowercase();
+    if id == needle {
+        Some(1.0)
+    } else if id.contains(&needle) || title.to_lowercase().contains(&needle) {
+        Some(0.8)
+    } else if text.to_lowercase().contains(&needle) {
+        Some(0.6)
+    } else {
+        None
+    }
+}
+
+/// Blend a row's semantic and keyword scores; `None` means that search missed the row.
+pub fn hybrid_score(semantic: Option<f32>, keyword: Option<f32>) -> f32 {
+    (1.0 - KEYWORD_WEIGHT) * semantic.unwrap_or(0.0) + KEYWORD_WEIGHT * keyword.unwrap_or(0.0)
+}
+
+/// Merge two `(id, score)` rankings into one hybrid ranking without duplicate ids.
+///
+/// Results are sorted by blended score, highest first, with ties broken by id.
+pub fn merge_rankings(
+    semantic: &[(String, f32)],
+    keyword: &[(String, f32)],
+) -> Vec<(String, f32)> {
+    let mut scores: HashMap<&str, (Option<f32>, Option<f32>)> = HashMap::new();
+    for (id, score) in semantic {
+        scores.entry(id.as_str()).or_default().0 = Some(*score);
+    }
+    for (id, score) in keyword {
+        scores.entry(id.as_str()).or_default().1 = Some(*score);
+    }
+
+    let mut merged: Vec<(String, f32)> = scores
+        .into_iter()
+        .map(|(id, (semantic, keyword))| (id.to_string(), hybrid_score(semantic, keyword)))
+        .collect();
+    merged.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    merged
+}
+
+/// Heading of the section sharing the most distinct query terms, or `None` if no section
+/// contains any of them. Ties go to the earlier section.
+///
+/// Terms are the query's alphanumeric words, compared case-insensitively against each
+/// section's heading and content.
+pub fn best_matching_section<'a>(
+    query: &str,
+    sections: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> Option<&'a str> {
+    let mut terms: Vec<String> = query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    terms.sort();
+    terms.dedup();
+
+    let mut best: Option<(&str, usize)> = None;
+    for (heading, content) in sections {
+        let haystack = format!("{heading}\n{content}").to_lowercase();
+        let hits = terms.iter().filter(|t| haystack.contains(t.as_str())).count();
+        if hits > 0 && best.is_none_or(|(_, most)| hits > most) {
+            best = Some((heading, hits));
+        }
+    }
+    best.map(|(heading, _)| heading)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keyword_score_prefers_exact_ids_over_titles_over_text() {
+        assert_eq!(keyword_score("sl.con.1", "SL.con.1", "Prefer vector", ""), Some(1.0));
+        assert_eq!(keyword_score("SL.con", "SL.con.1", "Prefer vector", ""), Some(0.8));
+        assert_eq!(keyword_score("NOEXCEPT", "F.6", "Declare noexcept", ""), Some(0.8));
+        assert_eq!(keyword_score("noexcept", "E.12", "Errors", "use noexcept when"), Some(0.6));
+        assert_eq!(keyword_score("noexcept", "P.1", "Express ideas", "directly"), None);
+        assert_eq!(keyword_score("  ", "P.1", "Express ideas", "directly"), None);
+    }
+
+    #[test]
+    fn merge_deduplicates_and_ranks_rows_found_by_both_first() {
+        let semantic = [("a".to_string(), 0.9), ("b".to_string(), 0.8)];
+        let keyword = [("b".to_string(), 0.6), ("c".to_string(), 1.0)];
+
+        let merged = merge_rankings(&semantic, &keyword);
+        let ids: Vec<&str> = merged.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, ["b", "c", "a"]);
+        assert!((merged[0].1 - 0.7).abs() < 1e-6);
+        assert!((merged[1].1 - 0.5).abs() < 1e-6);
+        assert!((merged[2].1 - 0.45).abs() < 1e-6);
+    }
+
+    #[test]
+    fn best_matching_section_picks_the_section_with_most_query_terms() {
+        let sections = [
+            ("Reason", "Compilers don't read comments."),
+            ("Enforcement", "Flag raw new and delete outside owners."),
+            ("Example", "Use new carefully."),
+        ];
+        assert_eq!(best_matching_section("raw NEW delete", sections), Some("Enforcement"));
+        assert_eq!(best_matching_section("new", sections), Some("Enforcement"));
+        assert_eq!(best_matching_section("reason", sections), Some("Reason"));
+        assert_eq!(best_matching_section("templates", sections), None);
+    }
+}
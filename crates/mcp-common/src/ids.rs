@@ -0,0 +1,161 @@
+/// Loose matching of guideline ids and category keys as typed by people and models.
+///
+/// Ids are compared in a normalized form: uppercase, with everything but letters and digits
+/// removed, so `p1`, `P 1` and `P..1` all find `P.1`, and `es 20` finds `ES.20`. An exact
+/// case-insensitive match always wins; a normalized match is only used when it is unique.
+/// A guideline can also be named by its anchor, bare or as the fragment of a documentation
+/// link, so deep links resolve too.
+
+/// Number of suggestions included in a "not found" message.
+pub const SUGGESTION_COUNT: usize = 3;
+
+/// Uppercase `id` and drop spaces, dots, dashes and any other separators.
+pub fn normalize_id(id: &str) -> String {
+    id.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_uppercase)
+        .collect()
+}
+
+/// The id in `ids` that `query` refers to, or `None` when there is no match or the normalized
+/// form is ambiguous.
+pub fn resolve_id<'a>(query: &str, ids: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let query = query.trim();
+    let normalized = normalize_id(query);
+    let mut loose: Option<&str> = None;
+    let mut ambiguous = false;
+    for id in ids {
+        if id.eq_ignore_ascii_case(query) {
+            return Some(id);
+        }
+        if !normalized.is_empty() && normalize_id(id) == normalized {
+            ambiguous |= loose.is_some();
+            loose = Some(id);
+        }
+    }
+    loose.filter(|_| !ambiguous)
+}
+
+/// Whether `link` names `anchor`: either the anchor itself or a URL whose `#` fragment it is,
+/// such as `CppCoreGuidelines.md#Rp-direct`, compared case-insensitively.
+pub fn matches_anchor(link: &str, anchor: &str) -> bool {
+    let link = link.trim();
+    let fragment = link.rsplit_once('#').map_or(link, |(_, fragment)| fragment);
+    !fragment.is_empty() && fragment.eq_ignore_ascii_case(anchor)
+}
+
+/// Up to `n` ids closest to `query` by edit distance between normalized forms, closest
+/// first, with ties broken by id.
+pub fn closest_ids<'a>(
+    query: &str,
+    ids: impl IntoIterator<Item = &'a str>,
+    n: usize,
+) -> Vec<&'a str> {
+    let normalized = normalize_id(query);
+    let mut scored: Vec<(usize, &str)> = ids
+        .into_iter()
+        .map(|id| (edit_distance(&normalized, &normalize_id(id)), id))
+        .collect();
+    scored.sort_unstable();
+    scored.into_iter().take(n).map(|(_, id)| id).collect()
+}
+
+/// `guideline not found: {query}`, followed by the closest ids when there are any.
+pub fn not_found_message<'a>(query: &str, ids: impl IntoIterator<Item = &'a str>) -> String {
+    let closest = closest_ids(query, ids, SUGGESTION_COUNT);
+    if closest.is_empty() {
+        format!("guideline not found: {query}")
+    } else {
+        format!("guideline not found: {query}. Closest ids: {}", closest.join(", "))
+    }
+}
+
+/// The candidate closest to `query` by case-insensitive edit distance, with ties broken by
+/// candidate; `None` when there are no candidates.
+pub fn closest_match<'a>(
+    query: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<&'a str> {
+    let query = query.to_lowercase();
+    candidates
+        .into_iter()
+        .min_by_key(|c| (edit_distance(&query, &c.to_lowercase()), *c))
+}
+
+/// Levenshtein distance between two strings, counted in chars.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IDS: [&str; 6] = ["P.1", "P.10", "ES.20", "SL.con.1", "C-CASE", "1.10"];
+
+    #[test]
+    fn resolves_loosely_typed_ids() {
+        assert_eq!(resolve_id("p.1", IDS), Some("P.1"));
+        assert_eq!(resolve_id("p1", IDS), Some("P.1"));
+        assert_eq!(resolve_id("ES 20", IDS), Some("ES.20"));
+        assert_eq!(resolve_id("sl.con..1", IDS), Some("SL.con.1"));
+        assert_eq!(resolve_id("c case", IDS), Some("C-CASE"));
+        assert_eq!(resolve_id("P.2", IDS), None);
+        assert_eq!(resolve_id("...", IDS), None);
+    }
+
+    #[test]
+    fn ambiguous_normalized_ids_need_the_exact_form() {
+        let ids = ["1.10", "11.0"];
+        assert_eq!(resolve_id("110", ids), None);
+        assert_eq!(resolve_id("11.0", ids), Some("11.0"));
+    }
+
+    #[test]
+    fn anchors_match_bare_or_as_link_fragments() {
+        assert!(matches_anchor("rp-direct", "Rp-direct"));
+        assert!(matches_anchor(" #Rp-direct ", "Rp-direct"));
+        assert!(matches_anchor(
+            "https://isocpp.github.io/CppCoreGuidelines/CppCoreGuidelines#Rp-direct",
+            "Rp-direct"
+        ));
+        assert!(!matches_anchor("rp", "Rp-direct"));
+        assert!(!matches_anchor("https://example.com/#", ""));
+    }
+
+    #[test]
+    fn suggests_the_closest_ids() {
+        assert_eq!(closest_ids("ES.2", IDS, 2), ["ES.20", "1.10"]);
+        assert_eq!(
+            not_found_message("P.100", IDS),
+            "guideline not found: P.100. Closest ids: P.10, 1.10, P.1"
+        );
+        assert_eq!(not_found_message("P.1", []), "guideline not found: P.1");
+    }
+
+    #[test]
+    fn closest_match_ignores_case() {
+        let categories = ["Naming", "Macros", "Type safety"];
+        assert_eq!(closest_match("Naeming", categories), Some("Naming"));
+        assert_eq!(closest_match("TYPE SAFTEY", categories), Some("Type safety"));
+        assert_eq!(closest_match("es", ["ES", "E", "SL"]), Some("ES"));
+        assert_eq!(closest_match("P", []), None);
+    }
+
+    #[test]
+    fn edit_distance_counts_single_char_edits() {
+        assert_eq!(edit_distance("", "ABC"), 3);
+        assert_eq!(edit_distance("P10", "P1"), 1);
+        assert_eq!(edit_distance("ES20", "SE20"), 2);
+    }
+}
@@ -1,7 +1,17 @@
+pub mod batch;
 pub mod embedding;
 pub mod error;
+pub mod hybrid;
+pub mod ids;
 pub mod llm_state;
 pub mod mcp_api;
 pub mod openai;
 pub mod redis;
+pub mod render;
+pub mod shutdown;
+pub mod spans;
+pub mod summary;
+pub mod tokens;
+pub mod tool_timeout;
+pub mod unique_id;
 pub mod vectordb;
@@ -1,14 +1,13 @@
-use std::sync::atomic::{AtomicU64, Ordering};
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::collections::HashMap;
+use std::time::Duration;
 
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use sha2::{Digest, Sha256};
+use tracing::warn;
 
 use crate::openai::{ChatCompletionUsage, Message};
-use crate::redis::RedisCache;
-
-static CONVO_COUNTER: AtomicU64 = AtomicU64::new(0);
+use crate::redis::{RedisCache, VersionedSet};
+use crate::unique_id::new_unique_id;
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct UsageStats {
@@ -21,18 +20,55 @@ pub struct ModelUsageStats {
     pub model: String,
     pub requests: u64,
     pub total_tokens: Option<u64>,
+    pub prompt_tokens: Option<u64>,
+    pub completion_tokens: Option<u64>,
+    /// Spend at `MODEL_PRICING` rates; `None` when the model has no pricing entry or
+    /// upstream never reported a prompt/completion split.
+    pub estimated_cost_usd: Option<f64>,
     pub token_counted_requests: u64,
     pub token_unknown_requests: u64,
 }
 
+/// USD price per 1k tokens for one model.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModelPricing {
+    pub prompt_per_1k: f64,
+    pub completion_per_1k: f64,
+}
+
+impl ModelPricing {
+    pub fn cost(&self, prompt_tokens: u64, completion_tokens: u64) -> f64 {
+        let prompt = prompt_tokens as f64 * self.prompt_per_1k;
+        let completion = completion_tokens as f64 * self.completion_per_1k;
+        (prompt + completion) / 1000.0
+    }
+}
+
 #[derive(Clone)]
 pub struct UsageTracker {
     redis: RedisCache,
+    pricing: HashMap<String, ModelPricing>,
 }
 
 impl UsageTracker {
+    /// Reads `MODEL_PRICING` (`model:prompt/completion` pairs in USD per 1k tokens,
+    /// separated by commas) for cost estimates.
     pub fn new(redis: RedisCache) -> Self {
-        Self { redis }
+        let pricing = std::env::var("MODEL_PRICING")
+            .ok()
+            .map(|s| parse_pricing(&s))
+            .unwrap_or_default();
+        Self { redis, pricing }
+    }
+
+    /// Whether `MODEL_PRICING` has an entry for `model`.
+    pub fn has_pricing(&self, model: &str) -> bool {
+        self.pricing.contains_key(model)
+    }
+
+    /// Returns `true` if Redis is reachable.
+    pub async fn redis_available(&self) -> bool {
+        self.redis.is_available().await
     }
 
     pub async fn record(&self, model: &str, usage: Option<&ChatCompletionUsage>) {
@@ -41,7 +77,25 @@ impl UsageTracker {
             .hincr_by("llm_proxy:usage", &format!("requests:{model}"), 1)
             .await;
 
-        match usage.and_then(|u| u.total_tokens) {
+        // Prompt and completion are tracked separately so they can be priced differently.
+        if let Some(prompt) = usage.and_then(|u| u.prompt_tokens) {
+            let _ = self
+                .redis
+                .hincr_by("llm_proxy:usage", &format!("tokens_prompt:{model}"), prompt as i64)
+                .await;
+        }
+        if let Some(completion) = usage.and_then(|u| u.completion_tokens) {
+            let _ = self
+                .redis
+                .hincr_by(
+                    "llm_proxy:usage",
+                    &format!("tokens_completion:{model}"),
+                    completion as i64,
+                )
+                .await;
+        }
+
+        match usage.and_then(total_tokens) {
             Some(total) => {
                 let _ = self
                     .redis
@@ -81,6 +135,9 @@ impl UsageTracker {
                 model: model.to_string(),
                 requests: 0,
                 total_tokens: None,
+                prompt_tokens: None,
+                completion_tokens: None,
+                estimated_cost_usd: None,
                 token_counted_requests: 0,
                 token_unknown_requests: 0,
             });
@@ -89,12 +146,18 @@ impl UsageTracker {
             match kind {
                 "requests" => stat.requests = parsed,
                 "tokens_total" => stat.total_tokens = Some(parsed),
+                "tokens_prompt" => stat.prompt_tokens = Some(parsed),
+                "tokens_completion" => stat.completion_tokens = Some(parsed),
                 "tokens_known_requests" => stat.token_counted_requests = parsed,
                 "tokens_unknown_requests" => stat.token_unknown_requests = parsed,
                 _ => {}
             }
         }
 
+        for stat in by_model.values_mut() {
+            stat.estimated_cost_usd = self.estimate_cost(stat);
+        }
+
         let mut models: Vec<ModelUsageStats> = by_model.into_values().collect();
         models.sort_by(|a, b| a.model.cmp(&b.model));
         UsageStats {
@@ -102,14 +165,102 @@ impl UsageTracker {
             redis_available,
         }
     }
+
+    fn estimate_cost(&self, stat: &ModelUsageStats) -> Option<f64> {
+        let pricing = self.pricing.get(&stat.model)?;
+        if stat.prompt_tokens.is_none() && stat.completion_tokens.is_none() {
+            return None;
+        }
+        Some(pricing.cost(
+            stat.prompt_tokens.unwrap_or(0),
+            stat.completion_tokens.unwrap_or(0),
+        ))
+    }
+}
+
+/// Reported total, or prompt + completion when upstream only sends the split.
+fn total_tokens(usage: &ChatCompletionUsage) -> Option<u64> {
+    usage
+        .total_tokens
+        .or_else(|| Some(usage.prompt_tokens? + usage.completion_tokens?))
+}
+
+/// Parse `model:prompt/completion` pairs separated by commas. The prices follow the last `:`
+/// so model ids with tags (`llama3:8b:0.1/0.2`) work. Malformed entries are skipped with a
+/// warning.
+fn parse_pricing(value: &str) -> HashMap<String, ModelPricing> {
+    let mut pricing = HashMap::new();
+    for entry in value.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let parsed = entry.rsplit_once(':').and_then(|(model, prices)| {
+            let (prompt, completion) = prices.split_once('/')?;
+            let price =
+                |s: &str| s.trim().parse::<f64>().ok().filter(|p| p.is_finite() && *p >= 0.0);
+            let prices = ModelPricing {
+                prompt_per_1k: price(prompt)?,
+                completion_per_1k: price(completion)?,
+            };
+            let model = model.trim();
+            (!model.is_empty()).then(|| (model.to_string(), prices))
+        });
+        match parsed {
+            Some((model, prices)) => {
+                pricing.insert(model, prices);
+            }
+            None => warn!(entry, "ignoring malformed MODEL_PRICING entry"),
+        }
+    }
+    pricing
 }
 
 pub type ConversationId = String;
 
+/// A conversation's history together with the version it was read at.
+///
+/// Pass `version` back to [`ConversationStore::set_messages`] so a concurrent update made
+/// in between is detected instead of silently overwritten.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConversationSnapshot {
+    pub version: u64,
+    pub messages: Vec<Message>,
+}
+
+/// One stored conversation as reported by [`ConversationStore::list`].
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ConversationInfo {
+    pub conversation_id: ConversationId,
+    pub version: u64,
+    /// Stored messages, including the system prompt.
+    pub message_count: usize,
+    /// Seconds until the conversation expires; every stored turn resets it to `CONVO_TTL_SECS`.
+    pub ttl_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ConversationList {
+    /// Soonest to expire (least recently updated) first.
+    pub conversations: Vec<ConversationInfo>,
+    pub redis_available: bool,
+}
+
+/// Why a conversation update was not stored.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ConversationWriteError {
+    #[error("unknown conversation_id: {0}")]
+    NotFound(ConversationId),
+
+    #[error("conversation {0} was updated concurrently; reload it and retry")]
+    Conflict(ConversationId),
+
+    #[error("failed to persist conversation state")]
+    Unavailable,
+}
+
 #[derive(Clone)]
 pub struct ConversationStore {
     redis: RedisCache,
     ttl_secs: u64,
+    /// Most recent non-system messages kept per conversation; `None` keeps everything.
+    max_messages: Option<usize>,
 }
 
 impl ConversationStore {
@@ -118,66 +269,410 @@ impl ConversationStore {
             .ok()
             .and_then(|s| s.parse::<u64>().ok())
             .unwrap_or(86_400);
-        Self { redis, ttl_secs }
+        // Unset or 0 keeps the full history.
+        let max_messages = std::env::var("CONVO_MAX_MESSAGES")
+            .ok()
+            .and_then(|s| s.parse::<usize>().ok())
+            .filter(|&n| n > 0);
+        Self {
+            redis,
+            ttl_secs,
+            max_messages,
+        }
+    }
+
+    /// Cap on stored non-system messages (`CONVO_MAX_MESSAGES`), if any.
+    pub fn max_messages(&self) -> Option<usize> {
+        self.max_messages
     }
 
     pub fn ttl(&self) -> Duration {
         Duration::from_secs(self.ttl_secs)
     }
 
-    pub async fn start(&self) -> ConversationId {
-        let id = new_conversation_id();
-        let _ = self
-            .redis
-            .set_with_ttl(&convo_key(&id), "[]", self.ttl_secs)
-            .await;
+    /// Start a conversation, storing `system` (if non-blank) as the first message.
+    pub async fn start(&self, system: Option<&str>) -> ConversationId {
+        let id = new_unique_id();
+        self.create(&id, initial_messages(system)).await;
         id
     }
 
+    /// Copy a conversation's history under a new id, leaving the original untouched. The
+    /// copy starts at version 0 with a fresh `CONVO_TTL_SECS` expiry, like [`Self::start`].
+    pub async fn fork(
+        &self,
+        conversation_id: &str,
+    ) -> Result<ConversationId, ConversationWriteError> {
+        let snapshot = self
+            .load(conversation_id)
+            .await
+            .ok_or_else(|| ConversationWriteError::NotFound(conversation_id.to_string()))?;
+        let id = new_unique_id();
+        if self.create(&id, snapshot.messages).await {
+            Ok(id)
+        } else {
+            Err(ConversationWriteError::Unavailable)
+        }
+    }
+
+    /// Store `messages` as a new conversation at version 0. Returns `true` if it was written.
+    async fn create(&self, id: &str, messages: Vec<Message>) -> bool {
+        let snapshot = ConversationSnapshot {
+            version: 0,
+            messages,
+        };
+        match serde_json::to_string(&snapshot) {
+            Ok(raw) => self.redis.set_with_ttl(&convo_key(id), &raw, self.ttl_secs).await,
+            Err(_) => false,
+        }
+    }
+
     pub async fn end(&self, conversation_id: &str) {
         let _ = self.redis.delete(&convo_key(conversation_id)).await;
     }
 
-    pub async fn get_messages(&self, conversation_id: &str) -> Option<Vec<Message>> {
+    /// Read a conversation's history and current version.
+    pub async fn load(&self, conversation_id: &str) -> Option<ConversationSnapshot> {
         let raw = self.redis.get(&convo_key(conversation_id)).await?;
-        serde_json::from_str::<Vec<Message>>(&raw).ok()
+        decode_snapshot(&raw)
+    }
+
+    pub async fn get_messages(&self, conversation_id: &str) -> Option<Vec<Message>> {
+        self.load(conversation_id).await.map(|s| s.messages)
+    }
+
+    /// IDs of all stored conversations, sorted. Returns `None` if Redis is unavailable.
+    pub async fn list_ids(&self) -> Option<Vec<ConversationId>> {
+        let keys = self.redis.keys_by_prefix(&convo_key("")).await?;
+        Some(conversation_ids(&keys))
+    }
+
+    /// Every stored conversation with its message count and remaining TTL, soonest to expire
+    /// first. Conversations that expire while being listed are left out.
+    pub async fn list(&self) -> ConversationList {
+        let Some(ids) = self.list_ids().await else {
+            return ConversationList {
+                conversations: vec![],
+                redis_available: self.redis.is_available().await,
+            };
+        };
+        let keys: Vec<String> = ids.iter().map(|id| convo_key(id)).collect();
+        let (values, ttls) = tokio::join!(self.redis.mget(&keys), self.redis.ttls(&keys));
+        let (Some(values), Some(ttls)) = (values, ttls) else {
+            return ConversationList {
+                conversations: vec![],
+                redis_available: false,
+            };
+        };
+
+        let mut conversations: Vec<ConversationInfo> = ids
+            .into_iter()
+            .zip(values)
+            .zip(ttls)
+            .filter_map(|((conversation_id, raw), ttl_secs)| {
+                let snapshot = decode_snapshot(&raw?)?;
+                Some(ConversationInfo {
+                    conversation_id,
+                    version: snapshot.version,
+                    message_count: snapshot.messages.len(),
+                    ttl_secs,
+                })
+            })
+            .collect();
+        conversations.sort_by_key(|c| c.ttl_secs.unwrap_or(u64::MAX));
+        ConversationList {
+            conversations,
+            redis_available: true,
+        }
     }
 
-    pub async fn set_messages(&self, conversation_id: &str, messages: &[Message]) -> bool {
-        let Ok(raw) = serde_json::to_string(messages) else {
-            return false;
+    /// Replace the history of a conversation read at `expected_version`.
+    ///
+    /// When `CONVO_MAX_MESSAGES` is set, only the leading system message and the most recent
+    /// messages up to the cap are stored.
+    ///
+    /// Fails with [`ConversationWriteError::Conflict`] if another writer stored a newer
+    /// version in the meantime. Returns the new version on success.
+    pub async fn set_messages(
+        &self,
+        conversation_id: &str,
+        expected_version: u64,
+        messages: &[Message],
+    ) -> Result<u64, ConversationWriteError> {
+        let snapshot = ConversationSnapshot {
+            version: expected_version + 1,
+            messages: trim_history(messages, self.max_messages),
         };
-        self.redis
-            .set_with_ttl(&convo_key(conversation_id), &raw, self.ttl_secs)
+        let raw =
+            serde_json::to_string(&snapshot).map_err(|_| ConversationWriteError::Unavailable)?;
+        match self
+            .redis
+            .set_if_version(&convo_key(conversation_id), expected_version, &raw, self.ttl_secs)
             .await
+        {
+            Some(VersionedSet::Written) => Ok(snapshot.version),
+            Some(VersionedSet::Conflict) => {
+                Err(ConversationWriteError::Conflict(conversation_id.to_string()))
+            }
+            Some(VersionedSet::Missing) => {
+                Err(ConversationWriteError::NotFound(conversation_id.to_string()))
+            }
+            None => Err(ConversationWriteError::Unavailable),
+        }
     }
 }
 
+/// Decode stored state, accepting the plain message arrays written before versioning (version 0).
+fn decode_snapshot(raw: &str) -> Option<ConversationSnapshot> {
+    serde_json::from_str::<ConversationSnapshot>(raw)
+        .ok()
+        .or_else(|| {
+            serde_json::from_str::<Vec<Message>>(raw)
+                .ok()
+                .map(|messages| ConversationSnapshot { version: 0, messages })
+        })
+}
+
+/// Keep a leading system message plus the last `max` other messages.
+fn trim_history(messages: &[Message], max: Option<usize>) -> Vec<Message> {
+    let Some(max) = max else {
+        return messages.to_vec();
+    };
+    let (system, rest) = match messages.split_first() {
+        Some((first, rest)) if first.role == "system" => (Some(first), rest),
+        _ => (None, messages),
+    };
+    let recent = &rest[rest.len().saturating_sub(max)..];
+    system.into_iter().chain(recent).cloned().collect()
+}
+
+/// Seed history for a new conversation: a single `system` message, or nothing.
+fn initial_messages(system: Option<&str>) -> Vec<Message> {
+    system
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| vec![Message::new("system", s)])
+        .unwrap_or_default()
+}
+
 fn convo_key(conversation_id: &str) -> String {
     format!("llm_proxy:convo:{conversation_id}")
 }
 
-fn new_conversation_id() -> String {
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_else(|_| Duration::from_secs(0));
-    let counter = CONVO_COUNTER.fetch_add(1, Ordering::Relaxed);
-    let pid = std::process::id();
-
-    let mut h = Sha256::new();
-    h.update(now.as_nanos().to_le_bytes());
-    h.update(pid.to_le_bytes());
-    h.update(counter.to_le_bytes());
-    let digest = h.finalize();
-    hex_lower(&digest[..16])
+/// Conversation IDs from keys produced by [`convo_key`].
+fn conversation_ids(keys: &[String]) -> Vec<ConversationId> {
+    keys.iter()
+        .filter_map(|key| key.strip_prefix(&convo_key("")))
+        .map(str::to_string)
+        .collect()
 }
 
-fn hex_lower(bytes: &[u8]) -> String {
-    const HEX: &[u8; 16] = b"0123456789abcdef";
-    let mut out = String::with_capacity(bytes.len() * 2);
-    for &b in bytes {
-        out.push(HEX[(b >> 4) as usize] as char);
-        out.push(HEX[(b & 0x0f) as usize] as char);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_model_pricing() {
+        let pricing = parse_pricing(" gpt4:0.03/0.06, llama3:8b:0/0.001 ,bad,x:1,y:a/b,:1/2");
+        assert_eq!(pricing.len(), 2);
+        assert_eq!(
+            pricing["gpt4"],
+            ModelPricing {
+                prompt_per_1k: 0.03,
+                completion_per_1k: 0.06
+            }
+        );
+        assert_eq!(pricing["llama3:8b"].completion_per_1k, 0.001);
+    }
+
+    #[test]
+    fn cost_uses_separate_prompt_and_completion_rates() {
+        let tracker = UsageTracker {
+            redis: RedisCache::new(None),
+            pricing: parse_pricing("gpt4:0.03/0.06"),
+        };
+        let mut stat = ModelUsageStats {
+            model: "gpt4".to_string(),
+            requests: 1,
+            total_tokens: Some(3000),
+            prompt_tokens: Some(2000),
+            completion_tokens: Some(1000),
+            estimated_cost_usd: None,
+            token_counted_requests: 1,
+            token_unknown_requests: 0,
+        };
+        let cost = tracker.estimate_cost(&stat).unwrap();
+        assert!((cost - 0.12).abs() < 1e-9, "{cost}");
+
+        stat.prompt_tokens = None;
+        stat.completion_tokens = None;
+        assert_eq!(tracker.estimate_cost(&stat), None);
+        stat.model = "unpriced".to_string();
+        stat.prompt_tokens = Some(10);
+        assert_eq!(tracker.estimate_cost(&stat), None);
+    }
+
+    #[test]
+    fn total_tokens_falls_back_to_prompt_plus_completion() {
+        let usage = |prompt, completion, total| ChatCompletionUsage {
+            prompt_tokens: prompt,
+            completion_tokens: completion,
+            total_tokens: total,
+        };
+        assert_eq!(total_tokens(&usage(Some(5), Some(7), Some(12))), Some(12));
+        assert_eq!(total_tokens(&usage(Some(5), Some(7), None)), Some(12));
+        assert_eq!(total_tokens(&usage(Some(5), None, None)), None);
+    }
+
+    #[test]
+    fn initial_messages_seed_system_prompt() {
+        let messages = initial_messages(Some("  Be terse.  "));
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].role, "system");
+        assert_eq!(messages[0].content, "Be terse.");
+
+        assert!(initial_messages(None).is_empty());
+        assert!(initial_messages(Some("   ")).is_empty());
+    }
+
+    #[test]
+    fn trim_keeps_system_prompt_and_recent_turns() {
+        let messages = vec![
+            Message::new("system", "sys"),
+            Message::new("user", "u1"),
+            Message::new("assistant", "a1"),
+            Message::new("user", "u2"),
+            Message::new("assistant", "a2"),
+        ];
+        let trimmed = trim_history(&messages, Some(2));
+        let contents: Vec<_> = trimmed.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(contents, ["sys", "u2", "a2"]);
+
+        let no_system = trim_history(&messages[1..], Some(3));
+        let contents: Vec<_> = no_system.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(contents, ["a1", "u2", "a2"]);
+
+        assert_eq!(trim_history(&messages, None).len(), 5);
+        assert_eq!(trim_history(&messages, Some(10)).len(), 5);
+    }
+
+    #[test]
+    fn decodes_versioned_and_legacy_state() {
+        let snapshot = decode_snapshot(r#"{"version":3,"messages":[{"role":"user","content":"hi"}]}"#)
+            .unwrap();
+        assert_eq!(snapshot.version, 3);
+        assert_eq!(snapshot.messages.len(), 1);
+
+        let legacy = decode_snapshot(r#"[{"role":"user","content":"hi"}]"#).unwrap();
+        assert_eq!(legacy.version, 0);
+        assert_eq!(legacy.messages[0].content, "hi");
+
+        assert!(decode_snapshot("not json").is_none());
+    }
+
+    #[test]
+    fn conversation_ids_strip_the_key_prefix() {
+        let keys = vec![convo_key("abc"), "llm_proxy:usage".to_string(), convo_key("def")];
+        assert_eq!(conversation_ids(&keys), ["abc", "def"]);
+    }
+
+    /// Two writers read the same version and race to append a turn: exactly one wins and the
+    /// loser gets a conflict instead of silently overwriting. Needs a live Redis (`REDIS_URL`).
+    #[tokio::test]
+    async fn concurrent_appends_do_not_lose_turns() {
+        let Ok(url) = std::env::var("REDIS_URL") else {
+            return;
+        };
+        let redis = RedisCache::new(Some(&url));
+        if !redis.is_available().await {
+            return;
+        }
+        let store = ConversationStore::new(redis);
+        let id = store.start(Some("system prompt")).await;
+
+        let (a, b) = tokio::join!(store.load(&id), store.load(&id));
+        let (mut a, mut b) = (a.unwrap(), b.unwrap());
+        a.messages.push(Message::new("user", "from a"));
+        b.messages.push(Message::new("user", "from b"));
+
+        let (ra, rb) = tokio::join!(
+            store.set_messages(&id, a.version, &a.messages),
+            store.set_messages(&id, b.version, &b.messages),
+        );
+        let stored = store.load(&id).await.unwrap();
+        store.end(&id).await;
+
+        assert_eq!(stored.version, 1);
+        let winner = match (ra, rb) {
+            (Ok(1), Err(ConversationWriteError::Conflict(_))) => "from a",
+            (Err(ConversationWriteError::Conflict(_)), Ok(1)) => "from b",
+            other => panic!("expected exactly one write to win: {other:?}"),
+        };
+        assert_eq!(stored.messages.len(), 2);
+        assert_eq!(stored.messages[0].role, "system");
+        assert_eq!(stored.messages[1].content, winner);
+    }
+
+    /// Needs a live Redis (`REDIS_URL`).
+    #[tokio::test]
+    async fn lists_stored_conversations_with_counts_and_ttl() {
+        let Ok(url) = std::env::var("REDIS_URL") else {
+            return;
+        };
+        let redis = RedisCache::new(Some(&url));
+        if !redis.is_available().await {
+            return;
+        }
+        let store = ConversationStore::new(redis);
+        let id = store.start(Some("system prompt")).await;
+
+        let listed = store.list().await;
+        store.end(&id).await;
+
+        assert!(listed.redis_available);
+        let info = listed
+            .conversations
+            .iter()
+            .find(|c| c.conversation_id == id)
+            .expect("started conversation should be listed");
+        assert_eq!((info.version, info.message_count), (0, 1));
+        assert!(info.ttl_secs.is_some_and(|ttl| ttl <= store.ttl().as_secs()));
+        assert!(!store.list_ids().await.unwrap().contains(&id));
+    }
+
+    /// Needs a live Redis (`REDIS_URL`).
+    #[tokio::test]
+    async fn forks_copy_history_without_touching_the_parent() {
+        let Ok(url) = std::env::var("REDIS_URL") else {
+            return;
+        };
+        let redis = RedisCache::new(Some(&url));
+        if !redis.is_available().await {
+            return;
+        }
+        let store = ConversationStore::new(redis);
+        let parent = store.start(Some("system prompt")).await;
+        let mut messages = store.get_messages(&parent).await.unwrap();
+        messages.push(Message::new("user", "shared turn"));
+        store.set_messages(&parent, 0, &messages).await.unwrap();
+
+        let child = store.fork(&parent).await.unwrap();
+        let mut branch = store.load(&child).await.unwrap();
+        branch.messages.push(Message::new("user", "branch only"));
+        store.set_messages(&child, branch.version, &branch.messages).await.unwrap();
+
+        let (parent_state, child_state) = (store.load(&parent).await, store.load(&child).await);
+        store.end(&parent).await;
+        store.end(&child).await;
+
+        assert_ne!(parent, child);
+        assert_eq!(branch.version, 0);
+        let parent_state = parent_state.unwrap();
+        assert_eq!((parent_state.version, parent_state.messages.len()), (1, 2));
+        assert_eq!(child_state.unwrap().messages.len(), 3);
+        assert!(matches!(
+            store.fork("missing").await,
+            Err(ConversationWriteError::NotFound(_))
+        ));
     }
-    out
 }
@@ -1,24 +1,168 @@
+use std::collections::BTreeMap;
+
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Deserialize, JsonSchema)]
+use crate::render::MAX_RENDERED_BYTES;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SearchGuidelinesParams {
-    /// The search query describing what you're looking for.
+    /// The search query describing what you're looking for, in plain language, such as
+    /// "avoid raw new and delete" or "naming of conversion methods".
     pub query: String,
     /// Maximum number of results to return (default: 10, max: 50).
     pub limit: Option<u32>,
+    /// Drop results whose similarity score is below this threshold (0.0 to 1.0).
+    /// Results may be empty when nothing scores high enough.
+    pub min_score: Option<f32>,
+    /// Ranking mode: "semantic" (default), "keyword" for exact identifiers or terms such as
+    /// "SL.con.1" or "noexcept", or "hybrid" to blend both rankings.
+    pub mode: Option<SearchMode>,
+    /// Also report each result's best-matching section heading in `matched_section`
+    /// (default: false).
+    pub include_sections: Option<bool>,
+    /// Keep only rules with one of these enforcement levels, such as
+    /// `["enforceable", "partially"]` for rules a linter can check. Only C++ rules have one.
+    pub enforcement: Option<Vec<EnforcementLevel>>,
+}
+
+/// How `search_guidelines` ranks results. See [`crate::hybrid`] for how scores combine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchMode {
+    /// Vector similarity to the query embedding.
+    #[default]
+    Semantic,
+    /// Case-insensitive substring match of the whole query against id, title and text.
+    Keyword,
+    /// Semantic and keyword scores blended into one ranking.
+    Hybrid,
+}
+
+impl SearchMode {
+    /// Stable lowercase name, suitable for cache keys and logging.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SearchMode::Semantic => "semantic",
+            SearchMode::Keyword => "keyword",
+            SearchMode::Hybrid => "hybrid",
+        }
+    }
+}
+
+/// How mechanically checkable a C++ rule is, from the wording of its Enforcement section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum EnforcementLevel {
+    /// A tool can check the rule ("Flag ...", "Warn ...").
+    Enforceable,
+    /// Checks are heuristic or cover only some cases.
+    Partially,
+    /// Hard or impossible to check mechanically.
+    Hard,
+    /// The rule has no Enforcement section.
+    Unknown,
 }
 
-#[derive(Debug, Clone, Deserialize, JsonSchema)]
+/// Maximum number of queries accepted by one `search_batch` call.
+pub const MAX_BATCH_QUERIES: usize = 20;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SearchBatchParams {
+    /// Queries to run (at most 20), such as `["error handling", "naming"]`. Result sets come
+    /// back in the same order.
+    pub queries: Vec<String>,
+    /// Maximum number of results per query (default: 10, max: 50).
+    pub limit: Option<u32>,
+    /// Drop results whose similarity score is below this threshold (0.0 to 1.0).
+    pub min_score: Option<f32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct GetGuidelineParams {
-    /// Stable guideline ID such as "P.1" or "C-CASE".
+    /// Stable guideline ID: "P.1" or "ES.20" (C++), "C-CASE" (Rust API), "1.1" (Node.js).
+    /// The guideline's anchor, bare or as a link fragment such as "#Rp-direct", also works.
+    pub guideline_id: String,
+    /// Also return the content rendered as "markdown", "html" or "text" in `rendered`.
+    pub render: Option<String>,
+}
+
+/// Maximum number of ids accepted by one `get_guidelines` call.
+pub const MAX_BATCH_IDS: usize = 50;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GetGuidelinesParams {
+    /// Guideline IDs to fetch (at most 50), such as `["P.1", "ES.20"]` or `["C-CASE", "C-DEBUG"]`.
+    pub guideline_ids: Vec<String>,
+    /// Also return each content rendered as "markdown", "html" or "text" in `rendered`.
+    pub render: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GetGuidelineSectionParams {
+    /// Stable guideline ID such as "P.1" or "ES.20".
+    pub guideline_id: String,
+    /// Section heading such as "Reason", "Example" or "Enforcement" (case-insensitive).
+    pub section: String,
+}
+
+/// Arguments of the `review_snippet` prompt.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ReviewSnippetArgs {
+    /// Code to review; also used as the query for the rules quoted in the prompt.
+    pub code: String,
+}
+
+/// Arguments of the `explain_rule` prompt.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ExplainRuleArgs {
+    /// Guideline ID to explain, such as "P.1" or "ES.20".
+    pub guideline_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RelatedGuidelinesParams {
+    /// Guideline whose cross-references to return, such as "C.20".
     pub guideline_id: String,
 }
 
-#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GuidelineExamplesParams {
+    /// Guideline whose code examples to return, such as "P.1".
+    pub guideline_id: String,
+    /// Only return examples of this kind: "good", "bad" or "neutral".
+    pub kind: Option<ExampleKind>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SimilarGuidelinesParams {
+    /// Guideline to find neighbours for: "P.1" (C++), "C-CASE" (Rust API), "1.1" (Node.js).
+    pub guideline_id: String,
+    /// Maximum number of results to return (default: 10, max: 50).
+    pub limit: Option<u32>,
+    /// Drop neighbours whose similarity score is below this threshold (0.0 to 1.0).
+    /// Results may be empty when no other guideline is genuinely similar.
+    pub min_score: Option<f32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ListCategoryParams {
-    /// Category key/prefix such as "ES" or "Naming".
+    /// Category key: a prefix such as "ES" (C++), a chapter name such as "Naming" (Rust API),
+    /// or a section number such as "2" (Node.js).
     pub category: String,
+    /// Number of guidelines (sorted by id) to skip before the page starts (default: 0).
+    pub offset: Option<u32>,
+    /// Maximum number of guidelines to return (default: all remaining).
+    pub limit: Option<u32>,
+}
+
+impl ListCategoryParams {
+    /// Slice `items` (already sorted) to the requested page.
+    pub fn page<T>(&self, items: Vec<T>) -> Vec<T> {
+        let offset = self.offset.unwrap_or(0) as usize;
+        let limit = self.limit.map_or(usize::MAX, |l| l as usize);
+        items.into_iter().skip(offset).take(limit).collect()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -28,6 +172,9 @@ pub struct GuidelineSearchResult {
     pub category: String,
     pub score: f32,
     pub summary: String,
+    /// Heading of the section that best matches the query, such as "Enforcement"; only
+    /// set when `include_sections` was requested and some section matched.
+    pub matched_section: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -35,6 +182,17 @@ pub struct SearchGuidelinesResponse {
     pub results: Vec<GuidelineSearchResult>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BatchQueryResults {
+    pub query: String,
+    pub results: Vec<GuidelineSearchResult>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SearchBatchResponse {
+    pub queries: Vec<BatchQueryResults>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct GuidelineSection {
     pub heading: String,
@@ -48,10 +206,23 @@ pub struct GuidelineDetailResponse {
     pub title: String,
     pub category: String,
     pub raw_markdown: String,
-    /// Populated when a source has explicit subsection structure (for example C++ guidelines).
+    /// `raw_markdown` in the format requested via `render`; `None` when none was requested.
+    pub rendered: Option<String>,
+    /// Populated when a source has subsection structure (for example C++ guidelines or the
+    /// sub-headings of Rust API guidelines).
     pub sections: Option<Vec<GuidelineSection>>,
     /// Populated when a source is chapter/file based (for example Rust API guidelines).
     pub source_file: Option<String>,
+    /// Populated when a source classifies its rules' enforcement (C++ guidelines).
+    pub enforcement: Option<EnforcementLevel>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GetGuidelinesResponse {
+    /// Guidelines that were found, keyed by the ID as requested.
+    pub found: BTreeMap<String, GuidelineDetailResponse>,
+    /// Requested IDs with no matching guideline, in request order.
+    pub not_found: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -67,9 +238,57 @@ pub struct GuidelineSummary {
     pub title: String,
 }
 
+/// Verdict of an example section heading (`Example, good` / `Example, bad` / `Example`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ExampleKind {
+    Good,
+    Bad,
+    Neutral,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GuidelineExample {
+    pub kind: ExampleKind,
+    pub code: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GuidelineExamplesResponse {
+    pub guideline_id: String,
+    pub examples: Vec<GuidelineExample>,
+}
+
+/// Rules a guideline links to or mentions, in order of first appearance.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RelatedGuidelinesResponse {
+    pub guideline_id: String,
+    pub related: Vec<GuidelineSummary>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CategoriesResponse {
+    pub categories: Vec<CategoryInfo>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CategoryListResponse {
     pub category: CategoryInfo,
+    /// Number of guidelines in the category across all pages.
+    pub total: usize,
+    pub guidelines: Vec<GuidelineSummary>,
+}
+
+/// Every guideline, grouped by category in the source's canonical chapter order.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ChecklistResponse {
+    pub categories: Vec<ChecklistCategory>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ChecklistCategory {
+    pub category: CategoryInfo,
+    /// Guidelines in the category, sorted by id.
     pub guidelines: Vec<GuidelineSummary>,
 }
 
@@ -79,3 +298,439 @@ pub struct UpdateGuidelinesResponse {
     pub commit: String,
     pub guideline_count: usize,
 }
+
+/// Counts of the loaded corpus, for monitoring.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct StatsResponse {
+    pub guideline_count: usize,
+    pub category_count: usize,
+    /// Commit the index was built from, or the checkout's HEAD when none is cached;
+    /// `None` if neither is known.
+    pub commit: Option<String>,
+    /// Rows in the vector index; `None` if the table could not be read.
+    pub indexed_count: Option<usize>,
+    pub redis_available: bool,
+    /// Cache hit/miss counts since the server started.
+    pub cache: CacheMetrics,
+}
+
+/// Hit/miss counts for one kind of cached lookup. A miss is any lookup not answered from
+/// Redis, including while Redis is unavailable.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// `hits / (hits + misses)`; `None` before the first lookup.
+    pub hit_rate: Option<f64>,
+}
+
+/// Cache hit/miss counts per cached lookup kind.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct CacheMetrics {
+    /// Single and batch guideline lookups, counted per id.
+    pub guideline: CacheStats,
+    /// Search result lookups.
+    pub search: CacheStats,
+    /// Category list and category membership lookups.
+    pub category: CacheStats,
+}
+
+/// Serialization produced by `export_guidelines`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// Every guideline as a structured record in `guidelines`.
+    #[default]
+    Json,
+    /// One markdown document in `markdown`, headed by the commit.
+    Markdown,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ExportGuidelinesParams {
+    /// "json" (default) or "markdown".
+    pub format: Option<ExportFormat>,
+    /// Number of guidelines (sorted by id) to skip before the page starts (default: 0).
+    pub offset: Option<u32>,
+    /// Maximum number of guidelines to export, at least 1 (default: as many as fit in
+    /// 512 KiB). A limit whose page would exceed 512 KiB is refused.
+    pub limit: Option<u32>,
+}
+
+/// Snapshot of the loaded guidelines, for offline use or diffing across commits.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ExportGuidelinesResponse {
+    pub format: ExportFormat,
+    /// Commit the guidelines were indexed from; `None` if unknown.
+    pub commit: Option<String>,
+    /// Number of guidelines loaded, across all pages.
+    pub guideline_count: usize,
+    /// `offset` of the next page; `None` when this page reaches the last guideline.
+    pub next_offset: Option<usize>,
+    /// The page's guidelines, sorted by id; empty for markdown exports. `sections` is
+    /// left out since it repeats `raw_markdown`.
+    pub guidelines: Vec<GuidelineDetailResponse>,
+    /// The page's raw markdown in id order under a `title` and commit header; `None` for
+    /// JSON exports.
+    pub markdown: Option<String>,
+}
+
+impl ExportGuidelinesResponse {
+    /// Build the page of `guidelines` (already sorted by id) that `params` asks for.
+    ///
+    /// Without a `limit` the page holds as many guidelines as fit in [`MAX_RENDERED_BYTES`].
+    /// Fails when `limit` is 0, or when the requested page would exceed the cap, naming a
+    /// `limit` to retry with.
+    pub fn new(
+        params: &ExportGuidelinesParams,
+        title: &str,
+        commit: Option<String>,
+        guidelines: Vec<GuidelineDetailResponse>,
+    ) -> Result<Self, String> {
+        if params.limit == Some(0) {
+            return Err("limit must be at least 1".to_string());
+        }
+        let format = params.format.unwrap_or_default();
+        let guideline_count = guidelines.len();
+        let offset = (params.offset.unwrap_or(0) as usize).min(guideline_count);
+        let limit = params.limit.map_or(usize::MAX, |l| l as usize);
+        let page: Vec<GuidelineDetailResponse> = guidelines
+            .into_iter()
+            .skip(offset)
+            .take(limit)
+            .map(|guideline| GuidelineDetailResponse {
+                sections: None,
+                ..guideline
+            })
+            .collect();
+
+        let mut page_len = page.len();
+        loop {
+            let end = offset + page_len;
+            let export = Self {
+                format,
+                commit: commit.clone(),
+                guideline_count,
+                next_offset: (end < guideline_count).then_some(end),
+                guidelines: Vec::new(),
+                markdown: None,
+            }
+            .with_page(title, &page[..page_len]);
+
+            let bytes = serde_json::to_vec(&export).map_or(usize::MAX, |json| json.len());
+            if bytes <= MAX_RENDERED_BYTES {
+                return Ok(export);
+            }
+            if page_len == 1 {
+                return Err(format!(
+                    "guideline {} alone is {bytes} bytes, over the {MAX_RENDERED_BYTES} byte \
+                     limit",
+                    page[0].id
+                ));
+            }
+            let suggested = (page_len * MAX_RENDERED_BYTES / bytes).clamp(1, page_len - 1);
+            if params.limit.is_some() {
+                return Err(format!(
+                    "export of {page_len} guidelines is {bytes} bytes, over the \
+                     {MAX_RENDERED_BYTES} byte limit; pass a smaller limit (such as \
+                     {suggested}) and page with offset"
+                ));
+            }
+            page_len = suggested;
+        }
+    }
+
+    /// Fill in `page` as JSON records or as one markdown document, per `self.format`.
+    fn with_page(mut self, title: &str, page: &[GuidelineDetailResponse]) -> Self {
+        match self.format {
+            ExportFormat::Json => self.guidelines = page.to_vec(),
+            ExportFormat::Markdown => {
+                let mut markdown = format!(
+                    "# {title}\n\nCommit: {}\n",
+                    self.commit.as_deref().unwrap_or("unknown")
+                );
+                for guideline in page {
+                    markdown.push('\n');
+                    markdown.push_str(guideline.raw_markdown.trim());
+                    markdown.push('\n');
+                }
+                self.markdown = Some(markdown);
+            }
+        }
+        self
+    }
+}
+
+/// Result of clearing the cached search results.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct InvalidateSearchCacheResponse {
+    /// Number of cached search entries removed.
+    pub deleted: usize,
+}
+
+/// Result of cross-checking the loaded guidelines against the vector index.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct VerifyIndexResponse {
+    /// `true` when both sides hold exactly the same ids, each indexed once.
+    pub consistent: bool,
+    pub memory_count: usize,
+    pub index_row_count: usize,
+    /// Guidelines loaded in memory with no row in the index (sorted).
+    pub missing_from_index: Vec<String>,
+    /// Index rows with no matching guideline in memory (sorted).
+    pub missing_from_memory: Vec<String>,
+    /// Ids with more than one row in the index (sorted).
+    pub duplicate_index_ids: Vec<String>,
+}
+
+impl VerifyIndexResponse {
+    /// Compare in-memory guideline ids with the ids stored in the index.
+    pub fn compare(memory_ids: impl IntoIterator<Item = String>, index_ids: Vec<String>) -> Self {
+        use std::collections::{BTreeMap, BTreeSet};
+
+        let memory: BTreeSet<String> = memory_ids.into_iter().collect();
+        let index_row_count = index_ids.len();
+        let mut index: BTreeMap<String, usize> = BTreeMap::new();
+        for id in index_ids {
+            *index.entry(id).or_default() += 1;
+        }
+
+        let missing_from_index: Vec<String> =
+            memory.iter().filter(|id| !index.contains_key(*id)).cloned().collect();
+        let missing_from_memory: Vec<String> =
+            index.keys().filter(|id| !memory.contains(*id)).cloned().collect();
+        let duplicate_index_ids: Vec<String> = index
+            .iter()
+            .filter(|(_, &count)| count > 1)
+            .map(|(id, _)| id.clone())
+            .collect();
+
+        Self {
+            consistent: missing_from_index.is_empty()
+                && missing_from_memory.is_empty()
+                && duplicate_index_ids.is_empty(),
+            memory_count: memory.len(),
+            index_row_count,
+            missing_from_index,
+            missing_from_memory,
+            duplicate_index_ids,
+        }
+    }
+}
+
+/// Sanitized view of a guideline server's effective configuration.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ConfigInfoResponse {
+    pub repo_path: String,
+    pub lancedb_path: String,
+    pub table_name: String,
+    pub embedding_model: String,
+    pub embedding_dimension: usize,
+    pub distance_metric: String,
+    pub search_cache_ttl_secs: u64,
+    pub search_cache_canonical_limit: usize,
+    /// Characters of text kept in each search result's `summary` (`SEARCH_SUMMARY_LEN`).
+    pub search_summary_len: usize,
+    /// Whether `REDIS_URL` is set. The URL itself is omitted since it may carry credentials.
+    pub redis_configured: bool,
+    pub redis_available: bool,
+    /// Whether `GUIDELINES_AUTO_PULL` pulls the repository before update checks.
+    pub auto_pull: bool,
+    /// Seconds between scheduled background update checks, if enabled.
+    pub refresh_secs: Option<u64>,
+    /// Seconds a tool call may run before it fails with "tool timed out", if limited.
+    pub tool_timeout_secs: Option<u64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn verify_index_reports_drift_in_both_directions() {
+        let report = VerifyIndexResponse::compare(
+            ids(&["P.1", "P.2", "P.3"]),
+            ids(&["P.3", "P.1", "X.9", "P.1"]),
+        );
+        assert!(!report.consistent);
+        assert_eq!(report.memory_count, 3);
+        assert_eq!(report.index_row_count, 4);
+        assert_eq!(report.missing_from_index, ["P.2"]);
+        assert_eq!(report.missing_from_memory, ["X.9"]);
+        assert_eq!(report.duplicate_index_ids, ["P.1"]);
+
+        let report = VerifyIndexResponse::compare(ids(&["a", "b"]), ids(&["b", "a"]));
+        assert!(report.consistent);
+    }
+
+    #[test]
+    fn list_category_pages_after_sorting() {
+        let params = |offset, limit| ListCategoryParams {
+            category: "ES".to_string(),
+            offset,
+            limit,
+        };
+        let items = || vec![1, 2, 3, 4, 5];
+        assert_eq!(params(None, None).page(items()), [1, 2, 3, 4, 5]);
+        assert_eq!(params(Some(1), Some(2)).page(items()), [2, 3]);
+        assert_eq!(params(Some(3), None).page(items()), [4, 5]);
+        assert!(params(Some(9), Some(2)).page(items()).is_empty());
+    }
+
+    #[test]
+    fn markdown_export_heads_the_bundle_with_the_commit() {
+        let guideline = |id: &str| GuidelineDetailResponse {
+            id: id.to_string(),
+            anchor: String::new(),
+            title: String::new(),
+            category: String::new(),
+            raw_markdown: format!("### {id}\n\nBody.\n"),
+            rendered: None,
+            sections: Some(vec![GuidelineSection {
+                heading: "Reason".to_string(),
+                content: "Body.".to_string(),
+            }]),
+            source_file: None,
+            enforcement: None,
+        };
+        let guidelines = vec![guideline("P.1"), guideline("P.2")];
+        let params = |format, offset, limit| ExportGuidelinesParams {
+            format: Some(format),
+            offset,
+            limit,
+        };
+        let export = |params: ExportGuidelinesParams, guidelines| {
+            ExportGuidelinesResponse::new(
+                &params,
+                "C++ Core Guidelines",
+                Some("abc123".to_string()),
+                guidelines,
+            )
+        };
+
+        let bundle =
+            export(params(ExportFormat::Markdown, None, None), guidelines.clone()).unwrap();
+        assert_eq!(bundle.guideline_count, 2);
+        assert_eq!(bundle.next_offset, None);
+        assert!(bundle.guidelines.is_empty());
+        assert_eq!(
+            bundle.markdown.as_deref(),
+            Some(
+                "# C++ Core Guidelines\n\nCommit: abc123\n\n### P.1\n\nBody.\n\n### P.2\n\nBody.\n"
+            )
+        );
+
+        let json = export(params(ExportFormat::Json, None, None), guidelines.clone()).unwrap();
+        assert_eq!(json.guidelines.len(), 2);
+        assert!(json.guidelines.iter().all(|g| g.sections.is_none()));
+        assert!(json.markdown.is_none());
+
+        let first = export(params(ExportFormat::Json, None, Some(1)), guidelines.clone()).unwrap();
+        assert_eq!(first.guidelines[0].id, "P.1");
+        assert_eq!((first.guideline_count, first.next_offset), (2, Some(1)));
+        let second = export(params(ExportFormat::Json, Some(1), Some(1)), guidelines).unwrap();
+        assert_eq!(second.guidelines[0].id, "P.2");
+        assert_eq!(second.next_offset, None);
+    }
+
+    #[test]
+    fn oversized_exports_are_refused_with_a_limit_to_retry() {
+        let guidelines: Vec<GuidelineDetailResponse> = (0..4)
+            .map(|i| GuidelineDetailResponse {
+                id: format!("G.{i}"),
+                anchor: String::new(),
+                title: String::new(),
+                category: String::new(),
+                raw_markdown: "x".repeat(MAX_RENDERED_BYTES / 3),
+                rendered: None,
+                sections: None,
+                source_file: None,
+                enforcement: None,
+            })
+            .collect();
+        let params = |limit| ExportGuidelinesParams {
+            format: None,
+            offset: None,
+            limit,
+        };
+
+        let err = ExportGuidelinesResponse::new(&params(Some(4)), "", None, guidelines.clone())
+            .unwrap_err();
+        assert!(err.contains("export of 4 guidelines"), "{err}");
+        assert!(err.contains("(such as 2)"), "{err}");
+        assert!(ExportGuidelinesResponse::new(&params(Some(2)), "", None, guidelines).is_ok());
+    }
+
+    #[test]
+    fn exports_without_a_limit_page_through_everything_within_the_cap() {
+        let guidelines: Vec<GuidelineDetailResponse> = (0..5)
+            .map(|i| GuidelineDetailResponse {
+                id: format!("G.{i}"),
+                anchor: String::new(),
+                title: String::new(),
+                category: String::new(),
+                raw_markdown: "x".repeat(MAX_RENDERED_BYTES / 3),
+                rendered: None,
+                sections: None,
+                source_file: None,
+                enforcement: None,
+            })
+            .collect();
+
+        let mut offset = Some(0);
+        let mut exported = Vec::new();
+        while let Some(start) = offset {
+            let params = ExportGuidelinesParams {
+                format: None,
+                offset: Some(start as u32),
+                limit: None,
+            };
+            let page =
+                ExportGuidelinesResponse::new(&params, "", None, guidelines.clone()).unwrap();
+            assert!(!page.guidelines.is_empty());
+            assert!(serde_json::to_vec(&page).unwrap().len() <= MAX_RENDERED_BYTES);
+            exported.extend(page.guidelines.into_iter().map(|g| g.id));
+            offset = page.next_offset;
+        }
+        assert_eq!(exported, ["G.0", "G.1", "G.2", "G.3", "G.4"]);
+    }
+
+    #[test]
+    fn zero_limit_is_refused() {
+        let params = ExportGuidelinesParams {
+            format: None,
+            offset: None,
+            limit: Some(0),
+        };
+        let err = ExportGuidelinesResponse::new(&params, "", None, Vec::new()).unwrap_err();
+        assert_eq!(err, "limit must be at least 1");
+    }
+
+    /// Every tool parameter carries a description so clients can show usage hints.
+    #[test]
+    fn params_describe_every_property() {
+        let schemas = [
+            schemars::schema_for!(SearchGuidelinesParams),
+            schemars::schema_for!(SearchBatchParams),
+            schemars::schema_for!(GetGuidelineParams),
+            schemars::schema_for!(GetGuidelineSectionParams),
+            schemars::schema_for!(SimilarGuidelinesParams),
+            schemars::schema_for!(RelatedGuidelinesParams),
+            schemars::schema_for!(GuidelineExamplesParams),
+            schemars::schema_for!(ListCategoryParams),
+            schemars::schema_for!(ExportGuidelinesParams),
+        ];
+        for schema in schemas {
+            let value = serde_json::to_value(&schema).unwrap();
+            let properties = value["properties"].as_object().unwrap();
+            for (name, property) in properties {
+                let description = property["description"].as_str().unwrap_or_default();
+                assert!(!description.is_empty(), "{} lacks a description", name);
+            }
+        }
+    }
+}
@@ -1,19 +1,71 @@
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
 
 use futures::StreamExt;
-use reqwest::StatusCode;
+use metrics::histogram;
+pub use reqwest::StatusCode;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use tracing::warn;
+use tracing::{warn, Instrument};
 
-#[derive(Clone, Debug)]
+use crate::unique_id::new_unique_id;
+
+/// Histogram of upstream request latency in seconds, one sample per attempt, labelled by
+/// `endpoint` and `outcome` (`ok` or `error`). A no-op unless a metrics recorder is installed.
+pub const UPSTREAM_LATENCY_METRIC: &str = "openai_request_duration_seconds";
+
+/// Header carrying the id generated for each logical request. Retries of one call reuse its
+/// id, so upstream logs can be matched to the proxy's `openai_request` span and errors.
+pub const REQUEST_ID_HEADER: &str = "X-Request-Id";
+
+#[derive(Clone)]
 pub struct OpenAiClientConfig {
     pub base_url: String,
     pub default_timeout: Duration,
     pub max_retries: u32,
     pub initial_backoff: Duration,
+    /// Cap on the delay between retries, including delays requested by `Retry-After`.
     pub max_backoff: Duration,
+    /// How the delay before each retry is drawn from the capped exponential backoff.
+    pub backoff_strategy: BackoffStrategy,
     pub max_error_body_bytes: usize,
+    /// Largest amount of unterminated SSE data buffered while waiting for an event boundary.
+    pub max_stream_buffer_bytes: usize,
+    /// Sent as `Authorization: Bearer <key>` when set. Never logged or reported.
+    pub api_key: Option<String>,
+    /// Sent as `OpenAI-Organization` when set.
+    pub organization: Option<String>,
+    /// Extra headers sent with every request (for example a gateway's `X-Tenant`).
+    pub default_headers: HashMap<String, String>,
+    /// Proxy URL all upstream requests are routed through.
+    pub proxy: Option<String>,
+    /// Consecutive host failures (connect errors, timeouts, 5xx) that open the circuit,
+    /// from `OPENAI_CB_FAILURES`. 0 (the default) disables the breaker.
+    pub circuit_breaker_failures: u32,
+    /// How long an open circuit fails fast before letting a probe request through.
+    pub circuit_breaker_cooldown: Duration,
+}
+
+impl std::fmt::Debug for OpenAiClientConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OpenAiClientConfig")
+            .field("base_url", &self.redacted_base_url())
+            .field("default_timeout", &self.default_timeout)
+            .field("max_retries", &self.max_retries)
+            .field("initial_backoff", &self.initial_backoff)
+            .field("max_backoff", &self.max_backoff)
+            .field("backoff_strategy", &self.backoff_strategy)
+            .field("max_error_body_bytes", &self.max_error_body_bytes)
+            .field("max_stream_buffer_bytes", &self.max_stream_buffer_bytes)
+            .field("api_key", &self.api_key.as_ref().map(|_| "<redacted>"))
+            .field("organization", &self.organization)
+            .field("default_headers", &self.default_headers.keys().collect::<Vec<_>>())
+            .field("proxy", &self.proxy.as_ref().map(|_| "<redacted>"))
+            .field("circuit_breaker_failures", &self.circuit_breaker_failures)
+            .field("circuit_breaker_cooldown", &self.circuit_breaker_cooldown)
+            .finish()
+    }
 }
 
 impl OpenAiClientConfig {
@@ -44,18 +96,118 @@ impl OpenAiClientConfig {
             .map(Duration::from_millis)
             .unwrap_or_else(|| Duration::from_millis(5_000));
 
+        let backoff_strategy = match std::env::var("OPENAI_BACKOFF_STRATEGY") {
+            Ok(value) => BackoffStrategy::parse(&value).unwrap_or_else(|| {
+                warn!(value, "unknown OPENAI_BACKOFF_STRATEGY, using exponential");
+                BackoffStrategy::default()
+            }),
+            Err(_) => BackoffStrategy::default(),
+        };
+
         let max_error_body_bytes = std::env::var("OPENAI_MAX_ERROR_BODY_BYTES")
             .ok()
             .and_then(|s| s.parse::<usize>().ok())
             .unwrap_or(8 * 1024);
 
+        let max_stream_buffer_bytes = std::env::var("OPENAI_STREAM_MAX_BUFFER")
+            .ok()
+            .and_then(|s| s.parse::<usize>().ok())
+            .unwrap_or(1024 * 1024);
+
+        let api_key = std::env::var("OPENAI_API_KEY")
+            .ok()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty());
+
+        let organization = std::env::var("OPENAI_ORG")
+            .ok()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty());
+
+        let default_headers = std::env::var("OPENAI_EXTRA_HEADERS")
+            .ok()
+            .map(|s| parse_header_list(&s))
+            .unwrap_or_default();
+
+        let circuit_breaker_failures = std::env::var("OPENAI_CB_FAILURES")
+            .ok()
+            .and_then(|s| s.parse::<u32>().ok())
+            .unwrap_or(0);
+
+        let circuit_breaker_cooldown = std::env::var("OPENAI_CB_COOLDOWN_MS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .map(Duration::from_millis)
+            .unwrap_or_else(|| Duration::from_secs(30));
+
+        let proxy = std::env::var("HTTPS_PROXY")
+            .or_else(|_| std::env::var("https_proxy"))
+            .ok()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty());
+
         Self {
             base_url: base_url.trim_end_matches('/').to_string(),
             default_timeout,
             max_retries,
             initial_backoff,
             max_backoff,
+            backoff_strategy,
             max_error_body_bytes,
+            max_stream_buffer_bytes,
+            api_key,
+            organization,
+            default_headers,
+            proxy,
+            circuit_breaker_failures,
+            circuit_breaker_cooldown,
+        }
+    }
+
+    /// `base_url` with any embedded username/password stripped, safe to log or report.
+    pub fn redacted_base_url(&self) -> String {
+        match reqwest::Url::parse(&self.base_url) {
+            Ok(mut url) => {
+                if url.password().is_some() || !url.username().is_empty() {
+                    let _ = url.set_username("");
+                    let _ = url.set_password(None);
+                }
+                url.as_str().trim_end_matches('/').to_string()
+            }
+            Err(_) => "<unparseable>".to_string(),
+        }
+    }
+}
+
+/// How the delay before a retry is drawn, given `capped = min(max_backoff, initial * 2^n)`
+/// for the n-th retry.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BackoffStrategy {
+    /// `capped` plus up to a quarter of it at random.
+    #[default]
+    Exponential,
+    /// Half of `capped` plus a random share of the other half.
+    EqualJitter,
+    /// Anywhere from zero to `capped`, which spreads out contending clients the most.
+    FullJitter,
+}
+
+impl BackoffStrategy {
+    /// Parse an `OPENAI_BACKOFF_STRATEGY` value.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "exponential" => Some(Self::Exponential),
+            "equal_jitter" => Some(Self::EqualJitter),
+            "full_jitter" => Some(Self::FullJitter),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Exponential => "exponential",
+            Self::EqualJitter => "equal_jitter",
+            Self::FullJitter => "full_jitter",
         }
     }
 }
@@ -68,28 +220,104 @@ pub enum OpenAiClientError {
     #[error("invalid response JSON: {0}")]
     InvalidJson(#[from] serde_json::Error),
 
-    #[error("upstream returned error: status={status} message={message}")]
-    Upstream { status: StatusCode, message: String },
+    #[error("upstream returned error: status={status} message={message} request_id={request_id}")]
+    Upstream {
+        status: StatusCode,
+        message: String,
+        /// Delay requested by the response's `Retry-After` header.
+        retry_after: Option<Duration>,
+        /// The `X-Request-Id` sent with the failing request.
+        request_id: String,
+    },
 
-    #[error("upstream returned non-JSON error: status={status} body={body}")]
-    UpstreamBody { status: StatusCode, body: String },
+    #[error("upstream returned non-JSON error: status={status} body={body} request_id={request_id}")]
+    UpstreamBody {
+        status: StatusCode,
+        body: String,
+        /// Delay requested by the response's `Retry-After` header.
+        retry_after: Option<Duration>,
+        /// The `X-Request-Id` sent with the failing request.
+        request_id: String,
+    },
 
     #[error("streaming response ended without a completion")]
     StreamEnded,
+
+    #[error("streaming response interrupted after partial output: {0}")]
+    StreamInterrupted(String),
+
+    #[error("streaming response sent over {0} bytes without an event boundary")]
+    StreamBufferExceeded(usize),
+
+    #[error("invalid client configuration: {0}")]
+    InvalidConfig(String),
+
+    #[error("upstream circuit open after repeated failures; retry in ~{retry_in_ms}ms")]
+    CircuitOpen { retry_in_ms: u64 },
+}
+
+impl OpenAiClientError {
+    /// Whether the same request may succeed if sent again; the test the retry loop uses.
+    pub fn is_retryable(&self) -> bool {
+        should_retry(self)
+    }
+
+    /// Delay the upstream asked for before retrying, from a 429 or 503 `Retry-After` header.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::Upstream { retry_after, .. } | Self::UpstreamBody { retry_after, .. } => {
+                *retry_after
+            }
+            _ => None,
+        }
+    }
+
+    /// The `X-Request-Id` of the request the upstream rejected.
+    pub fn request_id(&self) -> Option<&str> {
+        match self {
+            Self::Upstream { request_id, .. } | Self::UpstreamBody { request_id, .. } => {
+                Some(request_id)
+            }
+            _ => None,
+        }
+    }
 }
 
 #[derive(Clone)]
 pub struct OpenAiClient {
     config: OpenAiClientConfig,
     http: reqwest::Client,
+    breaker: Arc<Mutex<Breaker>>,
+    /// Retry jitter source shared by all clones. Each draw is independent of the clock, so
+    /// retries started in the same instant still spread out.
+    jitter: Arc<Mutex<fastrand::Rng>>,
+}
+
+/// Circuit breaker state shared by all clones of a client.
+#[derive(Debug, Default)]
+struct Breaker {
+    consecutive_failures: u32,
+    /// Set while the circuit is open; calls fail fast until this instant.
+    open_until: Option<Instant>,
 }
 
 impl OpenAiClient {
     pub fn new(config: OpenAiClientConfig) -> Result<Self, OpenAiClientError> {
-        let http = reqwest::Client::builder()
+        let mut builder = reqwest::Client::builder()
             .user_agent("mcp-servers/llm-proxy")
-            .build()?;
-        Ok(Self { config, http })
+            .default_headers(default_headers(&config)?);
+        if let Some(proxy) = &config.proxy {
+            let proxy = reqwest::Proxy::all(proxy)
+                .map_err(|e| OpenAiClientError::InvalidConfig(format!("invalid proxy URL: {e}")))?;
+            builder = builder.proxy(proxy);
+        }
+        let http = builder.build()?;
+        Ok(Self {
+            config,
+            http,
+            breaker: Arc::new(Mutex::new(Breaker::default())),
+            jitter: Arc::new(Mutex::new(fastrand::Rng::new())),
+        })
     }
 
     pub fn config(&self) -> &OpenAiClientConfig {
@@ -97,10 +325,42 @@ impl OpenAiClient {
     }
 
     pub async fn list_models(&self) -> Result<ModelListResponse, OpenAiClientError> {
+        self.fetch_models(self.config.max_retries).await
+    }
+
+    /// Like [`Self::list_models`], but a single attempt: for callers that can do without the
+    /// list and should not wait out the retry backoff when the upstream is down.
+    pub async fn list_models_once(&self) -> Result<ModelListResponse, OpenAiClientError> {
+        self.fetch_models(0).await
+    }
+
+    /// A single `GET /models` for health checks. It bypasses the circuit breaker both ways:
+    /// an open circuit does not stop it, and its outcome is not counted, so failing probes
+    /// cannot open the circuit for real traffic.
+    pub async fn probe_models(&self) -> Result<ModelListResponse, OpenAiClientError> {
         let url = format!("{}/models", self.config.base_url);
-        self.request_with_retry(|| async {
-            let resp = self.http.get(&url).timeout(self.config.default_timeout).send().await?;
-            Self::parse_json_response(resp, self.config.max_error_body_bytes).await
+        let request_id = new_unique_id();
+        let resp = self
+            .http
+            .get(&url)
+            .header(REQUEST_ID_HEADER, &request_id)
+            .timeout(self.config.default_timeout)
+            .send()
+            .await?;
+        Self::parse_json_response(resp, self.config.max_error_body_bytes, request_id).await
+    }
+
+    async fn fetch_models(&self, max_retries: u32) -> Result<ModelListResponse, OpenAiClientError> {
+        let url = format!("{}/models", self.config.base_url);
+        self.request_with_retries("models", max_retries, |request_id| async {
+            let resp = self
+                .http
+                .get(&url)
+                .header(REQUEST_ID_HEADER, &request_id)
+                .timeout(self.config.default_timeout)
+                .send()
+                .await?;
+            Self::parse_json_response(resp, self.config.max_error_body_bytes, request_id).await
         })
         .await
     }
@@ -112,18 +372,72 @@ impl OpenAiClient {
     ) -> Result<ChatCompletionResponse, OpenAiClientError> {
         let url = format!("{}/chat/completions", self.config.base_url);
         let timeout = timeout_override.unwrap_or(self.config.default_timeout);
-        self.request_with_retry(|| {
+        self.request_with_retry("chat_completions", |request_id| {
             let req = request.clone();
             let url = url.clone();
             async move {
                 let resp = self
                     .http
                     .post(&url)
+                    .header(REQUEST_ID_HEADER, &request_id)
                     .timeout(timeout)
                     .json(&req)
                     .send()
                     .await?;
-                Self::parse_json_response(resp, self.config.max_error_body_bytes).await
+                Self::parse_json_response(resp, self.config.max_error_body_bytes, request_id)
+                    .await
+            }
+        })
+        .await
+    }
+
+    /// Send `body` as-is to `POST /chat/completions` and return the response JSON untouched.
+    /// For parameters `ChatCompletionRequest` does not model; the body must not ask to stream.
+    pub async fn chat_completions_raw(
+        &self,
+        body: serde_json::Value,
+        timeout_override: Option<Duration>,
+    ) -> Result<serde_json::Value, OpenAiClientError> {
+        let url = format!("{}/chat/completions", self.config.base_url);
+        let timeout = timeout_override.unwrap_or(self.config.default_timeout);
+        self.request_with_retry("chat_completions", |request_id| {
+            let url = url.clone();
+            let body = &body;
+            async move {
+                let resp = self
+                    .http
+                    .post(&url)
+                    .header(REQUEST_ID_HEADER, &request_id)
+                    .timeout(timeout)
+                    .json(body)
+                    .send()
+                    .await?;
+                Self::parse_json_response(resp, self.config.max_error_body_bytes, request_id)
+                    .await
+            }
+        })
+        .await
+    }
+
+    pub async fn embeddings(
+        &self,
+        request: EmbeddingRequest,
+    ) -> Result<EmbeddingResponse, OpenAiClientError> {
+        let url = format!("{}/embeddings", self.config.base_url);
+        self.request_with_retry("embeddings", |request_id| {
+            let req = request.clone();
+            let url = url.clone();
+            async move {
+                let resp = self
+                    .http
+                    .post(&url)
+                    .header(REQUEST_ID_HEADER, &request_id)
+                    .timeout(self.config.default_timeout)
+                    .json(&req)
+                    .send()
+                    .await?;
+                Self::parse_json_response(resp, self.config.max_error_body_bytes, request_id)
+                    .await
             }
         })
         .await
@@ -134,9 +448,27 @@ impl OpenAiClient {
         request: ChatCompletionRequest,
         timeout_override: Option<Duration>,
     ) -> Result<String, OpenAiClientError> {
+        self.chat_completions_streaming(request, timeout_override, |_| {}).await
+    }
+
+    /// Stream a chat completion, calling `on_delta` with each content piece as it arrives.
+    ///
+    /// Returns the aggregated text. Failures before the first piece are retried like any other
+    /// request; once a piece has been delivered the attempt is not retried, so `on_delta` never
+    /// sees the same output twice.
+    pub async fn chat_completions_streaming<F>(
+        &self,
+        request: ChatCompletionRequest,
+        timeout_override: Option<Duration>,
+        on_delta: F,
+    ) -> Result<String, OpenAiClientError>
+    where
+        F: Fn(&str) + Send + Sync,
+    {
         let url = format!("{}/chat/completions", self.config.base_url);
         let timeout = timeout_override.unwrap_or(self.config.default_timeout);
-        self.request_with_retry(|| {
+        let on_delta = &on_delta;
+        self.request_with_retry("chat_completions", |request_id| {
             let mut req = request.clone();
             req.stream = Some(true);
             let url = url.clone();
@@ -144,20 +476,32 @@ impl OpenAiClient {
                 let resp = self
                     .http
                     .post(&url)
+                    .header(REQUEST_ID_HEADER, &request_id)
                     .timeout(timeout)
                     .json(&req)
                     .send()
                     .await?;
 
                 if !resp.status().is_success() {
-                    return Err(Self::to_upstream_error(resp, self.config.max_error_body_bytes).await);
+                    return Err(Self::to_upstream_error(
+                        resp,
+                        self.config.max_error_body_bytes,
+                        request_id,
+                    )
+                    .await);
                 }
 
                 let mut stream = resp.bytes_stream();
                 let mut buffer = String::new();
                 let mut out = String::new();
                 while let Some(next) = stream.next().await {
-                    let chunk = next?;
+                    let chunk = match next {
+                        Ok(chunk) => chunk,
+                        Err(e) if !out.is_empty() => {
+                            return Err(OpenAiClientError::StreamInterrupted(e.to_string()));
+                        }
+                        Err(e) => return Err(e.into()),
+                    };
                     buffer.push_str(&String::from_utf8_lossy(&chunk));
                     while let Some(idx) = buffer.find("\n\n") {
                         let event = buffer[..idx].to_string();
@@ -177,15 +521,24 @@ impl OpenAiClient {
                                 {
                                     if let Some(piece) = delta
                                         .choices
-                                        .get(0)
+                                        .first()
                                         .and_then(|c| c.delta.content.as_deref())
+                                        .filter(|piece| !piece.is_empty())
                                     {
+                                        on_delta(piece);
                                         out.push_str(piece);
                                     }
                                 }
                             }
                         }
                     }
+                    // Whatever is left is an incomplete event; a stream that never sends a
+                    // boundary must not grow it without bound.
+                    if buffer.len() > self.config.max_stream_buffer_bytes {
+                        return Err(OpenAiClientError::StreamBufferExceeded(
+                            self.config.max_stream_buffer_bytes,
+                        ));
+                    }
                 }
                 Err(OpenAiClientError::StreamEnded)
             }
@@ -196,53 +549,107 @@ impl OpenAiClient {
     async fn parse_json_response<T: for<'de> Deserialize<'de>>(
         resp: reqwest::Response,
         max_error_body_bytes: usize,
+        request_id: String,
     ) -> Result<T, OpenAiClientError> {
         if resp.status().is_success() {
             let json = resp.json::<T>().await?;
             return Ok(json);
         }
-        Err(Self::to_upstream_error(resp, max_error_body_bytes).await)
+        Err(Self::to_upstream_error(resp, max_error_body_bytes, request_id).await)
     }
 
     async fn to_upstream_error(
         resp: reqwest::Response,
         max_error_body_bytes: usize,
+        request_id: String,
     ) -> OpenAiClientError {
         let status = resp.status();
+        let retry_after = retry_after(status, resp.headers());
         let body = read_limited_text(resp, max_error_body_bytes).await;
         if let Ok(parsed) = serde_json::from_str::<OpenAiErrorEnvelope>(&body) {
             let message = parsed
                 .error
                 .message
                 .unwrap_or_else(|| "unknown upstream error".to_string());
-            return OpenAiClientError::Upstream { status, message };
+            return OpenAiClientError::Upstream {
+                status,
+                message,
+                retry_after,
+                request_id,
+            };
+        }
+        OpenAiClientError::UpstreamBody {
+            status,
+            body,
+            retry_after,
+            request_id,
         }
-        OpenAiClientError::UpstreamBody { status, body }
     }
 
-    async fn request_with_retry<T, Fut, F>(&self, mut f: F) -> Result<T, OpenAiClientError>
+    /// Run `f` until it succeeds or the error is final. One request id is created per call
+    /// and reused on every retry, so all attempts share it and one `openai_request` span.
+    async fn request_with_retry<T, Fut, F>(
+        &self,
+        endpoint: &'static str,
+        f: F,
+    ) -> Result<T, OpenAiClientError>
+    where
+        F: FnMut(String) -> Fut,
+        Fut: std::future::Future<Output = Result<T, OpenAiClientError>>,
+    {
+        self.request_with_retries(endpoint, self.config.max_retries, f).await
+    }
+
+    /// Like [`Self::request_with_retry`], with `max_retries` in place of the configured
+    /// count.
+    async fn request_with_retries<T, Fut, F>(
+        &self,
+        endpoint: &'static str,
+        max_retries: u32,
+        f: F,
+    ) -> Result<T, OpenAiClientError>
+    where
+        F: FnMut(String) -> Fut,
+        Fut: std::future::Future<Output = Result<T, OpenAiClientError>>,
+    {
+        let request_id = new_unique_id();
+        let span = tracing::info_span!("openai_request", endpoint, request_id = %request_id);
+        self.retry_loop(endpoint, request_id, max_retries, f).instrument(span).await
+    }
+
+    async fn retry_loop<T, Fut, F>(
+        &self,
+        endpoint: &'static str,
+        request_id: String,
+        max_retries: u32,
+        mut f: F,
+    ) -> Result<T, OpenAiClientError>
     where
-        F: FnMut() -> Fut,
+        F: FnMut(String) -> Fut,
         Fut: std::future::Future<Output = Result<T, OpenAiClientError>>,
     {
         let mut attempt: u32 = 0;
         loop {
             attempt += 1;
-            let result = f().await;
+            self.admit()?;
+            let started = Instant::now();
+            let result = f(request_id.clone()).await;
+            let outcome = if result.is_ok() { "ok" } else { "error" };
+            histogram!(UPSTREAM_LATENCY_METRIC, "endpoint" => endpoint, "outcome" => outcome)
+                .record(started.elapsed());
+            self.record_outcome(result.as_ref().err());
             match result {
                 Ok(v) => return Ok(v),
                 Err(e) => {
-                    if attempt > self.config.max_retries || !should_retry(&e) {
+                    if attempt > max_retries || !should_retry(&e) {
                         return Err(e);
                     }
-                    let delay = backoff_delay(
-                        self.config.initial_backoff,
-                        self.config.max_backoff,
-                        attempt - 1,
-                    );
+                    let delay = self.retry_delay(&e, attempt - 1);
                     warn!(
                         attempt,
+                        request_id = %request_id,
                         delay_ms = delay.as_millis(),
+                        retry_after = e.retry_after().is_some(),
                         error = %e,
                         "openai request failed, retrying"
                     );
@@ -251,6 +658,132 @@ impl OpenAiClient {
             }
         }
     }
+
+    /// How long to wait before retrying after `err`: the upstream's `Retry-After` capped by
+    /// `max_backoff` when it sent one, the exponential backoff otherwise.
+    fn retry_delay(&self, err: &OpenAiClientError, exponent: u32) -> Duration {
+        match err.retry_after() {
+            Some(requested) => requested.min(self.config.max_backoff),
+            None => {
+                let mut rng = self.jitter.lock().unwrap_or_else(|e| e.into_inner());
+                backoff_delay(
+                    self.config.backoff_strategy,
+                    self.config.initial_backoff,
+                    self.config.max_backoff,
+                    exponent,
+                    &mut rng,
+                )
+            }
+        }
+    }
+
+    /// Fail fast while the circuit is open. Once the cooldown has passed, one caller is let
+    /// through as a probe and the circuit stays open for everyone else until it resolves.
+    fn admit(&self) -> Result<(), OpenAiClientError> {
+        if self.config.circuit_breaker_failures == 0 {
+            return Ok(());
+        }
+        let mut breaker = self.breaker.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(open_until) = breaker.open_until else {
+            return Ok(());
+        };
+        let now = Instant::now();
+        if now < open_until {
+            return Err(OpenAiClientError::CircuitOpen {
+                retry_in_ms: (open_until - now).as_millis() as u64,
+            });
+        }
+        breaker.open_until = Some(now + self.config.circuit_breaker_cooldown);
+        Ok(())
+    }
+
+    /// Count consecutive host failures, opening the circuit at the threshold. Any other
+    /// outcome shows the host is up and closes it.
+    fn record_outcome(&self, err: Option<&OpenAiClientError>) {
+        let threshold = self.config.circuit_breaker_failures;
+        if threshold == 0 {
+            return;
+        }
+        let mut breaker = self.breaker.lock().unwrap_or_else(|e| e.into_inner());
+        if !err.is_some_and(is_host_failure) {
+            breaker.consecutive_failures = 0;
+            breaker.open_until = None;
+            return;
+        }
+        breaker.consecutive_failures = breaker.consecutive_failures.saturating_add(1);
+        if breaker.consecutive_failures >= threshold {
+            if breaker.open_until.is_none() {
+                warn!(
+                    failures = breaker.consecutive_failures,
+                    cooldown_ms = self.config.circuit_breaker_cooldown.as_millis(),
+                    "openai upstream circuit opened"
+                );
+            }
+            breaker.open_until = Some(Instant::now() + self.config.circuit_breaker_cooldown);
+        }
+    }
+}
+
+/// Parse `OPENAI_EXTRA_HEADERS` (`name=value` pairs separated by commas). An entry without
+/// `=` is kept with an empty value so client construction rejects it instead of dropping it.
+fn parse_header_list(value: &str) -> HashMap<String, String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| match entry.split_once('=') {
+            Some((name, value)) => (name.trim().to_string(), value.trim().to_string()),
+            None => (entry.to_string(), String::new()),
+        })
+        .collect()
+}
+
+/// Headers attached to every request: extra headers plus auth. Empty for unauthenticated
+/// local hosts.
+fn default_headers(
+    config: &OpenAiClientConfig,
+) -> Result<reqwest::header::HeaderMap, OpenAiClientError> {
+    use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION};
+
+    let mut headers = HeaderMap::new();
+    for (name, value) in &config.default_headers {
+        let invalid = |what: &str| {
+            OpenAiClientError::InvalidConfig(format!(
+                "OPENAI_EXTRA_HEADERS entry {name:?}: {what} (expected name=value)"
+            ))
+        };
+        let header_name =
+            HeaderName::from_bytes(name.as_bytes()).map_err(|_| invalid("invalid header name"))?;
+        if value.is_empty() {
+            return Err(invalid("missing value"));
+        }
+        let header_value = HeaderValue::from_str(value).map_err(|_| invalid("invalid value"))?;
+        headers.insert(header_name, header_value);
+    }
+    if let Some(key) = &config.api_key {
+        let mut value = HeaderValue::from_str(&format!("Bearer {key}")).map_err(|_| {
+            OpenAiClientError::InvalidConfig("OPENAI_API_KEY is not a valid header value".into())
+        })?;
+        value.set_sensitive(true);
+        headers.insert(AUTHORIZATION, value);
+    }
+    if let Some(org) = &config.organization {
+        let value = HeaderValue::from_str(org).map_err(|_| {
+            OpenAiClientError::InvalidConfig("OPENAI_ORG is not a valid header value".into())
+        })?;
+        headers.insert("OpenAI-Organization", value);
+    }
+    Ok(headers)
+}
+
+/// Errors that suggest the upstream host itself is down or failing.
+fn is_host_failure(err: &OpenAiClientError) -> bool {
+    match err {
+        OpenAiClientError::Request(e) => e.is_timeout() || e.is_connect(),
+        OpenAiClientError::Upstream { status, .. }
+        | OpenAiClientError::UpstreamBody { status, .. } => status.is_server_error(),
+        _ => false,
+    }
 }
 
 fn should_retry(err: &OpenAiClientError) -> bool {
@@ -262,25 +795,59 @@ fn should_retry(err: &OpenAiClientError) -> bool {
         | OpenAiClientError::UpstreamBody { status, .. } => {
             *status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
         }
-        OpenAiClientError::InvalidJson(_) | OpenAiClientError::StreamEnded => false,
+        OpenAiClientError::InvalidJson(_)
+        | OpenAiClientError::StreamEnded
+        | OpenAiClientError::StreamInterrupted(_)
+        | OpenAiClientError::StreamBufferExceeded(_)
+        | OpenAiClientError::InvalidConfig(_)
+        | OpenAiClientError::CircuitOpen { .. } => false,
     }
 }
 
-fn backoff_delay(initial: Duration, max: Duration, exponent: u32) -> Duration {
+/// The `Retry-After` delay of a 429 or 503 response; `None` for other statuses and for a
+/// missing or malformed header.
+fn retry_after(status: StatusCode, headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    if status != StatusCode::TOO_MANY_REQUESTS && status != StatusCode::SERVICE_UNAVAILABLE {
+        return None;
+    }
+    let value = headers.get(reqwest::header::RETRY_AFTER)?.to_str().ok()?;
+    parse_retry_after(value, SystemTime::now())
+}
+
+/// Parse a `Retry-After` value: whole seconds, or an HTTP date (a date already past means
+/// no wait).
+fn parse_retry_after(value: &str, now: SystemTime) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(date.duration_since(now).unwrap_or(Duration::ZERO))
+}
+
+/// Exponential backoff capped at `max`, with jitter drawn from `rng` as `strategy` says.
+fn backoff_delay(
+    strategy: BackoffStrategy,
+    initial: Duration,
+    max: Duration,
+    exponent: u32,
+    rng: &mut fastrand::Rng,
+) -> Duration {
     let mult = 1u128.checked_shl(exponent).unwrap_or(u128::MAX);
     let base_ms = initial.as_millis().saturating_mul(mult);
     let capped_ms = std::cmp::min(base_ms, max.as_millis()) as u64;
-    let jitter_cap = std::cmp::max(1, capped_ms / 4);
-    let jitter_ms = pseudo_jitter_ms(jitter_cap);
-    Duration::from_millis(capped_ms.saturating_add(jitter_ms))
-}
-
-fn pseudo_jitter_ms(max_inclusive: u64) -> u64 {
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_else(|_| Duration::from_secs(0));
-    let nanos = now.subsec_nanos() as u64;
-    nanos % (max_inclusive + 1)
+    let delay_ms = match strategy {
+        BackoffStrategy::Exponential => {
+            let jitter_cap = std::cmp::max(1, capped_ms / 4);
+            capped_ms.saturating_add(rng.u64(0..=jitter_cap))
+        }
+        BackoffStrategy::EqualJitter => {
+            let half = capped_ms / 2;
+            half + rng.u64(0..=capped_ms - half)
+        }
+        BackoffStrategy::FullJitter => rng.u64(0..=capped_ms),
+    };
+    Duration::from_millis(delay_ms)
 }
 
 async fn read_limited_text(resp: reqwest::Response, max_bytes: usize) -> String {
@@ -329,7 +896,60 @@ pub struct ModelInfo {
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Message {
     pub role: String,
+    /// Message text. May be empty on assistant messages that only carry `tool_calls`.
+    #[serde(default)]
     pub content: String,
+    /// Tool calls requested by the assistant (assistant messages only).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_calls: Option<Vec<ToolCall>>,
+    /// ID of the tool call this message answers (`tool` messages only).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+}
+
+impl Message {
+    /// A plain text message with the given role.
+    pub fn new(role: impl Into<String>, content: impl Into<String>) -> Self {
+        Self {
+            role: role.into(),
+            content: content.into(),
+            tool_calls: None,
+            tool_call_id: None,
+        }
+    }
+}
+
+/// A tool the model may call, in the OpenAI `tools` format.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ToolDefinition {
+    /// Always "function".
+    pub r#type: String,
+    pub function: FunctionDefinition,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FunctionDefinition {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// JSON Schema for the function arguments.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parameters: Option<serde_json::Value>,
+}
+
+/// A tool call emitted by the model.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ToolCall {
+    pub id: String,
+    pub r#type: String,
+    pub function: FunctionCall,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FunctionCall {
+    pub name: String,
+    /// Arguments as a JSON-encoded string, exactly as the model produced them.
+    pub arguments: String,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -341,7 +961,31 @@ pub struct ChatCompletionRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max_tokens: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    /// Sequences at which the model stops generating; not included in the output.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frequency_penalty: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub presence_penalty: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub stream: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tools: Option<Vec<ToolDefinition>>,
+    /// "none", "auto", "required", or `{ "type": "function", "function": { "name": ... } }`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_choice: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<ResponseFormat>,
+}
+
+/// Output format requested from the model (`{"type": "text"}` or `{"type": "json_object"}`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ResponseFormat {
+    Text,
+    JsonObject,
 }
 
 #[derive(Debug, Clone, Deserialize, JsonSchema)]
@@ -363,6 +1007,7 @@ pub struct ChatCompletionChoice {
 pub struct ChatCompletionMessage {
     pub role: Option<String>,
     pub content: Option<String>,
+    pub tool_calls: Option<Vec<ToolCall>>,
 }
 
 #[derive(Debug, Clone, Deserialize, JsonSchema)]
@@ -372,6 +1017,26 @@ pub struct ChatCompletionUsage {
     pub total_tokens: Option<u64>,
 }
 
+#[derive(Debug, Clone, Serialize)]
+pub struct EmbeddingRequest {
+    pub model: String,
+    pub input: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct EmbeddingResponse {
+    pub object: Option<String>,
+    pub data: Vec<EmbeddingData>,
+    pub model: Option<String>,
+    pub usage: Option<ChatCompletionUsage>,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct EmbeddingData {
+    pub index: usize,
+    pub embedding: Vec<f32>,
+}
+
 #[derive(Debug, Deserialize)]
 struct ChatCompletionStreamChunk {
     choices: Vec<ChatCompletionStreamChoice>,
@@ -386,3 +1051,530 @@ struct ChatCompletionStreamChoice {
 struct ChatCompletionStreamDelta {
     content: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    /// Serve a single canned SSE response and return the base URL.
+    async fn serve_sse_once(body: &'static str) -> String {
+        serve_once("text/event-stream", body.to_string()).await
+    }
+
+    /// Serve a single canned JSON response and return the base URL.
+    async fn serve_json_once(body: &'static str) -> String {
+        serve_once("application/json", body.to_string()).await
+    }
+
+    async fn serve_once(content_type: &'static str, body: String) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0u8; 8192];
+            let _ = socket.read(&mut buf).await;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: {content_type}\r\ncontent-length: {}\r\n\r\n{body}",
+                body.len()
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+        });
+        format!("http://{addr}/v1")
+    }
+
+    fn test_client(base_url: String) -> OpenAiClient {
+        OpenAiClient::new(OpenAiClientConfig {
+            base_url,
+            default_timeout: Duration::from_secs(5),
+            max_retries: 0,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(1),
+            backoff_strategy: BackoffStrategy::Exponential,
+            max_error_body_bytes: 1024,
+            max_stream_buffer_bytes: 4096,
+            api_key: None,
+            organization: None,
+            default_headers: HashMap::new(),
+            proxy: None,
+            circuit_breaker_failures: 0,
+            circuit_breaker_cooldown: Duration::from_secs(30),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn redacted_base_url_strips_credentials() {
+        let mut config = OpenAiClientConfig::from_env();
+        config.base_url = "http://user:secret@ai:8001/v1".to_string();
+        assert_eq!(config.redacted_base_url(), "http://ai:8001/v1");
+
+        config.base_url = "http://ai:8001/v1".to_string();
+        assert_eq!(config.redacted_base_url(), "http://ai:8001/v1");
+    }
+
+    fn request() -> ChatCompletionRequest {
+        ChatCompletionRequest {
+            model: "m".to_string(),
+            messages: vec![Message::new("user", "hi")],
+            temperature: None,
+            max_tokens: None,
+            top_p: None,
+            stop: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            stream: None,
+            tools: None,
+            tool_choice: None,
+            response_format: None,
+        }
+    }
+
+    #[test]
+    fn request_omits_unset_tool_fields() {
+        let json = serde_json::to_value(request()).unwrap();
+        assert!(json.get("tools").is_none());
+        assert!(json.get("tool_choice").is_none());
+        assert!(json["messages"][0].get("tool_calls").is_none());
+        for field in ["top_p", "stop", "frequency_penalty", "presence_penalty"] {
+            assert!(json.get(field).is_none(), "{field} should be omitted");
+        }
+    }
+
+    #[test]
+    fn response_format_serializes_as_typed_object() {
+        let mut req = request();
+        assert!(serde_json::to_value(&req).unwrap().get("response_format").is_none());
+
+        req.response_format = Some(ResponseFormat::JsonObject);
+        let json = serde_json::to_value(&req).unwrap();
+        assert_eq!(json["response_format"], serde_json::json!({ "type": "json_object" }));
+
+        req.response_format = Some(ResponseFormat::Text);
+        let json = serde_json::to_value(&req).unwrap();
+        assert_eq!(json["response_format"], serde_json::json!({ "type": "text" }));
+    }
+
+    #[test]
+    fn response_parses_tool_calls() {
+        let response: ChatCompletionResponse = serde_json::from_str(
+            r#"{"choices":[{"index":0,"finish_reason":"tool_calls","message":{
+                "role":"assistant","content":null,"tool_calls":[{"id":"call_1","type":"function",
+                "function":{"name":"lookup","arguments":"{\"id\":\"P.1\"}"}}]}}]}"#,
+        )
+        .unwrap();
+        let message = &response.choices[0].message;
+        assert!(message.content.is_none());
+        let calls = message.tool_calls.as_ref().unwrap();
+        assert_eq!(calls[0].id, "call_1");
+        assert_eq!(calls[0].function.name, "lookup");
+        assert_eq!(calls[0].function.arguments, r#"{"id":"P.1"}"#);
+    }
+
+    #[tokio::test]
+    async fn streaming_delivers_pieces_in_order() {
+        let base_url = serve_sse_once(
+            "data: {\"choices\":[{\"delta\":{\"role\":\"assistant\"}}]}\n\n\
+             data: {\"choices\":[{\"delta\":{\"content\":\"Hel\"}}]}\n\n\
+             data: {\"choices\":[{\"delta\":{\"content\":\"lo\"}}]}\n\n\
+             data: [DONE]\n\n",
+        )
+        .await;
+        let client = test_client(base_url);
+
+        let pieces = Mutex::new(Vec::new());
+        let text = client
+            .chat_completions_streaming(request(), None, |piece| {
+                pieces.lock().unwrap().push(piece.to_string());
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(text, "Hello");
+        assert_eq!(*pieces.lock().unwrap(), ["Hel", "lo"]);
+    }
+
+    #[tokio::test]
+    async fn raw_chat_forwards_the_body_and_returns_the_response_json() {
+        let body = r#"{"id":"c1","choices":[],"system_fingerprint":"fp_1"}"#;
+        let ok = format!(
+            "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\
+connection: close\r\n\r\n{body}",
+            body.len()
+        );
+        let (base_url, mut requests) = serve_responses(vec![ok]).await;
+        let client = test_client(base_url);
+
+        let request = serde_json::json!({ "model": "m", "messages": [], "seed": 42 });
+        let response = client.chat_completions_raw(request, None).await.unwrap();
+        assert_eq!(response["system_fingerprint"], "fp_1");
+
+        let sent = requests.recv().await.unwrap();
+        assert!(sent.starts_with("post /v1/chat/completions"), "{sent}");
+        assert!(sent.contains(r#""seed":42"#), "{sent}");
+    }
+
+    #[tokio::test]
+    async fn embeddings_parse_upstream_response() {
+        let base_url = serve_json_once(
+            "{\"object\":\"list\",\"model\":\"e\",\"data\":[\
+             {\"object\":\"embedding\",\"index\":1,\"embedding\":[0.5,0.25]},\
+             {\"object\":\"embedding\",\"index\":0,\"embedding\":[1.0,0.0]}],\
+             \"usage\":{\"prompt_tokens\":4,\"total_tokens\":4}}",
+        )
+        .await;
+        let client = test_client(base_url);
+
+        let response = client
+            .embeddings(EmbeddingRequest {
+                model: "e".to_string(),
+                input: vec!["a".to_string(), "b".to_string()],
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(response.data.len(), 2);
+        assert_eq!(response.data[0].index, 1);
+        assert_eq!(response.data[0].embedding, [0.5, 0.25]);
+        assert_eq!(response.usage.and_then(|u| u.total_tokens), Some(4));
+    }
+
+    #[tokio::test]
+    async fn streaming_without_done_is_an_error() {
+        let base_url =
+            serve_sse_once("data: {\"choices\":[{\"delta\":{\"content\":\"partial\"}}]}\n\n").await;
+        let client = test_client(base_url);
+
+        let err = client
+            .chat_completions_streaming_aggregate(request(), None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, OpenAiClientError::StreamEnded));
+    }
+
+    #[tokio::test]
+    async fn streaming_without_event_boundary_hits_buffer_cap() {
+        let body = format!("data: {}", "x".repeat(64 * 1024));
+        let base_url = serve_once("text/event-stream", body).await;
+        let client = test_client(base_url);
+
+        let err = client
+            .chat_completions_streaming_aggregate(request(), None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, OpenAiClientError::StreamBufferExceeded(4096)), "{err}");
+    }
+
+    #[tokio::test]
+    async fn auth_headers_are_sent_when_configured() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0u8; 8192];
+            let n = socket.read(&mut buf).await.unwrap();
+            let _ = tx.send(String::from_utf8_lossy(&buf[..n]).to_lowercase());
+            let body = r#"{"object":"list","data":[]}"#;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{body}",
+                body.len()
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+        });
+
+        let mut client = test_client(format!("http://{addr}/v1"));
+        client.config.api_key = Some("sk-test".to_string());
+        client.config.organization = Some("org-1".to_string());
+        client.config.default_headers = parse_header_list("X-Tenant=acme");
+        let client = OpenAiClient::new(client.config).unwrap();
+        client.list_models().await.unwrap();
+
+        let head = rx.await.unwrap();
+        assert!(head.contains("authorization: bearer sk-test"), "{head}");
+        assert!(head.contains("openai-organization: org-1"), "{head}");
+        assert!(head.contains("x-tenant: acme"), "{head}");
+        assert!(head.contains("x-request-id: "), "{head}");
+    }
+
+    #[test]
+    fn debug_output_redacts_api_key() {
+        let mut config = OpenAiClientConfig::from_env();
+        config.api_key = Some("sk-secret".to_string());
+        let debug = format!("{config:?}");
+        assert!(!debug.contains("sk-secret"), "{debug}");
+        assert!(debug.contains("<redacted>"), "{debug}");
+    }
+
+    #[test]
+    fn invalid_api_key_is_a_config_error() {
+        let mut config = test_client("http://127.0.0.1:1/v1".to_string()).config;
+        config.api_key = Some("bad\nkey".to_string());
+        assert!(matches!(
+            OpenAiClient::new(config),
+            Err(OpenAiClientError::InvalidConfig(_))
+        ));
+    }
+
+    #[test]
+    fn parses_extra_header_list() {
+        let headers = parse_header_list(" X-Tenant = acme ,X-Trace=1,, broken");
+        assert_eq!(headers["X-Tenant"], "acme");
+        assert_eq!(headers["X-Trace"], "1");
+        assert_eq!(headers["broken"], "");
+    }
+
+    #[test]
+    fn malformed_headers_and_proxy_are_config_errors() {
+        for list in ["broken", "bad name=1", "X-Ok=bad\u{7f}value"] {
+            let mut config = test_client("http://127.0.0.1:1/v1".to_string()).config;
+            config.default_headers = parse_header_list(list);
+            let result = OpenAiClient::new(config);
+            assert!(matches!(result, Err(OpenAiClientError::InvalidConfig(_))), "{list}");
+        }
+
+        let mut config = test_client("http://127.0.0.1:1/v1".to_string()).config;
+        config.proxy = Some("not a url".to_string());
+        assert!(matches!(
+            OpenAiClient::new(config),
+            Err(OpenAiClientError::InvalidConfig(_))
+        ));
+    }
+
+    /// Serve each raw HTTP response to its own connection, in order. Returns the base URL and
+    /// a receiver of the (lowercased) requests as they arrive.
+    async fn serve_responses(
+        responses: Vec<String>,
+    ) -> (String, tokio::sync::mpsc::UnboundedReceiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            for response in responses {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = vec![0u8; 8192];
+                let n = socket.read(&mut buf).await.unwrap_or(0);
+                let _ = tx.send(String::from_utf8_lossy(&buf[..n]).to_lowercase());
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        (format!("http://{addr}/v1"), rx)
+    }
+
+    fn request_id_header(head: &str) -> &str {
+        head.lines()
+            .find_map(|line| line.strip_prefix("x-request-id: "))
+            .expect("request should carry x-request-id")
+            .trim()
+    }
+
+    #[test]
+    fn parses_retry_after_seconds_and_dates() {
+        let now = std::time::UNIX_EPOCH + Duration::from_secs(1_445_412_480);
+        assert_eq!(parse_retry_after(" 7 ", now), Some(Duration::from_secs(7)));
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:30 GMT", now),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:27:00 GMT", now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("soon", now), None);
+        assert_eq!(parse_retry_after("-1", now), None);
+    }
+
+    #[test]
+    fn retry_after_is_capped_by_max_backoff() {
+        let client = test_client("http://127.0.0.1:1/v1".to_string());
+        let busy = OpenAiClientError::UpstreamBody {
+            status: StatusCode::TOO_MANY_REQUESTS,
+            body: String::new(),
+            retry_after: Some(Duration::from_secs(120)),
+            request_id: String::new(),
+        };
+        assert_eq!(client.retry_delay(&busy, 0), Duration::from_millis(1));
+    }
+
+    #[test]
+    fn backoff_strategies_stay_within_their_ranges() {
+        let mut rng = fastrand::Rng::with_seed(7);
+        let initial = Duration::from_millis(100);
+        let max = Duration::from_millis(1_000);
+        let delays = |strategy, exponent, rng: &mut fastrand::Rng| -> Vec<u64> {
+            (0..500)
+                .map(|_| backoff_delay(strategy, initial, max, exponent, rng).as_millis() as u64)
+                .collect()
+        };
+
+        // Third retry: 100ms * 2^2 = 400ms, under the cap.
+        let exponential = delays(BackoffStrategy::Exponential, 2, &mut rng);
+        assert!(exponential.iter().all(|ms| (400..=500).contains(ms)));
+        let equal = delays(BackoffStrategy::EqualJitter, 2, &mut rng);
+        assert!(equal.iter().all(|ms| (200..=400).contains(ms)));
+        let full = delays(BackoffStrategy::FullJitter, 2, &mut rng);
+        assert!(full.iter().all(|ms| *ms <= 400));
+        assert!(full.iter().any(|ms| *ms < 200), "full jitter should reach below half");
+
+        // Far past the cap every strategy is bounded by max_backoff.
+        let capped = delays(BackoffStrategy::FullJitter, 40, &mut rng);
+        assert!(capped.iter().all(|ms| *ms <= 1_000));
+    }
+
+    #[test]
+    fn parses_backoff_strategy_names() {
+        assert_eq!(BackoffStrategy::parse(" Full_Jitter "), Some(BackoffStrategy::FullJitter));
+        assert_eq!(BackoffStrategy::parse("equal_jitter"), Some(BackoffStrategy::EqualJitter));
+        assert_eq!(BackoffStrategy::parse("exponential"), Some(BackoffStrategy::Exponential));
+        assert_eq!(BackoffStrategy::parse("linear"), None);
+        assert_eq!(BackoffStrategy::FullJitter.as_str(), "full_jitter");
+    }
+
+    #[test]
+    fn back_to_back_retries_draw_independent_jitter() {
+        let mut config = test_client("http://127.0.0.1:1/v1".to_string()).config;
+        config.initial_backoff = Duration::from_millis(400);
+        config.max_backoff = Duration::from_millis(400);
+        let client = OpenAiClient::new(config).unwrap();
+        let failure = OpenAiClientError::StreamEnded;
+
+        // All drawn within a millisecond or so; clock-derived jitter would barely vary.
+        let delays: Vec<u128> = (0..200)
+            .map(|_| client.retry_delay(&failure, 0).as_millis())
+            .collect();
+        assert!(delays.iter().all(|ms| (400..=500).contains(ms)), "{delays:?}");
+        let distinct: std::collections::HashSet<_> = delays.iter().collect();
+        assert!(distinct.len() > 50, "only {} distinct delays", distinct.len());
+    }
+
+    #[tokio::test]
+    async fn retries_429_after_the_requested_delay() {
+        let busy = "HTTP/1.1 429 Too Many Requests\r\nretry-after: 0\r\n\
+content-length: 4\r\nconnection: close\r\n\r\nbusy";
+        let body = r#"{"object":"list","data":[]}"#;
+        let ok = format!(
+            "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\
+connection: close\r\n\r\n{body}",
+            body.len()
+        );
+        let (base_url, _) = serve_responses(vec![busy.to_string(), ok]).await;
+        let mut config = test_client(base_url).config;
+        // Without the header the retry would sleep for a minute.
+        config.max_retries = 1;
+        config.initial_backoff = Duration::from_secs(60);
+        config.max_backoff = Duration::from_secs(60);
+        let client = OpenAiClient::new(config).unwrap();
+
+        let models = tokio::time::timeout(Duration::from_secs(5), client.list_models())
+            .await
+            .expect("retry should honor Retry-After: 0")
+            .unwrap();
+        assert!(models.data.is_empty());
+    }
+
+    #[tokio::test]
+    async fn retries_reuse_the_request_id_and_errors_report_it() {
+        let busy = "HTTP/1.1 503 Service Unavailable\r\ncontent-length: 4\r\n\
+connection: close\r\n\r\nbusy";
+        let (base_url, mut requests) =
+            serve_responses(vec![busy.to_string(), busy.to_string(), busy.to_string()]).await;
+        let mut config = test_client(base_url).config;
+        config.max_retries = 1;
+        let client = OpenAiClient::new(config).unwrap();
+
+        let err = client.list_models().await.unwrap_err();
+        let first = requests.recv().await.unwrap();
+        let second = requests.recv().await.unwrap();
+        let id = request_id_header(&first);
+        assert_eq!(id.len(), 32);
+        assert_eq!(request_id_header(&second), id);
+        assert_eq!(err.request_id(), Some(id));
+        assert!(err.to_string().ends_with(&format!("request_id={id}")), "{err}");
+
+        // A new call gets a new id.
+        let _ = client.list_models().await;
+        let third = requests.recv().await.unwrap();
+        assert_ne!(request_id_header(&third), id);
+    }
+
+    #[tokio::test]
+    async fn list_models_once_does_not_retry() {
+        let busy = "HTTP/1.1 503 Service Unavailable\r\ncontent-length: 4\r\n\
+connection: close\r\n\r\nbusy";
+        let (base_url, mut requests) =
+            serve_responses(vec![busy.to_string(), busy.to_string()]).await;
+        let mut config = test_client(base_url).config;
+        config.max_retries = 1;
+        let client = OpenAiClient::new(config).unwrap();
+
+        assert!(client.list_models_once().await.is_err());
+        requests.recv().await.unwrap();
+        assert!(requests.try_recv().is_err());
+    }
+
+    /// Base URL of a port with nothing listening, so requests fail to connect.
+    async fn dead_base_url() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+        format!("http://{addr}/v1")
+    }
+
+    #[tokio::test]
+    async fn circuit_opens_after_consecutive_failures() {
+        let mut config = test_client(dead_base_url().await).config;
+        config.circuit_breaker_failures = 2;
+        let client = OpenAiClient::new(config).unwrap();
+
+        for _ in 0..2 {
+            let err = client.list_models().await.unwrap_err();
+            assert!(matches!(err, OpenAiClientError::Request(_)), "{err}");
+        }
+        let err = client.list_models().await.unwrap_err();
+        assert!(matches!(err, OpenAiClientError::CircuitOpen { .. }), "{err}");
+    }
+
+    #[tokio::test]
+    async fn probe_models_ignores_the_circuit() {
+        let mut config = test_client(dead_base_url().await).config;
+        config.circuit_breaker_failures = 1;
+        let client = OpenAiClient::new(config).unwrap();
+
+        for _ in 0..2 {
+            let err = client.probe_models().await.unwrap_err();
+            assert!(matches!(err, OpenAiClientError::Request(_)), "{err}");
+        }
+        assert_eq!(client.breaker.lock().unwrap().consecutive_failures, 0);
+
+        // An open circuit does not stop the probe either.
+        assert!(client.list_models().await.is_err());
+        let err = client.probe_models().await.unwrap_err();
+        assert!(matches!(err, OpenAiClientError::Request(_)), "{err}");
+    }
+
+    #[tokio::test]
+    async fn circuit_probes_after_cooldown_and_closes_on_success() {
+        let mut config = test_client(dead_base_url().await).config;
+        config.circuit_breaker_failures = 1;
+        config.circuit_breaker_cooldown = Duration::from_millis(50);
+        let mut client = OpenAiClient::new(config).unwrap();
+
+        assert!(client.list_models().await.is_err());
+        assert!(matches!(
+            client.list_models().await,
+            Err(OpenAiClientError::CircuitOpen { .. })
+        ));
+
+        // The host comes back; after the cooldown a probe goes through and closes the circuit.
+        client.config.base_url = serve_json_once(r#"{"object":"list","data":[]}"#).await;
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        client.list_models().await.unwrap();
+        let breaker = client.breaker.lock().unwrap();
+        assert_eq!(breaker.consecutive_failures, 0);
+        assert!(breaker.open_until.is_none());
+    }
+}
@@ -0,0 +1,133 @@
+/// Rendering of guideline markdown into client-friendly formats.
+///
+/// `get_guideline` returns the raw markdown by default; clients that cannot render
+/// markdown can ask for HTML or plain text instead.
+use pulldown_cmark::{html, Event, Options, Parser, TagEnd};
+
+use crate::mcp_api::GuidelineDetailResponse;
+
+/// Upper bound on rendered output. Guidelines are a few KB; anything larger is a
+/// pathological input and is refused rather than returned.
+pub const MAX_RENDERED_BYTES: usize = 512 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RenderFormat {
+    /// Raw markdown passthrough.
+    #[default]
+    Markdown,
+    Html,
+    /// Markdown with formatting stripped, one block per line.
+    Text,
+}
+
+impl RenderFormat {
+    /// Parse a format name (`markdown`, `html` or `text`, case-insensitive).
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "markdown" | "md" => Ok(Self::Markdown),
+            "html" => Ok(Self::Html),
+            "text" | "plain" => Ok(Self::Text),
+            other => Err(format!(
+                "unknown render format: {other:?} (expected markdown, html or text)"
+            )),
+        }
+    }
+}
+
+/// Render markdown in the requested format, failing if the output exceeds
+/// [`MAX_RENDERED_BYTES`].
+pub fn render(markdown: &str, format: RenderFormat) -> Result<String, String> {
+    let rendered = match format {
+        RenderFormat::Markdown => markdown.to_string(),
+        RenderFormat::Html => {
+            let mut out = String::with_capacity(markdown.len() * 3 / 2);
+            html::push_html(&mut out, Parser::new_ext(markdown, options()));
+            out
+        }
+        RenderFormat::Text => to_text(markdown),
+    };
+
+    if rendered.len() > MAX_RENDERED_BYTES {
+        return Err(format!(
+            "rendered output is {} bytes, over the {MAX_RENDERED_BYTES} byte limit",
+            rendered.len()
+        ));
+    }
+    Ok(rendered)
+}
+
+/// Fill `rendered` from `raw_markdown` when a format was requested.
+pub fn render_detail(
+    mut detail: GuidelineDetailResponse,
+    format: Option<RenderFormat>,
+) -> Result<GuidelineDetailResponse, String> {
+    if let Some(format) = format {
+        detail.rendered = Some(render(&detail.raw_markdown, format)?);
+    }
+    Ok(detail)
+}
+
+fn options() -> Options {
+    Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH
+}
+
+fn to_text(markdown: &str) -> String {
+    let mut out = String::with_capacity(markdown.len());
+    for event in Parser::new_ext(markdown, options()) {
+        match event {
+            Event::Text(t) | Event::Code(t) => out.push_str(&t),
+            Event::SoftBreak => out.push(' '),
+            Event::HardBreak => out.push('\n'),
+            Event::End(
+                TagEnd::Paragraph
+                | TagEnd::Heading(_)
+                | TagEnd::CodeBlock
+                | TagEnd::Item
+                | TagEnd::TableRow
+                | TagEnd::TableHead,
+            ) if !out.ends_with('\n') => out.push('\n'),
+            Event::End(TagEnd::TableCell) => out.push('\t'),
+            _ => {}
+        }
+    }
+    out.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "### P.1: Express ideas\n\nUse `std::vector` **always**.\n\n```cpp\nint x = 0;\n```\n\n| a | b |\n|---|---|\n| 1 | 2 |\n";
+
+    #[test]
+    fn parses_format_names() {
+        assert_eq!(RenderFormat::parse("HTML").unwrap(), RenderFormat::Html);
+        assert_eq!(RenderFormat::parse(" text ").unwrap(), RenderFormat::Text);
+        assert_eq!(RenderFormat::parse("markdown").unwrap(), RenderFormat::Markdown);
+        assert!(RenderFormat::parse("pdf").is_err());
+    }
+
+    #[test]
+    fn renders_html_with_code_blocks_and_tables() {
+        let html = render(SAMPLE, RenderFormat::Html).unwrap();
+        assert!(html.contains("<h3>P.1: Express ideas</h3>"), "{html}");
+        assert!(html.contains("<code>std::vector</code>"), "{html}");
+        assert!(html.contains("<pre><code class=\"language-cpp\">"), "{html}");
+        assert!(html.contains("<table>"), "{html}");
+    }
+
+    #[test]
+    fn renders_plain_text() {
+        let text = render(SAMPLE, RenderFormat::Text).unwrap();
+        assert!(text.starts_with("P.1: Express ideas\nUse std::vector always.\n"), "{text}");
+        assert!(text.contains("int x = 0;"), "{text}");
+        assert!(!text.contains('*') && !text.contains('`'), "{text}");
+    }
+
+    #[test]
+    fn markdown_is_passthrough_and_size_is_capped() {
+        assert_eq!(render(SAMPLE, RenderFormat::Markdown).unwrap(), SAMPLE);
+        let huge = "x".repeat(MAX_RENDERED_BYTES + 1);
+        assert!(render(&huge, RenderFormat::Markdown).is_err());
+    }
+}
@@ -0,0 +1,36 @@
+/// Process shutdown signals shared by every server binary.
+///
+/// Docker and Kubernetes stop containers with SIGTERM; an interactive run is stopped with
+/// Ctrl-C. Servers wait on [`shutdown_signal`] so both end in a clean exit instead of a
+/// hard kill.
+use tracing::info;
+
+/// Resolve once SIGINT (Ctrl-C) or, on Unix, SIGTERM is received.
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!(error = %e, "failed to listen for Ctrl-C");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                tracing::error!(error = %e, "failed to listen for SIGTERM");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        () = ctrl_c => info!("received SIGINT, shutting down"),
+        () = terminate => info!("received SIGTERM, shutting down"),
+    }
+}
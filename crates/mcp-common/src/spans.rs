@@ -0,0 +1,24 @@
+/// Per-call tracing spans for MCP tools.
+///
+/// Every tool method runs in a span named `tool`, declared with
+/// `#[tracing::instrument(name = "tool", skip_all, fields(...))]`, whose fields are:
+/// - `tool`: the tool's name
+/// - `input_bytes`: size of the call's arguments as JSON (see [`input_bytes`])
+/// - `cache`: `hit` or `miss` for the last cache lookup the call made (see
+///   [`record_cache`]); left empty when it made none
+///
+/// Each server logs span close events to stderr; they carry the call's elapsed time as
+/// `time.busy` and `time.idle`.
+use serde::Serialize;
+use tracing::Span;
+
+/// Size in bytes of `params` re-serialized as JSON.
+pub fn input_bytes<T: Serialize>(params: &T) -> usize {
+    serde_json::to_vec(params).map_or(0, |json| json.len())
+}
+
+/// Record a cache lookup's outcome in the current span's `cache` field. A no-op outside a
+/// span that declares the field.
+pub fn record_cache(hit: bool) {
+    Span::current().record("cache", if hit { "hit" } else { "miss" });
+}
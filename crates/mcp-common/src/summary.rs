@@ -0,0 +1,55 @@
+/// Length of the `summary` field in search results, from `SEARCH_SUMMARY_LEN`.
+///
+/// Summaries are the head of each guideline's indexed text, cut at a character (not byte)
+/// boundary. Agents with large context windows can ask for fuller summaries, token-constrained
+/// ones for shorter. Cached search results keep the summaries they were stored with until
+/// they expire (`SEARCH_CACHE_TTL_SECS`).
+
+/// Environment variable holding the summary length in characters.
+pub const SEARCH_SUMMARY_LEN_ENV: &str = "SEARCH_SUMMARY_LEN";
+
+/// Summary length used when `SEARCH_SUMMARY_LEN` is unset or invalid.
+pub const DEFAULT_SUMMARY_LEN: usize = 300;
+
+/// Longest accepted summary length; the indexed text itself is capped at 3000 characters.
+pub const MAX_SUMMARY_LEN: usize = 3000;
+
+/// Read `SEARCH_SUMMARY_LEN`, clamped to `1..=MAX_SUMMARY_LEN`.
+pub fn summary_len_from_env() -> usize {
+    parse_summary_len(std::env::var(SEARCH_SUMMARY_LEN_ENV).ok().as_deref())
+}
+
+fn parse_summary_len(value: Option<&str>) -> usize {
+    value
+        .and_then(|s| s.trim().parse::<usize>().ok())
+        .map_or(DEFAULT_SUMMARY_LEN, |len| len.clamp(1, MAX_SUMMARY_LEN))
+}
+
+/// The first `max_chars` characters of `text`, followed by `...` when anything was cut.
+pub fn summarize(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}...", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lengths_are_clamped_with_a_default() {
+        assert_eq!(parse_summary_len(None), DEFAULT_SUMMARY_LEN);
+        assert_eq!(parse_summary_len(Some("lots")), DEFAULT_SUMMARY_LEN);
+        assert_eq!(parse_summary_len(Some(" 800 ")), 800);
+        assert_eq!(parse_summary_len(Some("0")), 1);
+        assert_eq!(parse_summary_len(Some("1000000")), MAX_SUMMARY_LEN);
+    }
+
+    #[test]
+    fn summaries_cut_at_char_boundaries() {
+        assert_eq!(summarize("short", 10), "short");
+        assert_eq!(summarize("exact", 5), "exact");
+        assert_eq!(summarize("don’t — ever", 5), "don’t...");
+    }
+}
@@ -0,0 +1,55 @@
+/// Token estimation for budgeting chat requests before they are sent.
+///
+/// No model tokenizer is bundled, so counts use the common ~4 characters per token
+/// heuristic plus a small per-message allowance for chat framing (role markers and
+/// separators). Estimates are meant for budgeting, not billing.
+use crate::openai::Message;
+
+/// Average characters per token assumed by the heuristic.
+pub const CHARS_PER_TOKEN: usize = 4;
+/// Tokens added per chat message for role and separator framing.
+pub const MESSAGE_OVERHEAD_TOKENS: usize = 4;
+/// Tokens added once per request to prime the assistant reply.
+pub const REPLY_PRIMING_TOKENS: usize = 3;
+
+/// Name of the estimation method, reported alongside counts.
+pub const HEURISTIC_METHOD: &str = "chars_div_4";
+
+/// Estimate the token count of plain text.
+pub fn estimate_text_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(CHARS_PER_TOKEN)
+}
+
+/// Estimate the prompt token count of a chat message list.
+pub fn estimate_message_tokens(messages: &[Message]) -> usize {
+    let body: usize = messages
+        .iter()
+        .map(|m| {
+            MESSAGE_OVERHEAD_TOKENS + estimate_text_tokens(&m.role) + estimate_text_tokens(&m.content)
+        })
+        .sum();
+    body + REPLY_PRIMING_TOKENS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_rounds_up_to_whole_tokens() {
+        assert_eq!(estimate_text_tokens(""), 0);
+        assert_eq!(estimate_text_tokens("abc"), 1);
+        assert_eq!(estimate_text_tokens("abcd"), 1);
+        assert_eq!(estimate_text_tokens("abcde"), 2);
+        // Counts characters, not bytes.
+        assert_eq!(estimate_text_tokens("ééééé"), 2);
+    }
+
+    #[test]
+    fn messages_include_framing_overhead() {
+        let messages = vec![Message::new("user", "abcdefgh")];
+        // 4 overhead + 1 ("user") + 2 (content) + 3 priming
+        assert_eq!(estimate_message_tokens(&messages), 10);
+        assert_eq!(estimate_message_tokens(&[]), REPLY_PRIMING_TOKENS);
+    }
+}
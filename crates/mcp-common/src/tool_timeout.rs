@@ -0,0 +1,91 @@
+/// Deadline applied to every MCP tool call, from `TOOL_TIMEOUT_SECS`.
+///
+/// A call that overruns is dropped at its next await point and the client gets a
+/// "tool timed out" error instead of waiting on a hung upstream or a slow embedding. Dropping
+/// the call releases everything it held, including `AppState` lock guards.
+use std::future::Future;
+use std::time::Duration;
+
+use tracing::warn;
+
+/// Environment variable holding the limit in whole seconds.
+pub const TOOL_TIMEOUT_ENV: &str = "TOOL_TIMEOUT_SECS";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ToolTimeout {
+    limit: Option<Duration>,
+}
+
+impl ToolTimeout {
+    pub fn new(limit: Option<Duration>) -> Self {
+        Self { limit }
+    }
+
+    /// Read `TOOL_TIMEOUT_SECS`; unset or `0` leaves tool calls unbounded.
+    pub fn from_env() -> Result<Self, String> {
+        Self::parse(std::env::var(TOOL_TIMEOUT_ENV).ok().as_deref())
+    }
+
+    fn parse(value: Option<&str>) -> Result<Self, String> {
+        let Some(value) = value else {
+            return Ok(Self::default());
+        };
+        match value.trim().parse::<u64>() {
+            Ok(0) => Ok(Self::default()),
+            Ok(secs) => Ok(Self::new(Some(Duration::from_secs(secs)))),
+            Err(_) => Err(format!(
+                "{TOOL_TIMEOUT_ENV} must be a whole number of seconds, got '{value}'"
+            )),
+        }
+    }
+
+    /// The configured limit; `None` when calls are unbounded.
+    pub fn limit(&self) -> Option<Duration> {
+        self.limit
+    }
+
+    /// Run `call` for `tool`, failing with a "tool timed out" message once the limit passes.
+    pub async fn run<T>(&self, tool: &str, call: impl Future<Output = T>) -> Result<T, String> {
+        let Some(limit) = self.limit else {
+            return Ok(call.await);
+        };
+        tokio::time::timeout(limit, call).await.map_err(|_| {
+            warn!(tool, timeout_ms = limit.as_millis(), "tool timed out");
+            format!("tool timed out after {limit:?}: {tool}")
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_whole_seconds_with_zero_as_unbounded() {
+        assert_eq!(ToolTimeout::parse(None).unwrap().limit(), None);
+        assert_eq!(ToolTimeout::parse(Some("0")).unwrap().limit(), None);
+        assert_eq!(
+            ToolTimeout::parse(Some(" 20 ")).unwrap().limit(),
+            Some(Duration::from_secs(20))
+        );
+        let err = ToolTimeout::parse(Some("1.5")).unwrap_err();
+        assert!(err.contains("TOOL_TIMEOUT_SECS"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn overrunning_calls_fail_and_release_what_they_hold() {
+        let timeout = ToolTimeout::new(Some(Duration::from_millis(20)));
+        let lock = tokio::sync::RwLock::new(());
+
+        let hung = async {
+            let _guard = lock.write().await;
+            std::future::pending::<()>().await;
+        };
+        let err = timeout.run("search_guidelines", hung).await.unwrap_err();
+        assert_eq!(err, "tool timed out after 20ms: search_guidelines");
+        assert!(lock.try_write().is_ok());
+
+        assert_eq!(timeout.run("get_stats", async { 7 }).await, Ok(7));
+        assert_eq!(ToolTimeout::default().run("get_stats", async { 7 }).await, Ok(7));
+    }
+}
@@ -0,0 +1,50 @@
+/// Process-unique hex ids for conversations and upstream requests.
+///
+/// An id is the first 16 bytes of `sha256(time | pid | counter)` in lowercase hex: unique
+/// within a process thanks to the counter and, in practice, across replicas.
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use sha2::{Digest, Sha256};
+
+static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// A fresh 32-character lowercase hex id.
+pub fn new_unique_id() -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_else(|_| Duration::from_secs(0));
+    let counter = COUNTER.fetch_add(1, Ordering::Relaxed);
+    let pid = std::process::id();
+
+    let mut h = Sha256::new();
+    h.update(now.as_nanos().to_le_bytes());
+    h.update(pid.to_le_bytes());
+    h.update(counter.to_le_bytes());
+    let digest = h.finalize();
+    hex_lower(&digest[..16])
+}
+
+fn hex_lower(bytes: &[u8]) -> String {
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+    let mut out = String::with_capacity(bytes.len() * 2);
+    for &b in bytes {
+        out.push(HEX[(b >> 4) as usize] as char);
+        out.push(HEX[(b & 0x0f) as usize] as char);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ids_are_distinct_lowercase_hex() {
+        let (a, b) = (new_unique_id(), new_unique_id());
+        assert_ne!(a, b);
+        assert_eq!(a.len(), 32);
+        assert!(a.chars().all(|c| c.is_ascii_hexdigit() && !c.is_ascii_uppercase()));
+        assert_eq!(hex_lower(&[0x0f, 0xa0]), "0fa0");
+    }
+}
@@ -6,16 +6,71 @@
 /// - title: Utf8 (not null)
 /// - category: Utf8 (not null)
 /// - text: Utf8 (not null) — the text that was embedded
-/// - embedding: FixedSizeList<Float32, 768> (not null)
+/// - embedding: FixedSizeList<Float32, N> (not null), N being the embedder's dimension
 use std::sync::Arc;
 
-use arrow_array::{RecordBatch, RecordBatchIterator};
-use arrow_schema::Schema;
-use lancedb::query::{ExecutableQuery, QueryBase};
+use arrow_array::{
+    Array, FixedSizeListArray, Float32Array, RecordBatch, RecordBatchIterator, StringArray,
+};
+use arrow_schema::{DataType, Schema};
+use lancedb::index::Index;
+use lancedb::index::vector::IvfPqIndexBuilder;
+use lancedb::query::{ExecutableQuery, QueryBase, Select};
 use tracing::info;
 
 use crate::error::CommonError;
 
+/// Smallest table worth a vector index; PQ training needs 256 rows.
+pub const MIN_INDEX_ROWS: usize = 256;
+
+/// Candidates fetched per requested result on an indexed table and re-ranked on the full
+/// vectors, so `_distance` is exact rather than a PQ approximation.
+const REFINE_FACTOR: u32 = 10;
+
+/// Distance metric used for vector search.
+///
+/// LanceDB defaults to L2, but the guideline servers rank by cosine distance so that
+/// scores stay in a meaningful range regardless of embedding magnitude.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DistanceType {
+    /// Cosine distance, range [0, 2]. Scores map to cosine similarity clamped to [0, 1].
+    #[default]
+    Cosine,
+    /// Squared Euclidean distance, range [0, ∞). Scores map to `1 / (1 + distance)`.
+    L2,
+    /// Dot product distance (`1 - dot`). Scores map to the dot product clamped to [0, 1].
+    Dot,
+}
+
+impl DistanceType {
+    /// Convert a LanceDB `_distance` value into a similarity score where higher is better.
+    pub fn score(self, distance: f32) -> f32 {
+        match self {
+            DistanceType::Cosine | DistanceType::Dot => (1.0 - distance).clamp(0.0, 1.0),
+            DistanceType::L2 => 1.0 / (1.0 + distance.max(0.0)),
+        }
+    }
+
+    /// Stable lowercase name, suitable for cache keys and logging.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DistanceType::Cosine => "cosine",
+            DistanceType::L2 => "l2",
+            DistanceType::Dot => "dot",
+        }
+    }
+}
+
+impl From<DistanceType> for lancedb::DistanceType {
+    fn from(value: DistanceType) -> Self {
+        match value {
+            DistanceType::Cosine => lancedb::DistanceType::Cosine,
+            DistanceType::L2 => lancedb::DistanceType::L2,
+            DistanceType::Dot => lancedb::DistanceType::Dot,
+        }
+    }
+}
+
 pub struct VectorDb {
     db: lancedb::Connection,
 }
@@ -54,15 +109,104 @@ impl VectorDb {
         Ok(())
     }
 
+    /// Build an IVF_PQ index on `column` so searches stop scanning every row.
+    ///
+    /// The index is trained for `distance_type`, which must match the metric searches use.
+    /// Tables with fewer than [`MIN_INDEX_ROWS`] rows are left unindexed (PQ training needs
+    /// at least that many, and a flat scan is fast at that size); returns whether an index
+    /// was built.
+    pub async fn create_index(
+        &self,
+        table_name: &str,
+        column: &str,
+        distance_type: DistanceType,
+    ) -> Result<bool, CommonError> {
+        let table = self
+            .db
+            .open_table(table_name)
+            .execute()
+            .await
+            .map_err(|e| CommonError::VectorDb(format!("open table failed: {e}")))?;
+
+        let rows = table
+            .count_rows(None)
+            .await
+            .map_err(|e| CommonError::VectorDb(format!("counting rows failed: {e}")))?;
+        if rows < MIN_INDEX_ROWS {
+            info!(table = table_name, rows, "table too small for a vector index, skipping");
+            return Ok(false);
+        }
+
+        let index = IvfPqIndexBuilder::default().distance_type(distance_type.into());
+        table
+            .create_index(&[column], Index::IvfPq(index))
+            .replace(true)
+            .execute()
+            .await
+            .map_err(|e| CommonError::VectorDb(format!("create index failed: {e}")))?;
+
+        info!(table = table_name, column, rows, "vector index created");
+        Ok(true)
+    }
+
+    /// Delete every row whose `id` equals `id`. Deleting a missing id is not an error.
+    pub async fn delete_by_id(&self, table_name: &str, id: &str) -> Result<(), CommonError> {
+        let table = self
+            .db
+            .open_table(table_name)
+            .execute()
+            .await
+            .map_err(|e| CommonError::VectorDb(format!("open table failed: {e}")))?;
+
+        table
+            .delete(&format!("id = {}", sql_string(id)))
+            .await
+            .map_err(|e| CommonError::VectorDb(format!("delete by id failed: {e}")))?;
+        Ok(())
+    }
+
+    /// Insert or update rows keyed on the `id` column.
+    ///
+    /// Rows whose id already exists are replaced in full; new ids are appended. The
+    /// batches must match the table's schema.
+    pub async fn upsert_batch(
+        &self,
+        table_name: &str,
+        schema: Arc<Schema>,
+        batches: Vec<RecordBatch>,
+    ) -> Result<(), CommonError> {
+        let table = self
+            .db
+            .open_table(table_name)
+            .execute()
+            .await
+            .map_err(|e| CommonError::VectorDb(format!("open table failed: {e}")))?;
+
+        let rows: usize = batches.iter().map(RecordBatch::num_rows).sum();
+        let batch_iter = RecordBatchIterator::new(batches.into_iter().map(Ok), schema);
+        let mut merge = table.merge_insert(&["id"]);
+        merge.when_matched_update_all(None).when_not_matched_insert_all();
+        merge
+            .execute(Box::new(batch_iter))
+            .await
+            .map_err(|e| CommonError::VectorDb(format!("upsert failed: {e}")))?;
+
+        info!(table = table_name, rows, "rows upserted");
+        Ok(())
+    }
+
     /// Search for the nearest vectors to the given query embedding.
     ///
     /// Returns up to `limit` results as RecordBatches, including a `_distance` column
-    /// added by LanceDB.
+    /// added by LanceDB and computed with `distance_type` on the full vectors (indexed
+    /// tables are refined by [`REFINE_FACTOR`]), so scores and `min_score` cut-offs do not
+    /// depend on the PQ approximation.
     pub async fn search(
         &self,
         table_name: &str,
         query_embedding: &[f32],
         limit: usize,
+        distance_type: DistanceType,
     ) -> Result<Vec<RecordBatch>, CommonError> {
         let table = self
             .db
@@ -71,9 +215,25 @@ impl VectorDb {
             .await
             .map_err(|e| CommonError::VectorDb(format!("open table failed: {e}")))?;
 
+        // Catch a model change before LanceDB fails with an opaque schema error.
+        let schema = table
+            .schema()
+            .await
+            .map_err(|e| CommonError::VectorDb(format!("reading table schema failed: {e}")))?;
+        let stored_dim = embedding_dim(&schema)?;
+        if query_embedding.len() != stored_dim {
+            return Err(CommonError::VectorDb(format!(
+                "embedding dimension mismatch: query has {} dimensions but table '{table_name}' \
+                 stores {stored_dim}; re-index with the current embedding model",
+                query_embedding.len()
+            )));
+        }
+
         let results = table
             .vector_search(query_embedding)
             .map_err(|e| CommonError::VectorDb(format!("vector search setup failed: {e}")))?
+            .distance_type(distance_type.into())
+            .refine_factor(REFINE_FACTOR)
             .limit(limit)
             .execute()
             .await
@@ -84,6 +244,50 @@ impl VectorDb {
             .map_err(|e| CommonError::VectorDb(format!("collecting search results failed: {e}")))
     }
 
+    /// Find rows where any of `columns` contains `query`, ignoring case.
+    ///
+    /// This is a plain `LIKE` scan, not full-text search: the whole query must occur as a
+    /// substring. Returns up to `limit` rows, without a `_distance` column.
+    pub async fn keyword_search(
+        &self,
+        table_name: &str,
+        query: &str,
+        columns: &[&str],
+        limit: usize,
+    ) -> Result<Vec<RecordBatch>, CommonError> {
+        let table = self
+            .db
+            .open_table(table_name)
+            .execute()
+            .await
+            .map_err(|e| CommonError::VectorDb(format!("open table failed: {e}")))?;
+
+        // Escape LIKE wildcards so the query matches literally.
+        let escaped = query
+            .to_lowercase()
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_");
+        let pattern = sql_string(&format!("%{escaped}%"));
+        let filter = columns
+            .iter()
+            .map(|column| format!("lower({column}) LIKE {pattern}"))
+            .collect::<Vec<_>>()
+            .join(" OR ");
+
+        let results = table
+            .query()
+            .only_if(filter)
+            .limit(limit)
+            .execute()
+            .await
+            .map_err(|e| CommonError::VectorDb(format!("keyword search failed: {e}")))?;
+
+        futures::TryStreamExt::try_collect(results)
+            .await
+            .map_err(|e| CommonError::VectorDb(format!("collecting search results failed: {e}")))
+    }
+
     /// Look up a single row by its `id` column value.
     ///
     /// Returns `None` if the id is not found. Returns the first match if multiple exist.
@@ -101,7 +305,7 @@ impl VectorDb {
 
         // Use a SQL filter to find the row by id.
         // LanceDB uses DataFusion SQL syntax for filters.
-        let filter = format!("id = '{}'", id.replace('\'', "''"));
+        let filter = format!("id = {}", sql_string(id));
         let results = table
             .query()
             .only_if(filter)
@@ -116,4 +320,469 @@ impl VectorDb {
 
         Ok(batches.into_iter().next().filter(|b| b.num_rows() > 0))
     }
+
+    /// Look up every row whose `id` is in `ids` with a single `id IN (...)` query.
+    ///
+    /// Ids with no row are simply absent from the result; an empty `ids` returns no batches
+    /// without touching the table.
+    pub async fn get_by_ids(
+        &self,
+        table_name: &str,
+        ids: &[String],
+    ) -> Result<Vec<RecordBatch>, CommonError> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let table = self
+            .db
+            .open_table(table_name)
+            .execute()
+            .await
+            .map_err(|e| CommonError::VectorDb(format!("open table failed: {e}")))?;
+
+        let list: Vec<String> = ids.iter().map(|id| sql_string(id)).collect();
+        let filter = format!("id IN ({})", list.join(", "));
+        let results = table
+            .query()
+            .only_if(filter)
+            .execute()
+            .await
+            .map_err(|e| CommonError::VectorDb(format!("query by ids failed: {e}")))?;
+
+        let batches: Vec<RecordBatch> = futures::TryStreamExt::try_collect(results)
+            .await
+            .map_err(|e| CommonError::VectorDb(format!("collecting query results failed: {e}")))?;
+
+        Ok(batches.into_iter().filter(|b| b.num_rows() > 0).collect())
+    }
+
+    /// Return the stored `embedding` vector of the row with the given `id`, if any.
+    pub async fn get_embedding(
+        &self,
+        table_name: &str,
+        id: &str,
+    ) -> Result<Option<Vec<f32>>, CommonError> {
+        let Some(batch) = self.get_by_id(table_name, id).await? else {
+            return Ok(None);
+        };
+        let list = batch
+            .column_by_name("embedding")
+            .and_then(|c| c.as_any().downcast_ref::<FixedSizeListArray>())
+            .ok_or_else(|| {
+                CommonError::VectorDb("embedding column missing or not a fixed-size list".into())
+            })?;
+        let values = list.value(0);
+        let values = values
+            .as_any()
+            .downcast_ref::<Float32Array>()
+            .ok_or_else(|| CommonError::VectorDb("embedding values are not Float32".into()))?;
+        Ok(Some(values.values().to_vec()))
+    }
+
+    /// Number of rows stored in the table. Errors if the table does not exist.
+    pub async fn count_rows(&self, table_name: &str) -> Result<usize, CommonError> {
+        let table = self
+            .db
+            .open_table(table_name)
+            .execute()
+            .await
+            .map_err(|e| CommonError::VectorDb(format!("open table failed: {e}")))?;
+        table
+            .count_rows(None)
+            .await
+            .map_err(|e| CommonError::VectorDb(format!("counting rows failed: {e}")))
+    }
+
+    /// Dimension of the `embedding` column stored in the table.
+    pub async fn table_embedding_dim(&self, table_name: &str) -> Result<usize, CommonError> {
+        let table = self
+            .db
+            .open_table(table_name)
+            .execute()
+            .await
+            .map_err(|e| CommonError::VectorDb(format!("open table failed: {e}")))?;
+        let schema = table
+            .schema()
+            .await
+            .map_err(|e| CommonError::VectorDb(format!("reading table schema failed: {e}")))?;
+        embedding_dim(&schema)
+    }
+
+    /// Value stored under `key` in the table's schema metadata, if any.
+    pub async fn table_metadata(
+        &self,
+        table_name: &str,
+        key: &str,
+    ) -> Result<Option<String>, CommonError> {
+        let table = self
+            .db
+            .open_table(table_name)
+            .execute()
+            .await
+            .map_err(|e| CommonError::VectorDb(format!("open table failed: {e}")))?;
+        let schema = table
+            .schema()
+            .await
+            .map_err(|e| CommonError::VectorDb(format!("reading table schema failed: {e}")))?;
+        Ok(schema.metadata().get(key).cloned())
+    }
+
+    /// Return the `id` of every row in the table, in storage order.
+    pub async fn list_ids(&self, table_name: &str) -> Result<Vec<String>, CommonError> {
+        let table = self
+            .db
+            .open_table(table_name)
+            .execute()
+            .await
+            .map_err(|e| CommonError::VectorDb(format!("open table failed: {e}")))?;
+
+        let results = table
+            .query()
+            .select(Select::columns(&["id"]))
+            .execute()
+            .await
+            .map_err(|e| CommonError::VectorDb(format!("listing ids failed: {e}")))?;
+
+        let batches: Vec<RecordBatch> = futures::TryStreamExt::try_collect(results)
+            .await
+            .map_err(|e| CommonError::VectorDb(format!("collecting ids failed: {e}")))?;
+
+        let mut ids = Vec::new();
+        for batch in &batches {
+            let col = batch
+                .column_by_name("id")
+                .and_then(|c| c.as_any().downcast_ref::<StringArray>())
+                .ok_or_else(|| {
+                    CommonError::VectorDb("id column missing or not Utf8".to_string())
+                })?;
+            ids.extend(
+                (0..col.len())
+                    .filter(|&i| col.is_valid(i))
+                    .map(|i| col.value(i).to_string()),
+            );
+        }
+        Ok(ids)
+    }
+}
+
+/// Quote a value as a DataFusion SQL string literal for LanceDB filters.
+fn sql_string(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// Size of the `embedding` field's `FixedSizeList`.
+fn embedding_dim(schema: &Schema) -> Result<usize, CommonError> {
+    match schema.field_with_name("embedding").map(|f| f.data_type()) {
+        Ok(DataType::FixedSizeList(_, size)) => Ok(*size as usize),
+        _ => Err(CommonError::VectorDb(
+            "embedding field missing or not a fixed-size list".into(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashMap;
+
+    use arrow_array::{ArrayRef, FixedSizeListArray, Float32Array};
+    use arrow_schema::Field;
+
+    fn temp_db_path(name: &str) -> String {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or(0);
+        std::env::temp_dir()
+            .join(format!("vectordb-{name}-{}-{nanos}", std::process::id()))
+            .to_string_lossy()
+            .to_string()
+    }
+
+    fn batch_of(rows: &[(&str, [f32; 3])]) -> RecordBatch {
+        let ids: ArrayRef = Arc::new(StringArray::from(
+            rows.iter().map(|(id, _)| *id).collect::<Vec<_>>(),
+        ));
+        let values = Float32Array::from(
+            rows.iter().flat_map(|(_, v)| v.iter().copied()).collect::<Vec<_>>(),
+        );
+        let item = Arc::new(Field::new("item", DataType::Float32, true));
+        let embeddings: ArrayRef = Arc::new(
+            FixedSizeListArray::try_new(Arc::clone(&item), 3, Arc::new(values), None).unwrap(),
+        );
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Utf8, false),
+            Field::new("embedding", DataType::FixedSizeList(item, 3), false),
+        ]));
+        RecordBatch::try_new(schema, vec![ids, embeddings]).unwrap()
+    }
+
+    fn ranked_ids(batches: &[RecordBatch]) -> Vec<String> {
+        batches
+            .iter()
+            .flat_map(|b| {
+                let col = b
+                    .column(b.schema().index_of("id").unwrap())
+                    .as_any()
+                    .downcast_ref::<StringArray>()
+                    .unwrap()
+                    .clone();
+                (0..col.len()).map(move |i| col.value(i).to_string())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn scores_are_monotonic_in_distance() {
+        for metric in [DistanceType::Cosine, DistanceType::L2, DistanceType::Dot] {
+            let near = metric.score(0.1);
+            let far = metric.score(0.9);
+            assert!(near > far, "{metric:?}: expected {near} > {far}");
+            assert!((0.0..=1.0).contains(&near), "{metric:?}: score out of range");
+            assert!((0.0..=1.0).contains(&metric.score(5.0)), "{metric:?}: score out of range");
+        }
+        assert_eq!(DistanceType::Cosine.score(0.0), 1.0);
+        assert_eq!(DistanceType::Cosine.score(2.0), 0.0);
+        assert_eq!(DistanceType::L2.score(0.0), 1.0);
+    }
+
+    #[test]
+    fn default_metric_is_cosine() {
+        assert_eq!(DistanceType::default(), DistanceType::Cosine);
+    }
+
+    #[tokio::test]
+    async fn search_ranks_known_vectors_per_metric() {
+        let path = temp_db_path("ranking");
+        let db = VectorDb::connect(&path).await.unwrap();
+
+        // "long" points the same way as the query but has a large magnitude, so it is the
+        // nearest neighbour by cosine yet far away by L2.
+        let batch = batch_of(&[
+            ("long", [10.0, 0.0, 0.0]),
+            ("close", [0.9, 0.3, 0.0]),
+            ("orthogonal", [0.0, 1.0, 0.0]),
+        ]);
+        let schema = batch.schema();
+        db.create_or_replace_table("t", schema, vec![batch]).await.unwrap();
+
+        let query = [1.0, 0.0, 0.0];
+
+        let cosine = db.search("t", &query, 3, DistanceType::Cosine).await.unwrap();
+        assert_eq!(ranked_ids(&cosine), ["long", "close", "orthogonal"]);
+
+        let l2 = db.search("t", &query, 3, DistanceType::L2).await.unwrap();
+        assert_eq!(ranked_ids(&l2), ["close", "orthogonal", "long"]);
+
+        // Repeated searches return the same ordering.
+        let again = db.search("t", &query, 3, DistanceType::Cosine).await.unwrap();
+        assert_eq!(ranked_ids(&again), ranked_ids(&cosine));
+
+        let _ = std::fs::remove_dir_all(&path);
+    }
+
+    #[tokio::test]
+    async fn detects_embedding_dimension_mismatch() {
+        let path = temp_db_path("dims");
+        let db = VectorDb::connect(&path).await.unwrap();
+        let batch = batch_of(&[("a", [1.0, 0.0, 0.0])]);
+        let schema = batch.schema();
+        db.create_or_replace_table("t", schema, vec![batch]).await.unwrap();
+
+        assert_eq!(db.table_embedding_dim("t").await.unwrap(), 3);
+        let err = db.search("t", &[1.0, 0.0], 1, DistanceType::Cosine).await.unwrap_err();
+        assert!(err.to_string().contains("query has 2 dimensions"), "{err}");
+        assert!(err.to_string().contains("stores 3"), "{err}");
+
+        let _ = std::fs::remove_dir_all(&path);
+    }
+
+    #[tokio::test]
+    async fn create_index_skips_small_tables_and_keeps_search_working() {
+        let path = temp_db_path("index");
+        let db = VectorDb::connect(&path).await.unwrap();
+
+        let small = batch_of(&[("a", [1.0, 0.0, 0.0])]);
+        let schema = small.schema();
+        db.create_or_replace_table("small", schema, vec![small]).await.unwrap();
+        assert!(!db.create_index("small", "embedding", DistanceType::Cosine).await.unwrap());
+
+        // Enough rows of a 16-dim embedding for PQ training.
+        const DIM: i32 = 16;
+        let n = MIN_INDEX_ROWS + 44;
+        let ids: ArrayRef = Arc::new(StringArray::from(
+            (0..n).map(|i| format!("r{i}")).collect::<Vec<_>>(),
+        ));
+        let values = Float32Array::from(
+            (0..n * DIM as usize).map(|i| ((i * 7919) % 101) as f32 / 101.0).collect::<Vec<_>>(),
+        );
+        let item = Arc::new(Field::new("item", DataType::Float32, true));
+        let embeddings: ArrayRef = Arc::new(
+            FixedSizeListArray::try_new(Arc::clone(&item), DIM, Arc::new(values), None).unwrap(),
+        );
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Utf8, false),
+            Field::new("embedding", DataType::FixedSizeList(item, DIM), false),
+        ]));
+        let batch = RecordBatch::try_new(Arc::clone(&schema), vec![ids, embeddings]).unwrap();
+        db.create_or_replace_table("large", schema, vec![batch]).await.unwrap();
+        assert!(db.create_index("large", "embedding", DistanceType::Cosine).await.unwrap());
+
+        let query = [0.5f32; DIM as usize];
+        let found = db.search("large", &query, 5, DistanceType::Cosine).await.unwrap();
+        assert_eq!(ranked_ids(&found).len(), 5);
+
+        let _ = std::fs::remove_dir_all(&path);
+    }
+
+    #[tokio::test]
+    async fn table_metadata_is_kept_across_upserts() {
+        let path = temp_db_path("metadata");
+        let db = VectorDb::connect(&path).await.unwrap();
+        let with_settings = |batch: RecordBatch| {
+            let metadata = HashMap::from([("settings".to_string(), "model-a".to_string())]);
+            let schema = Arc::new(batch.schema().as_ref().clone().with_metadata(metadata));
+            batch.with_schema(schema).unwrap()
+        };
+        let batch = with_settings(batch_of(&[("a", [1.0, 0.0, 0.0])]));
+        let schema = batch.schema();
+        db.create_or_replace_table("t", Arc::clone(&schema), vec![batch]).await.unwrap();
+        assert_eq!(db.table_metadata("t", "settings").await.unwrap().as_deref(), Some("model-a"));
+        assert_eq!(db.table_metadata("t", "other").await.unwrap(), None);
+
+        let update = with_settings(batch_of(&[("b", [0.0, 1.0, 0.0])]));
+        db.upsert_batch("t", schema, vec![update]).await.unwrap();
+        assert_eq!(db.table_metadata("t", "settings").await.unwrap().as_deref(), Some("model-a"));
+        assert!(db.table_metadata("missing", "settings").await.is_err());
+
+        let _ = std::fs::remove_dir_all(&path);
+    }
+
+    #[tokio::test]
+    async fn count_rows_reports_table_size() {
+        let path = temp_db_path("count");
+        let db = VectorDb::connect(&path).await.unwrap();
+        let batch = batch_of(&[("a", [1.0, 0.0, 0.0]), ("b", [0.0, 1.0, 0.0])]);
+        let schema = batch.schema();
+        db.create_or_replace_table("t", schema, vec![batch]).await.unwrap();
+
+        assert_eq!(db.count_rows("t").await.unwrap(), 2);
+        let err = db.count_rows("missing").await.unwrap_err();
+        assert!(matches!(err, CommonError::VectorDb(_)), "{err}");
+
+        let _ = std::fs::remove_dir_all(&path);
+    }
+
+    #[tokio::test]
+    async fn list_ids_returns_every_row() {
+        let path = temp_db_path("list-ids");
+        let db = VectorDb::connect(&path).await.unwrap();
+        let batch = batch_of(&[("a", [1.0, 0.0, 0.0]), ("b", [0.0, 1.0, 0.0])]);
+        let schema = batch.schema();
+        db.create_or_replace_table("t", schema, vec![batch]).await.unwrap();
+
+        let mut ids = db.list_ids("t").await.unwrap();
+        ids.sort();
+        assert_eq!(ids, ["a", "b"]);
+        assert!(db.list_ids("missing").await.is_err());
+
+        let _ = std::fs::remove_dir_all(&path);
+    }
+
+    #[tokio::test]
+    async fn upsert_replaces_existing_ids_without_duplicates() {
+        let path = temp_db_path("upsert");
+        let db = VectorDb::connect(&path).await.unwrap();
+        let batch = batch_of(&[("a", [1.0, 0.0, 0.0]), ("b", [0.0, 1.0, 0.0])]);
+        let schema = batch.schema();
+        db.create_or_replace_table("t", Arc::clone(&schema), vec![batch]).await.unwrap();
+
+        let update = batch_of(&[("a", [0.0, 0.0, 1.0]), ("c", [1.0, 1.0, 0.0])]);
+        db.upsert_batch("t", Arc::clone(&schema), vec![update]).await.unwrap();
+        // Upserting the same rows again must not duplicate them.
+        let again = batch_of(&[("a", [0.0, 0.0, 1.0])]);
+        db.upsert_batch("t", schema, vec![again]).await.unwrap();
+
+        let mut ids = db.list_ids("t").await.unwrap();
+        ids.sort();
+        assert_eq!(ids, ["a", "b", "c"]);
+        assert_eq!(db.get_embedding("t", "a").await.unwrap(), Some(vec![0.0, 0.0, 1.0]));
+
+        let _ = std::fs::remove_dir_all(&path);
+    }
+
+    #[tokio::test]
+    async fn delete_by_id_removes_only_that_row() {
+        let path = temp_db_path("delete");
+        let db = VectorDb::connect(&path).await.unwrap();
+        let batch = batch_of(&[("a", [1.0, 0.0, 0.0]), ("o'brien", [0.0, 1.0, 0.0])]);
+        let schema = batch.schema();
+        db.create_or_replace_table("t", schema, vec![batch]).await.unwrap();
+
+        db.delete_by_id("t", "o'brien").await.unwrap();
+        db.delete_by_id("t", "missing").await.unwrap();
+        assert_eq!(db.list_ids("t").await.unwrap(), ["a"]);
+
+        let _ = std::fs::remove_dir_all(&path);
+    }
+
+    #[tokio::test]
+    async fn get_by_ids_fetches_only_requested_rows() {
+        let path = temp_db_path("get-by-ids");
+        let db = VectorDb::connect(&path).await.unwrap();
+        let batch = batch_of(&[
+            ("a", [1.0, 0.0, 0.0]),
+            ("it's", [0.0, 1.0, 0.0]),
+            ("c", [0.0, 0.0, 1.0]),
+        ]);
+        let schema = batch.schema();
+        db.create_or_replace_table("t", schema, vec![batch]).await.unwrap();
+
+        let ids = ["it's", "a", "missing"].map(String::from);
+        let mut found = ranked_ids(&db.get_by_ids("t", &ids).await.unwrap());
+        found.sort();
+        assert_eq!(found, ["a", "it's"]);
+        assert!(db.get_by_ids("t", &[]).await.unwrap().is_empty());
+
+        let _ = std::fs::remove_dir_all(&path);
+    }
+
+    #[tokio::test]
+    async fn keyword_search_matches_substrings_case_insensitively() {
+        let path = temp_db_path("keyword");
+        let db = VectorDb::connect(&path).await.unwrap();
+        let batch = batch_of(&[
+            ("SL.con.1", [1.0, 0.0, 0.0]),
+            ("SL_other", [0.0, 1.0, 0.0]),
+            ("P.1", [0.0, 0.0, 1.0]),
+        ]);
+        let schema = batch.schema();
+        db.create_or_replace_table("t", schema, vec![batch]).await.unwrap();
+
+        let found = db.keyword_search("t", "sl.con", &["id"], 10).await.unwrap();
+        assert_eq!(ranked_ids(&found), ["SL.con.1"]);
+        // `_` is matched literally rather than as a LIKE wildcard.
+        let found = db.keyword_search("t", "l_", &["id"], 10).await.unwrap();
+        assert_eq!(ranked_ids(&found), ["SL_other"]);
+        let found = db.keyword_search("t", "noexcept", &["id"], 10).await.unwrap();
+        assert!(ranked_ids(&found).is_empty());
+
+        let _ = std::fs::remove_dir_all(&path);
+    }
+
+    #[tokio::test]
+    async fn get_embedding_returns_stored_vector() {
+        let path = temp_db_path("get-embedding");
+        let db = VectorDb::connect(&path).await.unwrap();
+        let batch = batch_of(&[("a", [1.0, 2.0, 3.0]), ("b", [0.0, 1.0, 0.0])]);
+        let schema = batch.schema();
+        db.create_or_replace_table("t", schema, vec![batch]).await.unwrap();
+
+        assert_eq!(db.get_embedding("t", "a").await.unwrap(), Some(vec![1.0, 2.0, 3.0]));
+        assert_eq!(db.get_embedding("t", "zzz").await.unwrap(), None);
+
+        let _ = std::fs::remove_dir_all(&path);
+    }
 }
@@ -1,6 +1,11 @@
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use crate::error::AppError;
+use mcp_common::tool_timeout::ToolTimeout;
+
+/// Default for `SEARCH_CACHE_CANONICAL_LIMIT`; matches the `search_guidelines` limit cap.
+const DEFAULT_CANONICAL_LIMIT: usize = 50;
 
 #[derive(Debug, Clone)]
 pub struct Config {
@@ -8,6 +13,11 @@ pub struct Config {
     pub lancedb_path: String,
     pub repo_path: String,
     pub readme_rel_path: String,
+    pub auto_pull: bool,
+    pub refresh_interval: Option<Duration>,
+    pub cache_warmup: bool,
+    pub search_cache_canonical_limit: usize,
+    pub tool_timeout: ToolTimeout,
 }
 
 impl Config {
@@ -18,6 +28,11 @@ impl Config {
     /// Optional:
     /// - `REDIS_URL`
     /// - `NODEJS_GUIDELINES_README` (default: "README.md")
+    /// - `GUIDELINES_AUTO_PULL` (`1` to pull upstream before checking for updates)
+    /// - `GUIDELINES_REFRESH_SECS` (check for updates in the background this often)
+    /// - `CACHE_WARMUP` (`1` to repopulate the guideline caches at startup)
+    /// - `SEARCH_CACHE_CANONICAL_LIMIT` (minimum rows each search fetches, default 50)
+    /// - `TOOL_TIMEOUT_SECS` (fail tool calls that run longer than this)
     pub fn from_env() -> Result<Self, AppError> {
         let lancedb_path = std::env::var("LANCEDB_PATH")
             .map_err(|_| AppError::Config("LANCEDB_PATH environment variable is required".to_string()))?;
@@ -47,11 +62,43 @@ impl Config {
             }
         }
 
+        let auto_pull = env_flag("GUIDELINES_AUTO_PULL")?;
+        let cache_warmup = env_flag("CACHE_WARMUP")?;
+
+        let refresh_interval = match std::env::var("GUIDELINES_REFRESH_SECS") {
+            Ok(value) => match value.trim().parse::<u64>() {
+                Ok(0) => None,
+                Ok(secs) => Some(Duration::from_secs(secs)),
+                Err(_) => {
+                    return Err(AppError::Config(format!(
+                        "GUIDELINES_REFRESH_SECS must be a whole number of seconds, got '{value}'"
+                    )));
+                }
+            },
+            Err(_) => None,
+        };
+
+        let search_cache_canonical_limit = match std::env::var("SEARCH_CACHE_CANONICAL_LIMIT") {
+            Ok(value) => value.trim().parse::<usize>().map_err(|_| {
+                AppError::Config(format!(
+                    "SEARCH_CACHE_CANONICAL_LIMIT must be a whole number, got '{value}'"
+                ))
+            })?,
+            Err(_) => DEFAULT_CANONICAL_LIMIT,
+        };
+
+        let tool_timeout = ToolTimeout::from_env().map_err(AppError::Config)?;
+
         Ok(Self {
             redis_url: std::env::var("REDIS_URL").ok(),
             lancedb_path,
             repo_path: resolved_repo_path,
             readme_rel_path,
+            auto_pull,
+            refresh_interval,
+            cache_warmup,
+            search_cache_canonical_limit,
+            tool_timeout,
         })
     }
 
@@ -63,3 +110,12 @@ impl Config {
         Path::new(&self.repo_path).join(&self.readme_rel_path)
     }
 }
+
+/// Read a `1`/`0` (or `true`/`false`) flag; unset or empty means off.
+fn env_flag(name: &str) -> Result<bool, AppError> {
+    match std::env::var(name).as_deref().map(str::trim) {
+        Ok("1" | "true") => Ok(true),
+        Ok("" | "0" | "false") | Err(_) => Ok(false),
+        Ok(value) => Err(AppError::Config(format!("{name} must be '1' or '0', got '{value}'"))),
+    }
+}
@@ -8,6 +8,15 @@ pub struct Guideline {
     pub category: String,
     pub source_file: String,
     pub raw_markdown: String,
+    /// One-line summary from the `**TL;DR:**` lead-in, or the first paragraph without one.
+    #[serde(default)]
+    pub tldr: Option<String>,
+    /// Consequences of ignoring the practice, from the `**Otherwise:**` lead-in.
+    #[serde(default)]
+    pub otherwise: Option<String>,
+    /// Targets of the practice's "Read More" links, as written in the source.
+    #[serde(default)]
+    pub read_more_links: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
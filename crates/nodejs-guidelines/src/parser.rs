@@ -31,6 +31,10 @@ pub fn parse_guidelines(
         Regex::new(r#"^#\s+`?(\d+)\.\s+(.+?)`?\s*$"#).expect("valid regex");
     let guideline_re =
         Regex::new(r#"^##\s+!\[✔\]\s+(\d+(?:\.\d+)+)\s+(.+?)\s*$"#).expect("valid regex");
+    let tldr_re = Regex::new(r#"^\*{0,2}TL;DR\*{0,2}:\*{0,2}\s*(.*)$"#).expect("valid regex");
+    let otherwise_re =
+        Regex::new(r#"^\*{0,2}Otherwise\*{0,2}:\*{0,2}\s*(.*)$"#).expect("valid regex");
+    let link_re = Regex::new(r#"(!?)\[([^\]]*)\]\(([^)\s]+)\)"#).expect("valid regex");
 
     let mut guidelines = Vec::new();
     let mut categories: HashMap<String, Category> = HashMap::new();
@@ -45,7 +49,7 @@ pub fn parse_guidelines(
 
         if let Some(caps) = category_re.captures(line) {
             let key = caps[1].to_string();
-            let name = caps[2].trim().to_string();
+            let name = normalize_whitespace(&caps[2]);
             current_category_key = Some(key.clone());
             current_category_name = Some(name.clone());
             categories.entry(key.clone()).or_insert(Category {
@@ -59,7 +63,7 @@ pub fn parse_guidelines(
 
         if let Some(caps) = guideline_re.captures(line) {
             let id = caps[1].trim().to_string();
-            let title = caps[2].trim().to_string();
+            let title = normalize_whitespace(&caps[2]);
 
             let category = current_category_key
                 .clone()
@@ -91,6 +95,17 @@ pub fn parse_guidelines(
 
             let raw_markdown = lines[start..end].join("\n").trim().to_string();
             let anchor = guideline_anchor(&id, &title);
+            let body = &lines[start + 1..end];
+            let paragraphs = split_paragraphs(body);
+            let tldr = marked_paragraph(&paragraphs, &tldr_re)
+                .or_else(|| {
+                    paragraphs
+                        .first()
+                        .map(|p| normalize_whitespace(&p.join(" ")))
+                })
+                .filter(|t| !t.is_empty());
+            let otherwise = marked_paragraph(&paragraphs, &otherwise_re);
+            let read_more_links = read_more_links(body, &link_re);
 
             guidelines.push(Guideline {
                 id,
@@ -99,6 +114,9 @@ pub fn parse_guidelines(
                 category: category.clone(),
                 source_file: source_file.to_string(),
                 raw_markdown,
+                tldr,
+                otherwise,
+                read_more_links,
             });
 
             if let Some(cat) = categories.get_mut(&category) {
@@ -116,11 +134,18 @@ pub fn parse_guidelines(
     (guidelines, categories)
 }
 
+/// Text embedded for a guideline. The TL;DR goes right after the title so it is never cut
+/// off by the length cap and, repeated from the body, carries extra weight.
 pub fn compose_embedding_text(guideline: &Guideline) -> String {
-    let text = format!(
-        "{}: {}. Category: {}. {}",
-        guideline.id, guideline.title, guideline.category, guideline.raw_markdown
+    let mut text = format!(
+        "{}: {}. Category: {}. ",
+        guideline.id, guideline.title, guideline.category
     );
+    if let Some(tldr) = &guideline.tldr {
+        text.push_str(tldr);
+        text.push(' ');
+    }
+    text.push_str(&guideline.raw_markdown);
     if text.chars().count() > 3000 {
         text.chars().take(3000).collect()
     } else {
@@ -128,6 +153,44 @@ pub fn compose_embedding_text(guideline: &Guideline) -> String {
     }
 }
 
+/// Blank-line separated paragraphs of a guideline's body.
+fn split_paragraphs<'a>(body: &'a [&'a str]) -> Vec<&'a [&'a str]> {
+    body.split(|line| line.trim().is_empty())
+        .filter(|p| !p.is_empty())
+        .collect()
+}
+
+/// The first paragraph led by `marker` (such as `**TL;DR:**`), without the marker and with
+/// whitespace collapsed.
+fn marked_paragraph(paragraphs: &[&[&str]], marker: &Regex) -> Option<String> {
+    paragraphs.iter().find_map(|p| {
+        let caps = marker.captures(p[0].trim())?;
+        let text = normalize_whitespace(&format!("{} {}", &caps[1], p[1..].join(" ")));
+        (!text.is_empty()).then_some(text)
+    })
+}
+
+/// Targets of the markdown links whose text starts with "Read More", in order and without
+/// duplicates. Image links are skipped.
+fn read_more_links(body: &[&str], link_re: &Regex) -> Vec<String> {
+    let mut links: Vec<String> = Vec::new();
+    for caps in body.iter().flat_map(|line| link_re.captures_iter(line)) {
+        let text = caps[2].trim_matches(|c: char| c == '*' || c == '_' || c.is_whitespace());
+        let is_read_more = text
+            .get(..9)
+            .is_some_and(|lead| lead.eq_ignore_ascii_case("read more"));
+        if caps[1].is_empty() && is_read_more && !links.iter().any(|l| l == &caps[3]) {
+            links.push(caps[3].to_string());
+        }
+    }
+    links
+}
+
+/// Trim and collapse runs of whitespace (tabs, repeated spaces) into single spaces.
+fn normalize_whitespace(s: &str) -> String {
+    s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
 fn guideline_anchor(id: &str, title: &str) -> String {
     let id_flat: String = id.chars().filter(|c| c.is_ascii_digit()).collect();
     format!("-{}-{}", id_flat, slugify(title))
@@ -174,6 +237,97 @@ More text.
         assert_eq!(guidelines[0].anchor, "-11-structure-your-solution-by-business-components");
     }
 
+    #[test]
+    fn parse_normalizes_header_whitespace() {
+        let content = "#\t`1.  Project   Architecture Practices`  \n\n\
+##\t![✔]\t1.1\tStructure  your solution\tby business components \t\n\nTL;DR text.\n";
+
+        let (guidelines, categories) = parse_guidelines(content, "README.md");
+        assert_eq!(guidelines.len(), 1);
+        assert_eq!(guidelines[0].id, "1.1");
+        assert_eq!(
+            guidelines[0].title,
+            "Structure your solution by business components"
+        );
+        assert_eq!(categories["1"].display_name, "Project Architecture Practices");
+    }
+
+    #[test]
+    fn parse_extracts_tldr_or_falls_back_to_first_paragraph() {
+        let content = r#"# `2. Error Handling Practices`
+
+## ![✔] 2.1 Use Async-Await or promises for async error handling
+
+**TL;DR:** Handling async errors in callback style is probably the fastest way to hell.
+Use a reputable promise library or async-await instead.
+
+**Otherwise:** Node.js callback style is a recipe for nesting.
+
+## ![✔] 2.2 Extend the built-in Error object
+
+Many throw errors as a string or as some custom type.
+
+**Otherwise:** When invoking some component, being uncertain which type of errors come back.
+
+## ![✔] 2.3 Distinguish operational vs programmer errors
+"#;
+
+        let (guidelines, _) = parse_guidelines(content, "README.md");
+        assert_eq!(
+            guidelines[0].tldr.as_deref(),
+            Some(
+                "Handling async errors in callback style is probably the fastest way to hell. \
+                 Use a reputable promise library or async-await instead."
+            )
+        );
+        assert_eq!(
+            guidelines[1].tldr.as_deref(),
+            Some("Many throw errors as a string or as some custom type.")
+        );
+        assert_eq!(guidelines[2].tldr, None);
+        assert!(compose_embedding_text(&guidelines[0]).starts_with(
+            "2.1: Use Async-Await or promises for async error handling. Category: 2. Handling"
+        ));
+    }
+
+    #[test]
+    fn parse_extracts_otherwise_and_read_more_links() {
+        let content = r#"# `1. Project Architecture Practices`
+
+## ![✔] 1.1 Structure your solution by business components
+
+**TL;DR:** Organize the code by components, see [this post](https://example.com/post).
+
+**Otherwise:** Developers who code new features struggle to realize the impact
+of their change.
+
+🔗 [**Read More: structure by components**](./sections/projectstructre/breakintcomponents.md)
+
+🔗 [**Read more: layering**](./sections/projectstructre/createlayers.md) ![badge](./assets/badge.png)
+
+## ![✔] 1.2 Layer your components
+
+Keep Express within its boundaries.
+"#;
+
+        let (guidelines, _) = parse_guidelines(content, "README.md");
+        assert_eq!(
+            guidelines[0].otherwise.as_deref(),
+            Some(
+                "Developers who code new features struggle to realize the impact of their change."
+            )
+        );
+        assert_eq!(
+            guidelines[0].read_more_links,
+            [
+                "./sections/projectstructre/breakintcomponents.md",
+                "./sections/projectstructre/createlayers.md"
+            ]
+        );
+        assert_eq!(guidelines[1].otherwise, None);
+        assert!(guidelines[1].read_more_links.is_empty());
+    }
+
     #[test]
     fn parse_real_repo() {
         let path = std::env::var("NODEJS_GUIDELINES_REPO_PATH")
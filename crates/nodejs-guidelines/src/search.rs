@@ -1,54 +1,290 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use arrow_array::{Array, Float32Array, RecordBatch, StringArray};
 use tracing::{info, warn};
 
-use crate::cache::GuidelineCache;
+use crate::cache::{CachedSearch, GuidelineCache};
 use crate::model::GuidelineResult;
+use mcp_common::batch;
 use mcp_common::embedding::Embedder;
-use mcp_common::vectordb::VectorDb;
+use mcp_common::error::CommonError;
+use mcp_common::hybrid;
+use mcp_common::mcp_api::SearchMode;
+use mcp_common::summary;
+use mcp_common::vectordb::{DistanceType, VectorDb};
 
 const VECTOR_TABLE_NAME: &str = "nodejs_guidelines";
-const MAX_SUMMARY_LEN: usize = 300;
+const DISTANCE_TYPE: DistanceType = DistanceType::Cosine;
+/// Columns a keyword search matches the query against.
+const KEYWORD_COLUMNS: [&str; 3] = ["id", "title", "text"];
+/// Upper bound on rows one keyword scan reads; far above any corpus size, so ranking sees
+/// every match.
+const MAX_KEYWORD_ROWS: usize = 2000;
 
 pub struct SearchEngine {
     embedder: Arc<Embedder>,
     vectordb: Arc<VectorDb>,
     cache: Arc<GuidelineCache>,
+    /// Searches fetch at least this many rows so one cached entry per query serves every
+    /// smaller limit (`SEARCH_CACHE_CANONICAL_LIMIT`); 0 fetches exactly the requested limit.
+    canonical_limit: usize,
+    /// Characters of text kept in each result's summary (`SEARCH_SUMMARY_LEN`).
+    summary_len: usize,
 }
 
 impl SearchEngine {
-    pub fn new(embedder: Arc<Embedder>, vectordb: Arc<VectorDb>, cache: Arc<GuidelineCache>) -> Self {
+    pub fn new(
+        embedder: Arc<Embedder>,
+        vectordb: Arc<VectorDb>,
+        cache: Arc<GuidelineCache>,
+        canonical_limit: usize,
+    ) -> Self {
+        let summary_len = summary::summary_len_from_env();
+
         Self {
             embedder,
             vectordb,
             cache,
+            canonical_limit,
+            summary_len,
+        }
+    }
+
+    /// Search for the query ranked according to `mode`; see [`mcp_common::hybrid`] for how
+    /// keyword and hybrid scores are computed. Only semantic rankings are cached.
+    pub async fn search(
+        &self,
+        query: &str,
+        limit: usize,
+        min_score: Option<f32>,
+        mode: SearchMode,
+    ) -> Result<Vec<GuidelineResult>, crate::error::AppError> {
+        match mode {
+            SearchMode::Semantic => self.semantic_search(query, limit, min_score).await,
+            SearchMode::Keyword => self.keyword_search(query, limit, min_score).await,
+            SearchMode::Hybrid => self.hybrid_search(query, limit, min_score).await,
         }
     }
 
-    pub async fn search(&self, query: &str, limit: usize) -> Result<Vec<GuidelineResult>, crate::error::AppError> {
-        if let Some(cached) = self.cache.get_search_results(query, limit).await {
-            info!(query, "search cache hit");
-            return Ok(cached);
+    async fn semantic_search(
+        &self,
+        query: &str,
+        limit: usize,
+        min_score: Option<f32>,
+    ) -> Result<Vec<GuidelineResult>, crate::error::AppError> {
+        if let Some(results) = self.cached_results(query, limit, min_score).await {
+            return Ok(results);
         }
 
         let query_embedding = self.embedder.embed_query(query).await?;
+        self.search_embedding(query, &query_embedding, limit, min_score).await
+    }
+
+    /// Rank rows containing the query by keyword score alone. Needs no embedding.
+    async fn keyword_search(
+        &self,
+        query: &str,
+        limit: usize,
+        min_score: Option<f32>,
+    ) -> Result<Vec<GuidelineResult>, crate::error::AppError> {
         let batches = self
             .vectordb
-            .search(VECTOR_TABLE_NAME, &query_embedding, limit)
+            .keyword_search(VECTOR_TABLE_NAME, query, &KEYWORD_COLUMNS, MAX_KEYWORD_ROWS)
             .await?;
-
-        let results = extract_search_results(&batches);
-        self.cache.set_search_results(query, limit, &results).await;
+        let mut results =
+            extract_results(&batches, min_score, self.summary_len, |id, title, text, _| {
+                hybrid::keyword_score(query, id, title, text)
+            });
+        results.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.id.cmp(&b.id)));
+        results.truncate(limit);
         Ok(results)
     }
 
+    /// Merge the semantic and keyword rankings into one, de-duplicated by id.
+    async fn hybrid_search(
+        &self,
+        query: &str,
+        limit: usize,
+        min_score: Option<f32>,
+    ) -> Result<Vec<GuidelineResult>, crate::error::AppError> {
+        // Rank over the full canonical fetch; `min_score` applies to the blended score.
+        let fetch_limit = limit.max(self.canonical_limit);
+        let (semantic, keyword) = futures::try_join!(
+            self.semantic_search(query, fetch_limit, None),
+            self.keyword_search(query, fetch_limit, None),
+        )?;
+
+        let ranking = hybrid::merge_rankings(&id_scores(&semantic), &id_scores(&keyword));
+        let mut by_id: HashMap<String, GuidelineResult> = semantic
+            .into_iter()
+            .chain(keyword)
+            .map(|r| (r.id.clone(), r))
+            .collect();
+        Ok(ranking
+            .into_iter()
+            .filter(|(_, score)| !min_score.is_some_and(|min| *score < min))
+            .filter_map(|(id, score)| by_id.remove(&id).map(|r| GuidelineResult { score, ..r }))
+            .take(limit)
+            .collect())
+    }
+
+    /// Search several queries at once, returning one result list per query in input order.
+    /// Uncached queries are embedded in one model call and searched concurrently, each
+    /// distinct query once.
+    pub async fn search_batch(
+        &self,
+        queries: &[String],
+        limit: usize,
+        min_score: Option<f32>,
+    ) -> Result<Vec<Vec<GuidelineResult>>, crate::error::AppError> {
+        let mut results: Vec<Option<Vec<GuidelineResult>>> = Vec::with_capacity(queries.len());
+        for query in queries {
+            results.push(self.cached_results(query, limit, min_score).await);
+        }
+
+        batch::fill_distinct(queries, &mut results, |texts| async move {
+            let embeddings = self.embedder.embed_queries(&texts).await?;
+            if embeddings.len() != texts.len() {
+                return Err(CommonError::Embedding(format!(
+                    "embedding count mismatch: expected {}, got {}",
+                    texts.len(),
+                    embeddings.len()
+                ))
+                .into());
+            }
+
+            let searches = texts.iter().zip(&embeddings).map(|(query, embedding)| {
+                self.search_embedding(query, embedding, limit, min_score)
+            });
+            futures::future::try_join_all(searches).await
+        })
+        .await?;
+
+        Ok(results.into_iter().map(Option::unwrap_or_default).collect())
+    }
+
+    /// Find the guidelines nearest to `guideline_id` using its stored embedding.
+    ///
+    /// Returns `None` if the guideline has no row in the index. The guideline itself is
+    /// excluded, and neighbours scoring below `min_score` are dropped, so the list may be
+    /// shorter than `limit` or empty.
+    pub async fn similar(
+        &self,
+        guideline_id: &str,
+        limit: usize,
+        min_score: Option<f32>,
+    ) -> Result<Option<Vec<GuidelineResult>>, crate::error::AppError> {
+        let embedding = self.vectordb.get_embedding(VECTOR_TABLE_NAME, guideline_id).await?;
+        let Some(embedding) = embedding else {
+            return Ok(None);
+        };
+
+        // One extra row since the guideline is its own nearest neighbour
+        let batches = self
+            .vectordb
+            .search(VECTOR_TABLE_NAME, &embedding, limit + 1, DISTANCE_TYPE)
+            .await?;
+        let results = extract_search_results(&batches, DISTANCE_TYPE, min_score, self.summary_len)
+            .into_iter()
+            .filter(|r| r.id != guideline_id)
+            .take(limit)
+            .collect();
+        Ok(Some(results))
+    }
+
+    async fn cached_results(
+        &self,
+        query: &str,
+        limit: usize,
+        min_score: Option<f32>,
+    ) -> Option<Vec<GuidelineResult>> {
+        let cached = self
+            .cache
+            .get_search_results(query, SearchMode::Semantic, DISTANCE_TYPE, min_score)
+            .await?;
+        if !cached.serves(limit) {
+            return None;
+        }
+        info!(query, "search cache hit");
+        Some(cached.results.into_iter().take(limit).collect())
+    }
+
+    async fn search_embedding(
+        &self,
+        query: &str,
+        query_embedding: &[f32],
+        limit: usize,
+        min_score: Option<f32>,
+    ) -> Result<Vec<GuidelineResult>, crate::error::AppError> {
+        let fetch_limit = limit.max(self.canonical_limit);
+        let batches = self
+            .vectordb
+            .search(VECTOR_TABLE_NAME, query_embedding, fetch_limit, DISTANCE_TYPE)
+            .await?;
+
+        let cached = CachedSearch {
+            fetched_limit: fetch_limit,
+            results: extract_search_results(&batches, DISTANCE_TYPE, min_score, self.summary_len),
+        };
+        self.cache
+            .set_search_results(query, SearchMode::Semantic, DISTANCE_TYPE, min_score, &cached)
+            .await;
+        Ok(cached.results.into_iter().take(limit).collect())
+    }
+
     pub fn table_name() -> &'static str {
         VECTOR_TABLE_NAME
     }
+
+    pub fn distance_type() -> DistanceType {
+        DISTANCE_TYPE
+    }
+
+    /// Minimum number of rows each search fetches (and caches) per query.
+    pub fn canonical_limit(&self) -> usize {
+        self.canonical_limit
+    }
+
+    /// Characters of text kept in each result's summary.
+    pub fn summary_len(&self) -> usize {
+        self.summary_len
+    }
+
+    /// Ids of every row in the vector index.
+    pub async fn indexed_ids(&self) -> Result<Vec<String>, crate::error::AppError> {
+        Ok(self.vectordb.list_ids(VECTOR_TABLE_NAME).await?)
+    }
+
+    /// Number of rows in the vector index.
+    pub async fn indexed_count(&self) -> Result<usize, crate::error::AppError> {
+        Ok(self.vectordb.count_rows(VECTOR_TABLE_NAME).await?)
+    }
 }
 
-fn extract_search_results(batches: &[RecordBatch]) -> Vec<GuidelineResult> {
+fn extract_search_results(
+    batches: &[RecordBatch],
+    distance_type: DistanceType,
+    min_score: Option<f32>,
+    summary_len: usize,
+) -> Vec<GuidelineResult> {
+    extract_results(batches, min_score, summary_len, |_, _, _, distance| {
+        Some(distance_type.score(distance))
+    })
+}
+
+fn id_scores(results: &[GuidelineResult]) -> Vec<(String, f32)> {
+    results.iter().map(|r| (r.id.clone(), r.score)).collect()
+}
+
+/// Build results from any query's batches, scoring rows with
+/// `score_row(id, title, text, distance)` and skipping rows it scores `None`.
+fn extract_results(
+    batches: &[RecordBatch],
+    min_score: Option<f32>,
+    summary_len: usize,
+    score_row: impl Fn(&str, &str, &str, f32) -> Option<f32>,
+) -> Vec<GuidelineResult> {
     let mut results = Vec::new();
 
     for batch in batches {
@@ -70,14 +306,16 @@ fn extract_search_results(batches: &[RecordBatch]) -> Vec<GuidelineResult> {
 
         for row in 0..num_rows {
             let text = text_col.value(row);
-            let summary = if text.chars().count() > MAX_SUMMARY_LEN {
-                format!("{}...", text.chars().take(MAX_SUMMARY_LEN).collect::<String>())
-            } else {
-                text.to_string()
-            };
+            let summary = summary::summarize(text, summary_len);
 
             let distance = distance_col.map(|c| c.value(row)).unwrap_or(0.0);
-            let score = (1.0_f32 - distance).max(0.0);
+            let Some(score) = score_row(id_col.value(row), title_col.value(row), text, distance)
+            else {
+                continue;
+            };
+            if min_score.is_some_and(|min| score < min) {
+                continue;
+            }
 
             results.push(GuidelineResult {
                 id: id_col.value(row).to_string(),
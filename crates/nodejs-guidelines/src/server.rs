@@ -1,29 +1,48 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
+use std::time::Duration;
 
 use rmcp::{
-    Json, ServerHandler,
+    Json, Peer, RoleServer, ServerHandler,
+    service::RequestContext,
     handler::server::router::tool::ToolRouter,
+    handler::server::tool::ToolCallContext,
     handler::server::wrapper::Parameters,
     model::*,
-    tool, tool_handler, tool_router,
+    tool, tool_router,
 };
-use tokio::sync::RwLock;
-use tracing::info;
+use tokio::sync::{RwLock, oneshot};
+use tokio::time::{Instant, MissedTickBehavior};
+use tracing::field::Empty;
+use tracing::{info, warn};
 
 use crate::cache::GuidelineCache;
 use crate::config::Config;
-use crate::model::{Category, Guideline};
+use crate::error::AppError;
+use crate::model::{Category, Guideline, GuidelineResult};
 use crate::search::SearchEngine;
-use crate::update::UpdateService;
-use mcp_common::embedding::Embedder;
+use crate::update::{ReindexGuard, UpdateService};
+use mcp_common::embedding::{EmbedProgress, Embedder};
+use mcp_common::hybrid::best_matching_section;
+use mcp_common::ids::{closest_match, matches_anchor, not_found_message, resolve_id};
 use mcp_common::mcp_api::{
-    CategoryInfo, CategoryListResponse, GetGuidelineParams, GuidelineDetailResponse,
-    GuidelineSearchResult, GuidelineSummary, ListCategoryParams, SearchGuidelinesParams,
-    SearchGuidelinesResponse, UpdateGuidelinesResponse,
+    BatchQueryResults, CategoriesResponse, CategoryInfo, CategoryListResponse, ConfigInfoResponse,
+    ExportGuidelinesParams, ExportGuidelinesResponse, GetGuidelineParams, GetGuidelinesParams, GetGuidelinesResponse, GuidelineDetailResponse,
+    GuidelineSearchResult, GuidelineSection as ApiGuidelineSection, GuidelineSummary,
+    InvalidateSearchCacheResponse, ListCategoryParams,
+    MAX_BATCH_IDS, MAX_BATCH_QUERIES,
+    SearchBatchParams, SearchBatchResponse,
+    SearchGuidelinesParams, SearchGuidelinesResponse, SimilarGuidelinesParams, StatsResponse,
+    UpdateGuidelinesResponse, VerifyIndexResponse,
 };
+use mcp_common::render::{RenderFormat, render_detail};
+use mcp_common::spans::input_bytes;
+use mcp_common::tool_timeout::ToolTimeout;
 use mcp_common::vectordb::VectorDb;
 
+/// URI prefix of guideline resources: `guideline://{id}`.
+const RESOURCE_URI_PREFIX: &str = "guideline://";
+
 pub struct AppState {
     pub guidelines: HashMap<String, Guideline>,
     pub categories: HashMap<String, Category>,
@@ -35,6 +54,8 @@ pub struct NodejsGuidelinesServer {
     search_engine: Arc<SearchEngine>,
     update_service: Arc<UpdateService>,
     cache: Arc<GuidelineCache>,
+    config_info: ConfigInfoResponse,
+    tool_timeout: ToolTimeout,
     tool_router: ToolRouter<NodejsGuidelinesServer>,
 }
 
@@ -56,8 +77,27 @@ impl NodejsGuidelinesServer {
             Arc::clone(&embedder),
             Arc::clone(&vectordb),
             Arc::clone(&cache),
+            config.search_cache_canonical_limit,
         ));
 
+        let config_info = ConfigInfoResponse {
+            repo_path: config.repo_path.clone(),
+            lancedb_path: config.lancedb_path.clone(),
+            table_name: SearchEngine::table_name().to_string(),
+            embedding_model: embedder.model_name().to_string(),
+            embedding_dimension: embedder.dimensions(),
+            distance_metric: SearchEngine::distance_type().as_str().to_string(),
+            search_cache_ttl_secs: cache.search_ttl_secs(),
+            search_cache_canonical_limit: search_engine.canonical_limit(),
+            search_summary_len: search_engine.summary_len(),
+            redis_configured: config.redis_url.is_some(),
+            redis_available: false,
+            auto_pull: config.auto_pull,
+            refresh_secs: config.refresh_interval.map(|period| period.as_secs()),
+            tool_timeout_secs: config.tool_timeout.limit().map(|limit| limit.as_secs()),
+        };
+        let tool_timeout = config.tool_timeout;
+
         let update_service = Arc::new(UpdateService::new(
             config,
             Arc::clone(&embedder),
@@ -75,14 +115,105 @@ impl NodejsGuidelinesServer {
             search_engine,
             update_service,
             cache,
+            config_info,
+            tool_timeout,
             tool_router: Self::tool_router(),
         }
     }
+
+    /// Wait until no `update_guidelines` re-index is running. Call on shutdown only: it
+    /// queues for the state write lock, which stalls new tool calls while it waits.
+    pub async fn wait_for_reindex(&self) {
+        let _state = self.state.write().await;
+    }
+
+    /// Commit the loaded guidelines were indexed from: the cached one, falling back to the
+    /// checkout's HEAD.
+    async fn indexed_commit(&self) -> Option<String> {
+        match self.cache.get_repo_commit().await {
+            Some(commit) => Some(commit),
+            None => self.update_service.get_repo_commit().ok(),
+        }
+    }
+
+    /// Run an update under `reindex` and, if it re-indexed, swap the new data into memory.
+    async fn apply_update(
+        &self,
+        reindex: &ReindexGuard,
+        progress: Option<EmbedProgress<'_>>,
+    ) -> Result<UpdateGuidelinesResponse, AppError> {
+        // Held for the whole re-index so `wait_for_reindex` cannot return mid-write.
+        let loaded = self.state.read().await;
+        let (result, new_data) = self
+            .update_service
+            .update(reindex, Some(loaded.guidelines.len()), progress)
+            .await?;
+        drop(loaded);
+
+        // If re-indexed, update the in-memory state
+        if let Some((guidelines, categories)) = new_data {
+            let guideline_count = guidelines.len();
+            let guideline_map: HashMap<String, Guideline> = guidelines
+                .into_iter()
+                .map(|g| (g.id.clone(), g))
+                .collect();
+
+            let mut state = self.state.write().await;
+            state.guidelines = guideline_map;
+            state.categories = categories;
+            info!(guideline_count, "in-memory state updated");
+        }
+
+        let guideline_count = if result.updated {
+            result.guideline_count
+        } else {
+            let state = self.state.read().await;
+            state.guidelines.len()
+        };
+
+        Ok(UpdateGuidelinesResponse {
+            updated: result.updated,
+            commit: result.commit,
+            guideline_count,
+        })
+    }
+
+    /// Check for updates every `period` until `stop` resolves or its sender is dropped.
+    ///
+    /// A tick is skipped while another update holds the re-index lock, and a re-index that
+    /// has started always runs to completion before the loop stops.
+    pub async fn refresh_periodically(&self, period: Duration, mut stop: oneshot::Receiver<()>) {
+        let mut interval = tokio::time::interval_at(Instant::now() + period, period);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            tokio::select! {
+                _ = &mut stop => break,
+                _ = interval.tick() => {}
+            }
+
+            let Some(reindex) = self.update_service.try_lock() else {
+                info!("scheduled update skipped: a re-index is already in progress");
+                continue;
+            };
+            match self.apply_update(&reindex, None).await {
+                Ok(result) if result.updated => info!(
+                    commit = %result.commit,
+                    guideline_count = result.guideline_count,
+                    "scheduled update re-indexed guidelines"
+                ),
+                Ok(result) => {
+                    info!(commit = %result.commit, "scheduled update: guidelines up to date")
+                }
+                Err(e) => warn!(error = %e, "scheduled update failed"),
+            }
+        }
+    }
 }
 
 #[tool_router]
 impl NodejsGuidelinesServer {
-    #[tool(description = "Search Node.js best practices by semantic similarity. Returns ranked results matching the query.")]
+    #[tool(description = "Search Node.js best practices by semantic similarity. Returns ranked results matching the query, for example query=\"handle async errors\". Set mode=\"keyword\" to match exact identifiers or terms such as \"helmet\" or \"process.on\", or mode=\"hybrid\" to blend keyword and semantic rankings. Pass include_sections=true to get the best-matching section heading (\"TL;DR\", \"Otherwise\" or \"Read More\") of each result.")]
+    #[tracing::instrument(name = "tool", skip_all, fields(tool = "search_guidelines", input_bytes = input_bytes(&params), cache = Empty))]
     async fn search_guidelines(
         &self,
         Parameters(params): Parameters<SearchGuidelinesParams>,
@@ -94,29 +225,93 @@ impl NodejsGuidelinesServer {
 
         let limit = params.limit.unwrap_or(10).min(50) as usize;
 
+        if let Some(min_score) = params.min_score {
+            if !(0.0..=1.0).contains(&min_score) {
+                return Err("min_score must be between 0.0 and 1.0".to_string());
+            }
+        }
+        if params.enforcement.is_some() {
+            return Err("enforcement levels are only available for C++ guidelines".to_string());
+        }
+
         let results = self
             .search_engine
-            .search(&query, limit)
+            .search(&query, limit, params.min_score, params.mode.unwrap_or_default())
             .await
             .map_err(|e| format!("search failed: {e}"))?;
 
-        let normalized: Vec<GuidelineSearchResult> = results
+        let state = self.state.read().await;
+        let mut normalized: Vec<GuidelineSearchResult> = results
             .into_iter()
-            .map(|r| GuidelineSearchResult {
-                id: r.id,
-                title: r.title,
-                category: r.category,
-                score: r.score,
-                summary: r.summary,
-            })
+            .map(|r| to_api_search_result(r, &state.guidelines))
             .collect();
 
+        if params.include_sections.unwrap_or(false) {
+            for result in &mut normalized {
+                let Some(sections) = state.guidelines.get(&result.id).and_then(api_sections) else {
+                    continue;
+                };
+                let sections = sections.iter().map(|s| (s.heading.as_str(), s.content.as_str()));
+                result.matched_section =
+                    best_matching_section(&query, sections).map(str::to_string);
+            }
+        }
+
         Ok(Json(SearchGuidelinesResponse {
             results: normalized,
         }))
     }
 
-    #[tool(description = "Get the full content of a specific Node.js best practice by ID (for example '1.1', '2.10', '8.4').")]
+    #[tool(description = "Search Node.js best practices for several queries in one call (at most 20). Returns one ranked result set per query, in input order.")]
+    #[tracing::instrument(name = "tool", skip_all, fields(tool = "search_batch", input_bytes = input_bytes(&params), cache = Empty))]
+    async fn search_batch(
+        &self,
+        Parameters(params): Parameters<SearchBatchParams>,
+    ) -> Result<Json<SearchBatchResponse>, String> {
+        if params.queries.is_empty() {
+            return Err("queries must not be empty".to_string());
+        }
+        if params.queries.len() > MAX_BATCH_QUERIES {
+            return Err(format!("at most {MAX_BATCH_QUERIES} queries are allowed per call"));
+        }
+
+        let queries: Vec<String> = params.queries.iter().map(|q| q.trim().to_string()).collect();
+        if let Some(i) = queries.iter().position(|q| q.is_empty()) {
+            return Err(format!("queries[{i}] must not be empty"));
+        }
+
+        let limit = params.limit.unwrap_or(10).min(50) as usize;
+
+        if let Some(min_score) = params.min_score {
+            if !(0.0..=1.0).contains(&min_score) {
+                return Err("min_score must be between 0.0 and 1.0".to_string());
+            }
+        }
+
+        let results = self
+            .search_engine
+            .search_batch(&queries, limit, params.min_score)
+            .await
+            .map_err(|e| format!("search failed: {e}"))?;
+
+        let state = self.state.read().await;
+        let queries = queries
+            .into_iter()
+            .zip(results)
+            .map(|(query, results)| BatchQueryResults {
+                query,
+                results: results
+                    .into_iter()
+                    .map(|r| to_api_search_result(r, &state.guidelines))
+                    .collect(),
+            })
+            .collect();
+
+        Ok(Json(SearchBatchResponse { queries }))
+    }
+
+    #[tool(description = "Get the full content of a specific Node.js best practice by ID (for example '1.1' (project architecture), '2.10' (error handling), '8.4' (Docker)). IDs match loosely, so '2 10' also works, and an unknown ID fails with the closest matches. The practice's anchor or a README link to it (e.g. '#-11-structure-your-solution-by-business-components') also works. Pass render=\"html\" or \"text\" for a pre-rendered copy in `rendered`.")]
+    #[tracing::instrument(name = "tool", skip_all, fields(tool = "get_guideline", input_bytes = input_bytes(&params), cache = Empty))]
     async fn get_guideline(
         &self,
         Parameters(params): Parameters<GetGuidelineParams>,
@@ -125,23 +320,128 @@ impl NodejsGuidelinesServer {
         if guideline_id.is_empty() {
             return Err("guideline_id must not be empty".to_string());
         }
+        let format = params
+            .render
+            .as_deref()
+            .map(RenderFormat::parse)
+            .transpose()?;
 
         if let Some(cached) = self.cache.get_guideline(&guideline_id).await {
-            return Ok(Json(to_api_guideline(&cached)));
+            return render_detail(to_api_guideline(&cached), format).map(Json);
         }
 
         let state = self.state.read().await;
-        let guideline = state
-            .guidelines
-            .iter()
-            .find(|(id, _)| id.eq_ignore_ascii_case(&guideline_id))
-            .map(|(_, g)| g)
-            .ok_or_else(|| format!("guideline not found: {guideline_id}"))?;
+        let guideline = find_guideline(&state.guidelines, &guideline_id)?;
 
-        Ok(Json(to_api_guideline(guideline)))
+        render_detail(to_api_guideline(guideline), format).map(Json)
     }
 
-    #[tool(description = "List all best practices in a category. Use category keys like '1', '2', '3' (see the source table of contents).")]
+    #[tool(description = "Get several Node.js best practices by ID in one call (at most 50), e.g. [\"1.1\", \"2.10\"]. Returns the found practices keyed by requested ID plus the IDs that were not found, instead of failing on the first unknown one.")]
+    #[tracing::instrument(name = "tool", skip_all, fields(tool = "get_guidelines", input_bytes = input_bytes(&params), cache = Empty))]
+    async fn get_guidelines(
+        &self,
+        Parameters(params): Parameters<GetGuidelinesParams>,
+    ) -> Result<Json<GetGuidelinesResponse>, String> {
+        if params.guideline_ids.is_empty() {
+            return Err("guideline_ids must not be empty".to_string());
+        }
+        if params.guideline_ids.len() > MAX_BATCH_IDS {
+            return Err(format!("at most {MAX_BATCH_IDS} guideline_ids are allowed per call"));
+        }
+
+        let ids: Vec<String> =
+            params.guideline_ids.iter().map(|id| id.trim().to_string()).collect();
+        if let Some(i) = ids.iter().position(|id| id.is_empty()) {
+            return Err(format!("guideline_ids[{i}] must not be empty"));
+        }
+        let format = params
+            .render
+            .as_deref()
+            .map(RenderFormat::parse)
+            .transpose()?;
+
+        // Exact-id hits come from one Redis MGET; the rest fall back to memory under one
+        // read lock for the whole batch.
+        let cached = self.cache.get_guidelines(&ids).await.unwrap_or_default();
+        let state = self.state.read().await;
+        let mut found = BTreeMap::new();
+        let mut not_found = Vec::new();
+        for (i, guideline_id) in ids.into_iter().enumerate() {
+            if found.contains_key(&guideline_id) || not_found.contains(&guideline_id) {
+                continue;
+            }
+            let guideline = cached
+                .get(i)
+                .and_then(Option::as_ref)
+                .or_else(|| lookup_guideline(&state.guidelines, &guideline_id));
+            match guideline {
+                Some(g) => {
+                    let detail = render_detail(to_api_guideline(g), format)?;
+                    found.insert(guideline_id, detail);
+                }
+                None => not_found.push(guideline_id),
+            }
+        }
+
+        Ok(Json(GetGuidelinesResponse { found, not_found }))
+    }
+
+    #[tool(description = "Find the guidelines most similar to a given guideline ID. Use min_score (0.0 to 1.0) to drop weak neighbours; results may be empty when nothing is genuinely similar.")]
+    #[tracing::instrument(name = "tool", skip_all, fields(tool = "similar_guidelines", input_bytes = input_bytes(&params), cache = Empty))]
+    async fn similar_guidelines(
+        &self,
+        Parameters(params): Parameters<SimilarGuidelinesParams>,
+    ) -> Result<Json<SearchGuidelinesResponse>, String> {
+        let guideline_id = params.guideline_id.trim().to_string();
+        if guideline_id.is_empty() {
+            return Err("guideline_id must not be empty".to_string());
+        }
+
+        let limit = params.limit.unwrap_or(10).min(50) as usize;
+
+        if let Some(min_score) = params.min_score {
+            if !(0.0..=1.0).contains(&min_score) {
+                return Err("min_score must be between 0.0 and 1.0".to_string());
+            }
+        }
+
+        let canonical_id = {
+            let state = self.state.read().await;
+            find_guideline(&state.guidelines, &guideline_id)?.id.clone()
+        };
+
+        let results = self
+            .search_engine
+            .similar(&canonical_id, limit, params.min_score)
+            .await
+            .map_err(|e| format!("similar_guidelines failed: {e}"))?
+            .ok_or_else(|| format!("guideline not indexed: {canonical_id}"))?;
+
+        let state = self.state.read().await;
+        Ok(Json(SearchGuidelinesResponse {
+            results: results
+                .into_iter()
+                .map(|r| to_api_search_result(r, &state.guidelines))
+                .collect(),
+        }))
+    }
+
+    #[tool(description = "List every Node.js best practices category (section numbers such as '1' or '2') with its display name and practice count, to plan browsing with list_category.")]
+    #[tracing::instrument(name = "tool", skip_all, fields(tool = "list_categories", cache = Empty))]
+    async fn list_categories(&self) -> Result<Json<CategoriesResponse>, String> {
+        let mut categories: Vec<Category> = match self.cache.get_categories().await {
+            Some(cached) => cached,
+            None => self.state.read().await.categories.values().cloned().collect(),
+        };
+        categories.sort_by(|a, b| a.key.cmp(&b.key));
+
+        Ok(Json(CategoriesResponse {
+            categories: categories.iter().map(to_api_category).collect(),
+        }))
+    }
+
+    #[tool(description = "List all Node.js best practices in a category. Use section numbers such as '1' (Project architecture), '2' (Error handling) or '6' (Security).")]
+    #[tracing::instrument(name = "tool", skip_all, fields(tool = "list_category", input_bytes = input_bytes(&params), cache = Empty))]
     async fn list_category(
         &self,
         Parameters(params): Parameters<ListCategoryParams>,
@@ -160,8 +460,11 @@ impl NodejsGuidelinesServer {
             .ok_or_else(|| {
                 let mut available: Vec<&str> = state.categories.keys().map(|s| s.as_str()).collect();
                 available.sort_unstable();
+                let hint = closest_match(&category_key, available.iter().copied())
+                    .map(|key| format!("did you mean '{key}'? "))
+                    .unwrap_or_default();
                 format!(
-                    "unknown category: '{category_key}'. Available categories: {}",
+                    "{hint}unknown category: '{category_key}'. Available categories: {}",
                     available.join(", ")
                 )
             })?;
@@ -176,6 +479,8 @@ impl NodejsGuidelinesServer {
             })
             .collect();
         guideline_summaries.sort_by(|a, b| a.id.cmp(&b.id));
+        let total = guideline_summaries.len();
+        let guideline_summaries = params.page(guideline_summaries);
 
         let response = CategoryListResponse {
             category: CategoryInfo {
@@ -183,46 +488,222 @@ impl NodejsGuidelinesServer {
                 display_name: category.display_name,
                 guideline_count: category.guideline_count,
             },
+            total,
             guidelines: guideline_summaries,
         };
 
         Ok(Json(response))
     }
 
-    #[tool(description = "Trigger a re-index of Node.js best practices from the git repository. Checks for updates and re-parses/re-embeds if the content has changed.")]
-    async fn update_guidelines(&self) -> Result<Json<UpdateGuidelinesResponse>, String> {
+    #[tool(description = "Trigger a re-index of Node.js best practices from the git repository. Checks for updates and re-parses/re-embeds if the content has changed. Only one re-index runs at a time; a call made while one is in progress fails and can be retried. A re-index that outlasts the tool timeout keeps running and its result is loaded when it finishes. Send a progress token to receive embedding progress as progress notifications.")]
+    #[tracing::instrument(name = "tool", skip_all, fields(tool = "update_guidelines", cache = Empty))]
+    async fn update_guidelines(
+        &self,
+        meta: Meta,
+        peer: Peer<RoleServer>,
+    ) -> Result<Json<UpdateGuidelinesResponse>, String> {
         info!("update_guidelines tool invoked");
 
-        let (result, new_data) = self
-            .update_service
-            .update()
-            .await
+        // Kept until the new data is swapped in, so two updates can neither race on the
+        // LanceDB table nor apply their results out of order.
+        let Some(reindex) = self.update_service.try_lock() else {
+            return Err("a re-index is already in progress; retry once it completes".to_string());
+        };
+
+        // Embedding progress is relayed as progress notifications when the client sent a
+        // progress token, and dropped otherwise.
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<(usize, usize)>();
+        let report = move |done: usize, total: usize| {
+            let _ = tx.send((done, total));
+        };
+        let forward = async {
+            let Some(progress_token) = meta.get_progress_token() else {
+                return;
+            };
+            while let Some((done, total)) = rx.recv().await {
+                let param = ProgressNotificationParam {
+                    progress_token: progress_token.clone(),
+                    progress: done as f64,
+                    total: Some(total as f64),
+                    message: Some(format!("embedded {done} of {total} guidelines")),
+                };
+                if let Err(e) = peer.notify_progress(param).await {
+                    warn!(error = %e, "failed to send progress notification");
+                }
+            }
+        };
+
+        // The update runs as its own task, so a tool timeout or a dropped request cannot stop
+        // it between writing the vector index and swapping the new data into memory; the
+        // result is applied when it completes either way.
+        let server = self.clone();
+        let update = tokio::spawn(async move {
+            // Moved in so the channel closes, ending `forward`, once the update finishes.
+            let report = report;
+            server.apply_update(&reindex, Some(&report)).await
+        });
+        let (result, ()) = tokio::join!(update, forward);
+        let response = result
+            .map_err(|e| format!("update failed: re-index task failed: {e}"))?
             .map_err(|e| format!("update failed: {e}"))?;
+        Ok(Json(response))
+    }
 
-        if let Some((guidelines, categories)) = new_data {
-            let guideline_map: HashMap<String, Guideline> = guidelines
-                .into_iter()
-                .map(|g| (g.id.clone(), g))
-                .collect();
+    #[tool(description = "Clear cached search results so the next searches run against the current index, e.g. after re-indexing with changed embedding text. Guideline and category caches are left in place. Returns how many cached searches were removed.")]
+    #[tracing::instrument(name = "tool", skip_all, fields(tool = "invalidate_search_cache", cache = Empty))]
+    async fn invalidate_search_cache(&self) -> Result<Json<InvalidateSearchCacheResponse>, String> {
+        let deleted = self
+            .cache
+            .invalidate_search()
+            .await
+            .ok_or_else(|| "invalidate_search_cache failed: Redis is unavailable or returned an error".to_string())?;
+        info!(deleted, "search cache cleared");
+        Ok(Json(InvalidateSearchCacheResponse { deleted }))
+    }
 
-            let mut state = self.state.write().await;
-            state.guidelines = guideline_map;
-            state.categories = categories;
-        }
+    #[tool(description = "Cross-check the loaded guidelines against the vector index and report ids missing from either side or indexed more than once. Operator-facing consistency check for partial or drifted re-indexes.")]
+    #[tracing::instrument(name = "tool", skip_all, fields(tool = "verify_index", cache = Empty))]
+    async fn verify_index(&self) -> Result<Json<VerifyIndexResponse>, String> {
+        let index_ids = self
+            .search_engine
+            .indexed_ids()
+            .await
+            .map_err(|e| format!("verify_index failed: {e}"))?;
+        let state = self.state.read().await;
+        Ok(Json(VerifyIndexResponse::compare(
+            state.guidelines.keys().cloned(),
+            index_ids,
+        )))
+    }
 
-        let guideline_count = if result.updated {
-            result.guideline_count
-        } else {
+    #[tool(description = "Export the loaded guidelines, for offline use or diffing across commits. format=\"json\" (default) returns structured guidelines sorted by id; format=\"markdown\" returns one document of their raw markdown headed by the indexed commit. Page with offset and limit (at least 1): without a limit a page holds as many guidelines as fit in 512 KiB, a limit whose page exceeds 512 KiB is refused, and next_offset gives the offset of the following page. Prefer search or lookup tools for individual rules.")]
+    #[tracing::instrument(name = "tool", skip_all, fields(tool = "export_guidelines", input_bytes = input_bytes(&params), cache = Empty))]
+    async fn export_guidelines(
+        &self,
+        Parameters(params): Parameters<ExportGuidelinesParams>,
+    ) -> Result<Json<ExportGuidelinesResponse>, String> {
+        let commit = self.indexed_commit().await;
+        let state = self.state.read().await;
+        let mut guidelines: Vec<&Guideline> = state.guidelines.values().collect();
+        guidelines.sort_by(|a, b| a.id.cmp(&b.id));
+        ExportGuidelinesResponse::new(
+            &params,
+            "Node.js Best Practices",
+            commit,
+            guidelines.into_iter().map(to_api_guideline).collect(),
+        )
+        .map(Json)
+    }
+
+    #[tool(description = "Report how many guidelines and categories are loaded, how many rows the vector index holds, the repository commit they were indexed from, Redis status, and cache hit/miss counts for guideline, search and category lookups. Read-only and cheap; does not trigger a re-index.")]
+    #[tracing::instrument(name = "tool", skip_all, fields(tool = "get_stats", cache = Empty))]
+    async fn get_stats(&self) -> Result<Json<StatsResponse>, String> {
+        let (guideline_count, category_count) = {
             let state = self.state.read().await;
-            state.guidelines.len()
+            (state.guidelines.len(), state.categories.len())
         };
-
-        Ok(Json(UpdateGuidelinesResponse {
-            updated: result.updated,
-            commit: result.commit,
+        Ok(Json(StatsResponse {
             guideline_count,
+            category_count,
+            commit: self.indexed_commit().await,
+            indexed_count: self.search_engine.indexed_count().await.ok(),
+            redis_available: self.cache.is_available().await,
+            cache: self.cache.metrics(),
         }))
     }
+
+    #[tool(description = "Report the server's effective configuration (paths, embedding and search settings, cache TTLs, Redis status). Credentials are never included.")]
+    #[tracing::instrument(name = "tool", skip_all, fields(tool = "config_info", cache = Empty))]
+    async fn config_info(&self) -> Result<Json<ConfigInfoResponse>, String> {
+        let mut info = self.config_info.clone();
+        info.redis_available = self.cache.is_available().await;
+        Ok(Json(info))
+    }
+}
+
+/// Resource entry for a guideline, read back as its raw markdown.
+fn to_resource(guideline: &Guideline) -> Resource {
+    let mut resource = RawResource::new(
+        format!("{RESOURCE_URI_PREFIX}{}", guideline.id),
+        guideline.id.clone(),
+    );
+    resource.description = Some(guideline.title.clone());
+    resource.mime_type = Some("text/markdown".to_string());
+    resource.no_annotation()
+}
+
+/// Every guideline as a resource, sorted by id.
+fn guideline_resources(guidelines: &HashMap<String, Guideline>) -> Vec<Resource> {
+    let mut guidelines: Vec<&Guideline> = guidelines.values().collect();
+    guidelines.sort_by(|a, b| a.id.cmp(&b.id));
+    guidelines.into_iter().map(to_resource).collect()
+}
+
+/// The raw markdown of the guideline `uri` names, or `resource_not_found` when it names none.
+fn read_guideline_resource(
+    guidelines: &HashMap<String, Guideline>,
+    uri: String,
+) -> Result<ReadResourceResult, ErrorData> {
+    let guideline = uri
+        .strip_prefix(RESOURCE_URI_PREFIX)
+        .and_then(|id| guidelines.get(id))
+        .ok_or_else(|| ErrorData::resource_not_found(format!("unknown resource: {uri}"), None))?;
+    Ok(ReadResourceResult {
+        contents: vec![ResourceContents::text(guideline.raw_markdown.clone(), uri)],
+    })
+}
+
+/// The guideline with the loosely matched id, or else the one `guideline_id` names by anchor
+/// (see [`matches_anchor`]).
+fn lookup_guideline<'a>(
+    guidelines: &'a HashMap<String, Guideline>,
+    guideline_id: &str,
+) -> Option<&'a Guideline> {
+    resolve_id(guideline_id, guidelines.keys().map(String::as_str))
+        .and_then(|id| guidelines.get(id))
+        .or_else(|| {
+            guidelines
+                .values()
+                .find(|g| matches_anchor(guideline_id, &g.anchor))
+        })
+}
+
+/// Look up a guideline by a loosely typed id (see [`mcp_common::ids`]), suggesting the
+/// closest ids in the error when nothing matches.
+fn find_guideline<'a>(
+    guidelines: &'a HashMap<String, Guideline>,
+    guideline_id: &str,
+) -> Result<&'a Guideline, String> {
+    lookup_guideline(guidelines, guideline_id)
+        .ok_or_else(|| not_found_message(guideline_id, guidelines.keys().map(String::as_str)))
+}
+
+/// Convert a search hit, preferring the practice's TL;DR over the truncated indexed text
+/// as its summary.
+fn to_api_search_result(
+    result: GuidelineResult,
+    guidelines: &HashMap<String, Guideline>,
+) -> GuidelineSearchResult {
+    let summary = guidelines
+        .get(&result.id)
+        .and_then(|g| g.tldr.clone())
+        .unwrap_or(result.summary);
+    GuidelineSearchResult {
+        id: result.id,
+        title: result.title,
+        category: result.category,
+        score: result.score,
+        summary,
+        matched_section: None,
+    }
+}
+
+fn to_api_category(category: &Category) -> CategoryInfo {
+    CategoryInfo {
+        key: category.key.clone(),
+        display_name: category.display_name.clone(),
+        guideline_count: category.guideline_count,
+    }
 }
 
 fn to_api_guideline(guideline: &Guideline) -> GuidelineDetailResponse {
@@ -232,17 +713,48 @@ fn to_api_guideline(guideline: &Guideline) -> GuidelineDetailResponse {
         title: guideline.title.clone(),
         category: guideline.category.clone(),
         raw_markdown: guideline.raw_markdown.clone(),
-        sections: None,
+        rendered: None,
+        sections: api_sections(guideline),
         source_file: Some(guideline.source_file.clone()),
+        enforcement: None,
     }
 }
 
-#[tool_handler]
+/// The practice's TL;DR, "Otherwise" rationale and "Read More" links as sections, or `None`
+/// when it has none of them.
+fn api_sections(guideline: &Guideline) -> Option<Vec<ApiGuidelineSection>> {
+    let mut sections = Vec::new();
+    if let Some(tldr) = &guideline.tldr {
+        sections.push(ApiGuidelineSection {
+            heading: "TL;DR".to_string(),
+            content: tldr.clone(),
+        });
+    }
+    if let Some(otherwise) = &guideline.otherwise {
+        sections.push(ApiGuidelineSection {
+            heading: "Otherwise".to_string(),
+            content: otherwise.clone(),
+        });
+    }
+    if !guideline.read_more_links.is_empty() {
+        let links: Vec<String> =
+            guideline.read_more_links.iter().map(|link| format!("- {link}")).collect();
+        sections.push(ApiGuidelineSection {
+            heading: "Read More".to_string(),
+            content: links.join("\n"),
+        });
+    }
+    (!sections.is_empty()).then_some(sections)
+}
+
 impl ServerHandler for NodejsGuidelinesServer {
     fn get_info(&self) -> ServerInfo {
         ServerInfo {
             protocol_version: ProtocolVersion::V_2025_06_18,
-            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            capabilities: ServerCapabilities::builder()
+                .enable_tools()
+                .enable_resources()
+                .build(),
             server_info: Implementation {
                 name: "nodejs-guidelines".to_string(),
                 version: env!("CARGO_PKG_VERSION").to_string(),
@@ -253,12 +765,58 @@ impl ServerHandler for NodejsGuidelinesServer {
             instructions: Some(
                 "Node.js Best Practices MCP server. Provides semantic search and lookup over the \
                  nodebestpractices content. Use search_guidelines for natural language queries, \
-                 get_guideline for specific IDs (e.g. 1.1), list_category for chapter browsing, \
-                 and update_guidelines to refresh from the repository."
+                 get_guideline for specific IDs (e.g. 1.1), \
+                 get_guidelines to fetch several at once, \
+                 similar_guidelines for related practices, list_categories and list_category \
+                 for chapter browsing, \
+                 update_guidelines to refresh from the repository, config_info to inspect \
+                 the effective configuration, verify_index to check the index for drift, \
+                 export_guidelines to snapshot every practice, and get_stats for loaded counts and the indexed commit."
                     .to_string(),
             ),
         }
     }
+
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let tool = request.name.clone();
+        let call = self
+            .tool_router
+            .call(ToolCallContext::new(self, request, context));
+        self.tool_timeout
+            .run(&tool, call)
+            .await
+            .unwrap_or_else(|message| Ok(CallToolResult::error(vec![Content::text(message)])))
+    }
+
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, ErrorData> {
+        Ok(ListToolsResult::with_all_items(self.tool_router.list_all()))
+    }
+
+    async fn list_resources(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourcesResult, ErrorData> {
+        let state = self.state.read().await;
+        Ok(ListResourcesResult::with_all_items(guideline_resources(&state.guidelines)))
+    }
+
+    async fn read_resource(
+        &self,
+        ReadResourceRequestParams { uri, .. }: ReadResourceRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, ErrorData> {
+        let state = self.state.read().await;
+        read_guideline_resource(&state.guidelines, uri)
+    }
 }
 
 #[cfg(test)]
@@ -270,9 +828,18 @@ mod tests {
         let tools = NodejsGuidelinesServer::tool_router().list_all();
         for name in [
             "search_guidelines",
+            "search_batch",
             "get_guideline",
+            "get_guidelines",
+            "similar_guidelines",
+            "list_categories",
             "list_category",
             "update_guidelines",
+            "config_info",
+            "verify_index",
+            "get_stats",
+            "invalidate_search_cache",
+            "export_guidelines",
         ] {
             let tool = tools
                 .iter()
@@ -4,87 +4,158 @@
 /// callers fall through to compute from source.
 ///
 /// Key schema:
-/// - `rag:v1:guideline:{id}` — JSON-serialized Guideline
-/// - `rag:v1:search:{sha256(query|limit)}` — JSON-serialized Vec<GuidelineResult> (TTL 3600s)
-/// - `rag:v1:categories` — JSON-serialized Vec<Category>
-/// - `rag:v1:category:{key}` — JSON-serialized Vec<String> of guideline IDs
-/// - `rag:v1:repo_commit` — Git commit hash string
+/// - `rag:v2:guideline:{id}` — JSON-serialized Guideline
+/// - `rag:v2:search:{sha256(query|mode|metric|min_score)}` — JSON-serialized CachedSearch (TTL `SEARCH_CACHE_TTL_SECS`, default 3600s)
+/// - `rag:v2:categories` — JSON-serialized Vec<Category>
+/// - `rag:v2:category:{key}` — JSON-serialized Vec<String> of guideline IDs
+/// - `rag:v2:repo_commit` — Git commit hash string
+/// - `rag:v2:content_hashes` — JSON map of guideline ID to indexed content hash
+use std::collections::HashMap;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use tracing::warn;
 
 use crate::model::{Category, Guideline, GuidelineResult};
-use mcp_common::redis::RedisCache;
+use mcp_common::mcp_api::{CacheMetrics, SearchMode};
+use mcp_common::redis::{HitCounter, RedisCache};
+use mcp_common::vectordb::DistanceType;
 
-const KEY_PREFIX: &str = "rag:v1:";
-const SEARCH_TTL_SECS: u64 = 3600;
+const KEY_PREFIX: &str = "rag:v2:";
+/// Default for `SEARCH_CACHE_TTL_SECS`.
+const DEFAULT_SEARCH_TTL_SECS: u64 = 3600;
+
+/// Search results for one query, cached at the limit the vector search ran with.
+///
+/// Results are ranked, so any smaller limit is served by truncation. When the search
+/// returned fewer rows than it asked for, the entry already holds every match and can
+/// serve any limit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedSearch {
+    pub fetched_limit: usize,
+    pub results: Vec<GuidelineResult>,
+}
+
+impl CachedSearch {
+    /// Returns `true` if this entry can answer a request for `limit` results.
+    pub fn serves(&self, limit: usize) -> bool {
+        limit <= self.fetched_limit || self.results.len() < self.fetched_limit
+    }
+}
 
 pub struct GuidelineCache {
     redis: RedisCache,
+    search_ttl_secs: u64,
+    guideline_lookups: HitCounter,
+    search_lookups: HitCounter,
+    category_lookups: HitCounter,
 }
 
 impl GuidelineCache {
+    /// Search results expire after `SEARCH_CACHE_TTL_SECS` seconds (default 3600; unset,
+    /// zero or unparsable values use the default).
     pub fn new(redis: RedisCache) -> Self {
-        Self { redis }
+        let search_ttl_secs = std::env::var("SEARCH_CACHE_TTL_SECS")
+            .ok()
+            .and_then(|s| s.trim().parse::<u64>().ok())
+            .filter(|&secs| secs > 0)
+            .unwrap_or(DEFAULT_SEARCH_TTL_SECS);
+        Self {
+            redis,
+            search_ttl_secs,
+            guideline_lookups: HitCounter::default(),
+            search_lookups: HitCounter::default(),
+            category_lookups: HitCounter::default(),
+        }
     }
 
-    pub async fn get_guideline(&self, id: &str) -> Option<Guideline> {
-        let key = format!("{KEY_PREFIX}guideline:{id}");
-        let json = self.redis.get(&key).await?;
-        serde_json::from_str(&json)
-            .inspect_err(|e| warn!(error = %e, key, "cache deserialization failed"))
-            .ok()
+    /// Seconds a cached search result lives.
+    pub fn search_ttl_secs(&self) -> u64 {
+        self.search_ttl_secs
     }
 
-    pub async fn set_guideline(&self, guideline: &Guideline) {
-        let key = format!("{KEY_PREFIX}guideline:{}", guideline.id);
-        if let Ok(json) = serde_json::to_string(guideline) {
-            self.redis.set(&key, &json).await;
+    /// Hit/miss counts of guideline, search and category lookups since startup.
+    pub fn metrics(&self) -> CacheMetrics {
+        CacheMetrics {
+            guideline: self.guideline_lookups.stats(),
+            search: self.search_lookups.stats(),
+            category: self.category_lookups.stats(),
         }
     }
 
-    pub async fn get_search_results(&self, query: &str, limit: usize) -> Option<Vec<GuidelineResult>> {
-        let key = search_key(query, limit);
-        let json = self.redis.get(&key).await?;
-        serde_json::from_str(&json)
-            .inspect_err(|e| warn!(error = %e, key, "cache deserialization failed"))
-            .ok()
+    /// Read and deserialize the JSON value at `key`.
+    async fn get_json<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let json = self.redis.get(key).await?;
+        parse_json(key, &json)
     }
 
-    pub async fn set_search_results(&self, query: &str, limit: usize, results: &[GuidelineResult]) {
-        let key = search_key(query, limit);
-        if let Ok(json) = serde_json::to_string(results) {
-            self.redis.set_with_ttl(&key, &json, SEARCH_TTL_SECS).await;
-        }
+    /// Returns `true` if Redis is reachable.
+    pub async fn is_available(&self) -> bool {
+        self.redis.is_available().await
     }
 
-    pub async fn get_categories(&self) -> Option<Vec<Category>> {
-        let key = format!("{KEY_PREFIX}categories");
-        let json = self.redis.get(&key).await?;
-        serde_json::from_str(&json)
-            .inspect_err(|e| warn!(error = %e, key, "cache deserialization failed"))
-            .ok()
+    pub async fn get_guideline(&self, id: &str) -> Option<Guideline> {
+        let key = format!("{KEY_PREFIX}guideline:{id}");
+        self.guideline_lookups.record(self.get_json(&key).await)
     }
 
-    pub async fn set_categories(&self, categories: &[Category]) {
-        let key = format!("{KEY_PREFIX}categories");
-        if let Ok(json) = serde_json::to_string(categories) {
-            self.redis.set(&key, &json).await;
+    /// Batch form of [`Self::get_guideline`] using one `MGET`, in the order of `ids`.
+    pub async fn get_guidelines(&self, ids: &[String]) -> Option<Vec<Option<Guideline>>> {
+        let keys: Vec<String> = ids
+            .iter()
+            .map(|id| format!("{KEY_PREFIX}guideline:{id}"))
+            .collect();
+        let Some(values) = self.redis.mget(&keys).await else {
+            for _ in ids {
+                self.guideline_lookups.count(false);
+            }
+            return None;
+        };
+        let guidelines = keys
+            .iter()
+            .zip(values)
+            .map(|(key, json)| {
+                let guideline = json.and_then(|json| parse_json(key, &json));
+                self.guideline_lookups.record(guideline)
+            })
+            .collect();
+        Some(guidelines)
+    }
+
+    pub async fn get_search_results(
+        &self,
+        query: &str,
+        mode: SearchMode,
+        distance_type: DistanceType,
+        min_score: Option<f32>,
+    ) -> Option<CachedSearch> {
+        let key = search_key(query, mode, distance_type, min_score);
+        self.search_lookups.record(self.get_json(&key).await)
+    }
+
+    pub async fn set_search_results(
+        &self,
+        query: &str,
+        mode: SearchMode,
+        distance_type: DistanceType,
+        min_score: Option<f32>,
+        cached: &CachedSearch,
+    ) {
+        let key = search_key(query, mode, distance_type, min_score);
+        if let Ok(json) = serde_json::to_string(cached) {
+            self.redis.set_with_ttl(&key, &json, self.search_ttl_secs).await;
         }
     }
 
-    pub async fn get_category_guideline_ids(&self, key_name: &str) -> Option<Vec<String>> {
-        let key = format!("{KEY_PREFIX}category:{key_name}");
-        let json = self.redis.get(&key).await?;
-        serde_json::from_str(&json)
-            .inspect_err(|e| warn!(error = %e, key, "cache deserialization failed"))
-            .ok()
+    pub async fn get_categories(&self) -> Option<Vec<Category>> {
+        let key = format!("{KEY_PREFIX}categories");
+        self.category_lookups.record(self.get_json(&key).await)
     }
 
-    pub async fn set_category_guideline_ids(&self, key_name: &str, ids: &[String]) {
+    pub async fn get_category_guideline_ids(&self, key_name: &str) -> Option<Vec<String>> {
         let key = format!("{KEY_PREFIX}category:{key_name}");
-        if let Ok(json) = serde_json::to_string(ids) {
-            self.redis.set(&key, &json).await;
-        }
+        self.category_lookups.record(self.get_json(&key).await)
     }
 
     pub async fn get_repo_commit(&self) -> Option<String> {
@@ -97,16 +168,77 @@ impl GuidelineCache {
         self.redis.set(&key, commit).await;
     }
 
+    pub async fn get_content_hashes(&self) -> Option<HashMap<String, String>> {
+        let key = format!("{KEY_PREFIX}content_hashes");
+        self.get_json(&key).await
+    }
+
+    pub async fn set_content_hashes(&self, hashes: &HashMap<String, String>) {
+        let key = format!("{KEY_PREFIX}content_hashes");
+        if let Ok(json) = serde_json::to_string(hashes) {
+            self.redis.set(&key, &json).await;
+        }
+    }
+
+    /// Populate every guideline, the category list (in the order given) and each
+    /// category's sorted guideline ids with one `MSET`. Returns `true` if successful.
+    pub async fn warmup(&self, guidelines: &[Guideline], categories: &[Category]) -> bool {
+        let mut pairs = Vec::with_capacity(guidelines.len() + categories.len() + 1);
+        for g in guidelines {
+            if let Ok(json) = serde_json::to_string(g) {
+                pairs.push((format!("{KEY_PREFIX}guideline:{}", g.id), json));
+            }
+        }
+        if let Ok(json) = serde_json::to_string(categories) {
+            pairs.push((format!("{KEY_PREFIX}categories"), json));
+        }
+        for category in categories {
+            let mut ids: Vec<&str> = guidelines
+                .iter()
+                .filter(|g| g.category == category.key)
+                .map(|g| g.id.as_str())
+                .collect();
+            ids.sort();
+            if let Ok(json) = serde_json::to_string(&ids) {
+                pairs.push((format!("{KEY_PREFIX}category:{}", category.key), json));
+            }
+        }
+        self.redis.mset(&pairs).await
+    }
+
     pub async fn invalidate_all(&self) {
         self.redis.delete_by_prefix(KEY_PREFIX).await;
     }
+
+    /// Delete cached search results only, leaving guideline and category entries in place.
+    /// Returns the number of entries removed, or `None` if Redis is unavailable.
+    pub async fn invalidate_search(&self) -> Option<usize> {
+        self.redis.delete_by_prefix(&format!("{KEY_PREFIX}search:")).await
+    }
+}
+
+fn parse_json<T: DeserializeOwned>(key: &str, json: &str) -> Option<T> {
+    serde_json::from_str(json)
+        .inspect_err(|e| warn!(error = %e, key, "cache deserialization failed"))
+        .ok()
 }
 
-fn search_key(query: &str, limit: usize) -> String {
+fn search_key(
+    query: &str,
+    mode: SearchMode,
+    distance_type: DistanceType,
+    min_score: Option<f32>,
+) -> String {
     let mut hasher = Sha256::new();
     hasher.update(query.as_bytes());
     hasher.update(b"|");
-    hasher.update(limit.to_string().as_bytes());
+    hasher.update(mode.as_str().as_bytes());
+    hasher.update(b"|");
+    hasher.update(distance_type.as_str().as_bytes());
+    hasher.update(b"|");
+    if let Some(min_score) = min_score {
+        hasher.update(min_score.to_string().as_bytes());
+    }
     let hash = hasher.finalize();
     format!("{KEY_PREFIX}search:{:x}", hash)
 }
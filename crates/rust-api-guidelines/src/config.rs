@@ -1,6 +1,11 @@
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use crate::error::AppError;
+use mcp_common::tool_timeout::ToolTimeout;
+
+/// Default for `SEARCH_CACHE_CANONICAL_LIMIT`; matches the `search_guidelines` limit cap.
+const DEFAULT_CANONICAL_LIMIT: usize = 50;
 
 /// Application configuration loaded explicitly from environment variables.
 #[derive(Debug, Clone)]
@@ -11,6 +16,19 @@ pub struct Config {
     pub lancedb_path: String,
     /// Filesystem path to the cloned Rust API Guidelines repository.
     pub repo_path: String,
+    /// Whether to fetch and hard-reset the repository to `origin`'s default branch before
+    /// each update check. Off by default, so sandboxed deployments never touch the network.
+    pub auto_pull: bool,
+    /// Interval between scheduled background update checks. `None` disables them.
+    pub refresh_interval: Option<Duration>,
+    /// Whether to repopulate the guideline and category caches at startup when no
+    /// re-index is needed.
+    pub cache_warmup: bool,
+    /// Minimum rows each search fetches, so one cached entry serves every smaller limit.
+    /// 0 fetches exactly the requested limit.
+    pub search_cache_canonical_limit: usize,
+    /// Deadline for each tool call (`update_guidelines` excepted).
+    pub tool_timeout: ToolTimeout,
 }
 
 impl Config {
@@ -20,6 +38,11 @@ impl Config {
     ///
     /// Optional:
     /// - `REDIS_URL`: Redis connection string
+    /// - `GUIDELINES_AUTO_PULL`: `1` to pull upstream before checking for updates
+    /// - `GUIDELINES_REFRESH_SECS`: check for updates in the background this often
+    /// - `CACHE_WARMUP`: `1` to repopulate the guideline caches at startup
+    /// - `SEARCH_CACHE_CANONICAL_LIMIT`: minimum rows each search fetches, default 50
+    /// - `TOOL_TIMEOUT_SECS`: fail tool calls that run longer than this
     pub fn from_env() -> Result<Self, AppError> {
         let lancedb_path = std::env::var("LANCEDB_PATH").map_err(|_| {
             AppError::Config("LANCEDB_PATH environment variable is required".to_string())
@@ -31,12 +54,8 @@ impl Config {
             )
         })?;
 
-        let required = [
-            "src/checklist.md",
-            "src/SUMMARY.md",
-            "src/naming.md",
-            "src/documentation.md",
-        ];
+        // Chapter files are discovered from SUMMARY.md and skipped when missing.
+        let required = ["src/checklist.md", "src/SUMMARY.md"];
 
         for rel in required {
             let file = Path::new(&repo_path).join(rel);
@@ -45,10 +64,42 @@ impl Config {
             }
         }
 
+        let auto_pull = env_flag("GUIDELINES_AUTO_PULL")?;
+        let cache_warmup = env_flag("CACHE_WARMUP")?;
+
+        let refresh_interval = match std::env::var("GUIDELINES_REFRESH_SECS") {
+            Ok(value) => match value.trim().parse::<u64>() {
+                Ok(0) => None,
+                Ok(secs) => Some(Duration::from_secs(secs)),
+                Err(_) => {
+                    return Err(AppError::Config(format!(
+                        "GUIDELINES_REFRESH_SECS must be a whole number of seconds, got '{value}'"
+                    )));
+                }
+            },
+            Err(_) => None,
+        };
+
+        let search_cache_canonical_limit = match std::env::var("SEARCH_CACHE_CANONICAL_LIMIT") {
+            Ok(value) => value.trim().parse::<usize>().map_err(|_| {
+                AppError::Config(format!(
+                    "SEARCH_CACHE_CANONICAL_LIMIT must be a whole number, got '{value}'"
+                ))
+            })?,
+            Err(_) => DEFAULT_CANONICAL_LIMIT,
+        };
+
+        let tool_timeout = ToolTimeout::from_env().map_err(AppError::Config)?;
+
         Ok(Self {
             redis_url: std::env::var("REDIS_URL").ok(),
             lancedb_path,
             repo_path,
+            auto_pull,
+            refresh_interval,
+            cache_warmup,
+            search_cache_canonical_limit,
+            tool_timeout,
         })
     }
 
@@ -56,3 +107,12 @@ impl Config {
         Path::new(&self.repo_path).to_path_buf()
     }
 }
+
+/// Read a `1`/`0` (or `true`/`false`) flag; unset or empty means off.
+fn env_flag(name: &str) -> Result<bool, AppError> {
+    match std::env::var(name).as_deref().map(str::trim) {
+        Ok("1" | "true") => Ok(true),
+        Ok("" | "0" | "false") | Err(_) => Ok(false),
+        Ok(value) => Err(AppError::Config(format!("{name} must be '1' or '0', got '{value}'"))),
+    }
+}
@@ -15,6 +15,18 @@ pub struct Guideline {
     pub source_file: String,
     /// Full original markdown for this guideline
     pub raw_markdown: String,
+    /// Sub-sections split at `###` headings; prose before the first one is "Description"
+    #[serde(default)]
+    pub sections: Vec<GuidelineSection>,
+}
+
+/// A sub-section within a guideline (e.g., "Description", "Examples from the standard library").
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GuidelineSection {
+    /// Section heading, e.g. "Description"
+    pub heading: String,
+    /// Section content (markdown)
+    pub content: String,
 }
 
 /// A search result returned from vector similarity search.
@@ -39,4 +51,7 @@ pub struct Category {
     pub key: String,
     /// Number of guidelines in this category
     pub guideline_count: usize,
+    /// Zero-based position of the chapter in SUMMARY.md
+    #[serde(default)]
+    pub position: usize,
 }
@@ -2,10 +2,15 @@ use std::collections::HashMap;
 use std::path::Path;
 
 use regex::Regex;
+use tracing::warn;
 
 use crate::error::AppError;
-use crate::model::{Category, Guideline};
+use crate::model::{Category, Guideline, GuidelineSection};
 
+/// Book index whose chapter links decide which files are parsed.
+const SUMMARY_FILE: &str = "src/SUMMARY.md";
+
+/// Chapters parsed when `SUMMARY_FILE` links none.
 const CATEGORY_FILES: &[&str] = &[
     "src/naming.md",
     "src/interoperability.md",
@@ -26,35 +31,96 @@ pub fn parse_guidelines_repo(
     let mut guidelines = Vec::new();
     let mut category_map: HashMap<String, Category> = HashMap::new();
 
-    for rel_path in CATEGORY_FILES {
+    for rel_path in &chapter_files(repo_path) {
         let path = repo_path.join(rel_path);
-        let content = std::fs::read_to_string(&path).map_err(|e| {
-            AppError::Config(format!("failed to read {}: {e}", path.display()))
-        })?;
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) => {
+                warn!(error = %e, path = %path.display(), "skipping unreadable chapter");
+                continue;
+            }
+        };
 
         let (category_name, mut chapter_guidelines) =
-            parse_category_file(&content, rel_path).map_err(|e| {
-                AppError::Parse {
-                    line: e.line,
-                    message: format!("{} in {}", e.message, rel_path),
+            match parse_category_file(&content, rel_path) {
+                Ok(parsed) => parsed,
+                Err(e) => {
+                    warn!(
+                        line = e.line,
+                        error = %e.message,
+                        file = %rel_path,
+                        "skipping malformed chapter"
+                    );
+                    continue;
                 }
-            })?;
+            };
+
+        // Front matter such as the about page and checklist holds no guidelines.
+        if chapter_guidelines.is_empty() {
+            continue;
+        }
 
         let count = chapter_guidelines.len();
+        let position = category_map.len();
         category_map.insert(
             category_name.clone(),
             Category {
                 key: category_name,
                 guideline_count: count,
+                position,
             },
         );
         guidelines.append(&mut chapter_guidelines);
     }
 
+    if guidelines.is_empty() {
+        return Err(AppError::Config(format!(
+            "no guidelines parsed from {}",
+            repo_path.display()
+        )));
+    }
+
     guidelines.sort_by(|a, b| a.id.cmp(&b.id));
     Ok((guidelines, category_map))
 }
 
+/// Chapter files linked from `SUMMARY_FILE`, in book order, or `CATEGORY_FILES` when the
+/// summary cannot be read or links no markdown files.
+fn chapter_files(repo_path: &Path) -> Vec<String> {
+    let path = repo_path.join(SUMMARY_FILE);
+    let files = match std::fs::read_to_string(&path) {
+        Ok(content) => parse_summary(&content),
+        Err(e) => {
+            warn!(error = %e, path = %path.display(), "failed to read book summary");
+            Vec::new()
+        }
+    };
+    if files.is_empty() {
+        warn!("no chapters found in {SUMMARY_FILE}; using the built-in chapter list");
+        return CATEGORY_FILES.iter().map(|f| f.to_string()).collect();
+    }
+    files
+}
+
+/// Repo-relative paths of the local `.md` files linked from SUMMARY.md, without duplicates.
+fn parse_summary(content: &str) -> Vec<String> {
+    let link_re =
+        Regex::new(r"\]\(\s*(?:\./)?([^)\s#]+\.md)(?:#[^)\s]*)?\s*\)").expect("valid regex");
+
+    let mut files: Vec<String> = Vec::new();
+    for caps in link_re.captures_iter(content) {
+        let target = &caps[1];
+        if target.contains("://") {
+            continue;
+        }
+        let file = format!("src/{target}");
+        if !files.contains(&file) {
+            files.push(file);
+        }
+    }
+    files
+}
+
 pub fn compose_embedding_text(guideline: &Guideline) -> String {
     let text = format!(
         "{}: {}. Category: {}. {}",
@@ -75,14 +141,18 @@ struct ParseError {
 }
 
 fn parse_category_file(content: &str, source_file: &str) -> Result<(String, Vec<Guideline>), ParseError> {
-    let heading_re = Regex::new(r"^##\s+(.+?)\s+\((C-[A-Z0-9-]+)\)\s*$").expect("valid regex");
-    let anchor_re = Regex::new(r#"^<a id="([^"]+)"></a>\s*$"#).expect("valid regex");
+    let heading_re = Regex::new(r"^##\s+(.+?)\s+\(\s*(C-[A-Z0-9-]+)\s*\)\s*$").expect("valid regex");
+    let anchor_re = Regex::new(r#"^\s*<a id="([^"]+)"></a>\s*$"#).expect("valid regex");
+    let subheading_re = Regex::new(r"^#{3,6}\s+(.+?)\s*$").expect("valid regex");
 
     let lines: Vec<&str> = content.lines().collect();
     let category = lines
         .iter()
-        .find_map(|line| line.strip_prefix("# "))
-        .map(|s| s.trim().to_string())
+        .find_map(|line| {
+            line.strip_prefix('#')
+                .filter(|rest| rest.starts_with(char::is_whitespace))
+        })
+        .map(normalize_whitespace)
         .ok_or_else(|| ParseError {
             line: 1,
             message: "missing category heading".to_string(),
@@ -96,7 +166,7 @@ fn parse_category_file(content: &str, source_file: &str) -> Result<(String, Vec<
         let header_idx;
 
         if let Some(caps) = anchor_re.captures(lines[i]) {
-            anchor = Some(caps[1].to_string());
+            anchor = Some(caps[1].trim().to_string());
             if i + 1 < lines.len() && heading_re.is_match(lines[i + 1]) {
                 header_idx = i + 1;
             } else {
@@ -114,7 +184,7 @@ fn parse_category_file(content: &str, source_file: &str) -> Result<(String, Vec<
             line: header_idx + 1,
             message: "invalid guideline heading".to_string(),
         })?;
-        let title = caps[1].trim().to_string();
+        let title = normalize_whitespace(&caps[1]);
         let id = caps[2].trim().to_string();
         let anchor = anchor.unwrap_or_else(|| id.to_lowercase());
 
@@ -139,6 +209,7 @@ fn parse_category_file(content: &str, source_file: &str) -> Result<(String, Vec<
         }
 
         let raw_markdown = lines[start..end].join("\n").trim().to_string();
+        let sections = parse_sections(&lines[header_idx + 1..end], &subheading_re);
         guidelines.push(Guideline {
             id,
             anchor,
@@ -146,6 +217,7 @@ fn parse_category_file(content: &str, source_file: &str) -> Result<(String, Vec<
             category: category.clone(),
             source_file: source_file.to_string(),
             raw_markdown,
+            sections,
         });
 
         i = end;
@@ -154,6 +226,50 @@ fn parse_category_file(content: &str, source_file: &str) -> Result<(String, Vec<
     Ok((category, guidelines))
 }
 
+/// Split a guideline body into sections at `###` (or deeper) headings outside code fences.
+/// Prose before the first heading, or the whole body when there is none, becomes a
+/// "Description" section, so even an empty body yields one section.
+fn parse_sections(body: &[&str], subheading_re: &Regex) -> Vec<GuidelineSection> {
+    let mut sections = Vec::new();
+    let mut heading = "Description".to_string();
+    let mut content: Vec<&str> = Vec::new();
+    let mut in_fence = false;
+
+    for &line in body {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+        } else if let Some(caps) = subheading_re.captures(line).filter(|_| !in_fence) {
+            push_section(&mut sections, heading, &content);
+            heading = normalize_whitespace(&caps[1]);
+            content.clear();
+            continue;
+        }
+        content.push(line);
+    }
+    push_section(&mut sections, heading, &content);
+    if sections.is_empty() {
+        sections.push(GuidelineSection {
+            heading: "Description".to_string(),
+            content: String::new(),
+        });
+    }
+    sections
+}
+
+/// Append a section unless it is an empty "Description" (no prose before the first heading).
+fn push_section(sections: &mut Vec<GuidelineSection>, heading: String, content: &[&str]) {
+    let content = content.join("\n").trim().to_string();
+    if content.is_empty() && sections.is_empty() && heading == "Description" {
+        return;
+    }
+    sections.push(GuidelineSection { heading, content });
+}
+
+/// Trim and collapse runs of whitespace (tabs, repeated spaces) into single spaces.
+fn normalize_whitespace(s: &str) -> String {
+    s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -181,6 +297,121 @@ Use as_/to_/into_.
         assert_eq!(guidelines[1].id, "C-CONV");
     }
 
+    #[test]
+    fn parse_normalizes_header_whitespace() {
+        let content = "#\tNaming  \n\n<a id=\" c-case \"></a>\t\n\
+##\tCasing  conforms\tto RFC 430 ( C-CASE )  \n\nUse Rust conventions.\n";
+
+        let (category, guidelines) = parse_category_file(content, "src/naming.md").unwrap();
+        assert_eq!(category, "Naming");
+        assert_eq!(guidelines.len(), 1);
+        assert_eq!(guidelines[0].id, "C-CASE");
+        assert_eq!(guidelines[0].anchor, "c-case");
+        assert_eq!(guidelines[0].title, "Casing conforms to RFC 430");
+    }
+
+    #[test]
+    fn parse_splits_sections_at_subheadings() {
+        let content = r#"# Interoperability
+
+<a id="c-common-traits"></a>
+## Types eagerly implement common traits (C-COMMON-TRAITS)
+
+Rust's trait system does not allow orphans.
+
+```rust
+### not a heading
+```
+
+### Examples from the standard library
+
+- `Vec<T>` implements `Clone`.
+
+<a id="c-conv-traits"></a>
+## Conversions use the standard traits (C-CONV-TRAITS)
+
+Use `From`, `TryFrom`, `AsRef`, `AsMut`.
+"#;
+
+        let (_, guidelines) = parse_category_file(content, "src/interoperability.md").unwrap();
+        let sections: Vec<(&str, &str)> = guidelines[0]
+            .sections
+            .iter()
+            .map(|s| (s.heading.as_str(), s.content.as_str()))
+            .collect();
+        assert_eq!(
+            sections,
+            [
+                (
+                    "Description",
+                    "Rust's trait system does not allow orphans.\n\n```rust\n### not a heading\n```"
+                ),
+                ("Examples from the standard library", "- `Vec<T>` implements `Clone`."),
+            ]
+        );
+        assert_eq!(guidelines[1].sections.len(), 1);
+        assert_eq!(guidelines[1].sections[0].heading, "Description");
+    }
+
+    #[test]
+    fn empty_body_yields_an_empty_description_section() {
+        let content = "# Naming\n\n<a id=\"c-case\"></a>\n## Casing conforms to RFC 430 (C-CASE)\n";
+        let (_, guidelines) = parse_category_file(content, "src/naming.md").unwrap();
+        let sections = &guidelines[0].sections;
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].heading, "Description");
+        assert!(sections[0].content.is_empty());
+    }
+
+    #[test]
+    fn summary_links_become_chapter_files_in_order() {
+        let content = r#"# Summary
+
+- [About](about.md)
+- [Checklist](./checklist.md)
+- [Naming](naming.md)
+    - [Casing](naming.md#c-case)
+- [Interoperability](interoperability.md)
+- [External links](https://rust-lang.github.io/api-guidelines/external-links.html)
+"#;
+
+        assert_eq!(
+            parse_summary(content),
+            [
+                "src/about.md",
+                "src/checklist.md",
+                "src/naming.md",
+                "src/interoperability.md"
+            ]
+        );
+        assert!(parse_summary("# Summary\n").is_empty());
+    }
+
+    #[test]
+    fn missing_chapters_are_skipped_unless_nothing_parses() {
+        let repo = std::env::temp_dir().join(format!("rust-api-parser-{}", std::process::id()));
+        std::fs::create_dir_all(repo.join("src")).unwrap();
+        std::fs::write(
+            repo.join("src/SUMMARY.md"),
+            "- [Naming](naming.md)\n- [Macros](macros.md)\n",
+        )
+        .unwrap();
+
+        assert!(matches!(parse_guidelines_repo(&repo), Err(AppError::Config(_))));
+
+        std::fs::write(
+            repo.join("src/naming.md"),
+            "# Naming\n\n## Casing conforms to RFC 430 (C-CASE)\n\nUse Rust conventions.\n",
+        )
+        .unwrap();
+        let (guidelines, categories) = parse_guidelines_repo(&repo).unwrap();
+        std::fs::remove_dir_all(&repo).unwrap();
+
+        assert_eq!(guidelines.len(), 1);
+        assert_eq!(guidelines[0].id, "C-CASE");
+        assert_eq!(categories["Naming"].position, 0);
+    }
+
     #[test]
     fn parse_real_repo() {
         let path = std::env::var("RUST_API_GUIDELINES_REPO_PATH")
@@ -1,9 +1,11 @@
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use arrow_array::{ArrayRef, FixedSizeListArray, Float32Array, RecordBatch, StringArray};
 use arrow_schema::{DataType, Field, Schema};
-use tracing::info;
+use sha2::{Digest, Sha256};
+use tracing::{info, warn};
 
 use crate::cache::GuidelineCache;
 use crate::config::Config;
@@ -11,7 +13,7 @@ use crate::error::AppError;
 use crate::model::{Category, Guideline};
 use crate::parser;
 use crate::search::SearchEngine;
-use mcp_common::embedding::Embedder;
+use mcp_common::embedding::{EmbedProgress, Embedder};
 use mcp_common::vectordb::VectorDb;
 
 pub struct UpdateResult {
@@ -20,11 +22,18 @@ pub struct UpdateResult {
     pub guideline_count: usize,
 }
 
+/// Schema metadata key recording what the table's vectors were embedded from.
+const EMBEDDING_SETTINGS_KEY: &str = "embedding_settings";
+
+/// Held for the duration of an update, so only one re-index runs at a time.
+pub type ReindexGuard = tokio::sync::OwnedMutexGuard<()>;
+
 pub struct UpdateService {
     config: Config,
     embedder: Arc<Embedder>,
     vectordb: Arc<VectorDb>,
     cache: Arc<GuidelineCache>,
+    reindex_lock: Arc<tokio::sync::Mutex<()>>,
 }
 
 impl UpdateService {
@@ -39,42 +48,93 @@ impl UpdateService {
             embedder,
             vectordb,
             cache,
+            reindex_lock: Arc::new(tokio::sync::Mutex::new(())),
         }
     }
 
+    /// Take the re-index lock, or `None` if another update holds it. Keep the guard until
+    /// the re-indexed data has been swapped into memory.
+    pub fn try_lock(&self) -> Option<ReindexGuard> {
+        Arc::clone(&self.reindex_lock).try_lock_owned().ok()
+    }
+
     pub fn get_repo_commit(&self) -> Result<String, AppError> {
-        let output = std::process::Command::new("git")
-            .arg("rev-parse")
-            .arg("HEAD")
-            .current_dir(&self.config.repo_path)
-            .output()
-            .map_err(|e| AppError::Git(format!("failed to run git rev-parse: {e}")))?;
-
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(AppError::Git(format!("git rev-parse failed: {stderr}")));
-        }
+        let repo = git2::Repository::discover(&self.config.repo_path).map_err(|e| {
+            AppError::Git(format!(
+                "failed to open repository at {}: {}",
+                self.config.repo_path,
+                e.message()
+            ))
+        })?;
+        let head = repo
+            .head()
+            .and_then(|head| head.peel_to_commit())
+            .map_err(|e| AppError::Git(format!("failed to resolve HEAD: {}", e.message())))?;
+        Ok(head.id().to_string())
+    }
 
-        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    /// Fetch `origin` and hard-reset the checkout to its default branch, discarding any
+    /// local changes. Only runs when `GUIDELINES_AUTO_PULL` is enabled.
+    async fn pull_repo(&self) -> Result<(), AppError> {
+        let repo_path = self.config.repo_path.clone();
+        tokio::task::spawn_blocking(move || pull_origin(&repo_path))
+            .await
+            .map_err(|e| AppError::Git(format!("spawn_blocking join error: {e}")))?
+            .map_err(|e| AppError::Git(format!("pull from origin failed: {}", e.message())))?;
+        info!("pulled guidelines repository from origin");
+        Ok(())
     }
 
-    pub async fn needs_update(&self) -> Result<bool, AppError> {
+    /// Check if an update is needed by comparing the current commit with the cached one.
+    ///
+    /// When `expected_rows` is given, a table holding a different number of rows is treated
+    /// as partial or corrupted and also triggers a re-index. So does a table built with a
+    /// different embedding model. With `GUIDELINES_AUTO_PULL` enabled, the repository is
+    /// pulled from upstream before its HEAD is read; a failed pull is logged and the local
+    /// checkout used as is.
+    pub async fn needs_update(&self, expected_rows: Option<usize>) -> Result<bool, AppError> {
+        // Without network (or an `origin`) the local checkout is still servable, so a
+        // failed pull is logged rather than failing the check, and startup with it.
+        if self.config.auto_pull {
+            if let Err(e) = self.pull_repo().await {
+                warn!(error = %e, "pull from origin failed, using the local checkout");
+            }
+        }
         let current_commit = self.get_repo_commit()?;
         let cached_commit = self.cache.get_repo_commit().await;
 
         match cached_commit {
             Some(cached) if cached == current_commit => {
-                let table_check = self
-                    .vectordb
-                    .get_by_id(SearchEngine::table_name(), "__nonexistent__")
-                    .await;
-                match table_check {
-                    Ok(_) => Ok(false),
+                let rows = match self.vectordb.count_rows(SearchEngine::table_name()).await {
+                    Ok(rows) => rows,
                     Err(_) => {
                         info!("LanceDB table missing, re-index needed");
-                        Ok(true)
+                        return Ok(true);
                     }
+                };
+                if let Some(expected) = expected_rows.filter(|&expected| expected != rows) {
+                    info!(rows, expected, "LanceDB table row count mismatch, re-index needed");
+                    return Ok(true);
+                }
+
+                // A different embedding model makes the stored vectors unusable.
+                let stored_dim = self
+                    .vectordb
+                    .table_embedding_dim(SearchEngine::table_name())
+                    .await?;
+                let model_dim = self.embedder.dimensions();
+                if stored_dim != model_dim {
+                    info!(stored_dim, model_dim, "embedding dimension changed, re-index needed");
+                    return Ok(true);
                 }
+
+                // So does a different model of the same dimension, recorded in the table's
+                // schema metadata.
+                if !self.settings_match().await {
+                    info!("embedding settings changed, re-index needed");
+                    return Ok(true);
+                }
+                Ok(false)
             }
             _ => Ok(true),
         }
@@ -82,6 +142,7 @@ impl UpdateService {
 
     pub async fn full_reindex(
         &self,
+        progress: Option<EmbedProgress<'_>>,
     ) -> Result<(Vec<Guideline>, HashMap<String, Category>, String), AppError> {
         let current_commit = self.get_repo_commit()?;
         info!(commit = %current_commit, "starting full re-index");
@@ -97,64 +158,230 @@ impl UpdateService {
             .iter()
             .map(parser::compose_embedding_text)
             .collect();
+        let hashes: HashMap<String, String> = guidelines
+            .iter()
+            .zip(&embedding_texts)
+            .map(|(g, text)| (g.id.clone(), self.content_hash(g, text)))
+            .collect();
+
+        // Re-embed only what changed when the table still matches the last indexed set.
+        let previous = self.cache.get_content_hashes().await;
+        let incremental = match &previous {
+            Some(previous) => self.table_matches(previous, &hashes, guidelines.len()).await,
+            None => false,
+        };
+        match previous {
+            Some(previous) if incremental => {
+                self.apply_changes(&guidelines, &embedding_texts, &hashes, &previous, progress)
+                    .await?
+            }
+            _ => {
+                self.rebuild_table(&guidelines, &embedding_texts, progress)
+                    .await?
+            }
+        }
+
+        self.cache.invalidate_all().await;
+
+        let mut category_list: Vec<_> = categories.values().cloned().collect();
+        category_list.sort_by(|a, b| a.key.cmp(&b.key));
+        self.cache.warmup(&guidelines, &category_list).await;
+
+        self.cache.set_repo_commit(&current_commit).await;
+        self.cache.set_content_hashes(&hashes).await;
+
+        info!(
+            commit = %current_commit,
+            guidelines = guidelines.len(),
+            "re-index complete"
+        );
+
+        Ok((guidelines, categories, current_commit))
+    }
+
+    /// Hash identifying what a guideline's row was built from: the embedding model, the
+    /// embedded text and the source markdown.
+    fn content_hash(&self, guideline: &Guideline, embedding_text: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.embedder.model_name().as_bytes());
+        hasher.update(b"|");
+        hasher.update(embedding_text.as_bytes());
+        hasher.update(b"|");
+        hasher.update(guideline.raw_markdown.as_bytes());
+        format!("{:x}", hasher.finalize())
+    }
+
+    /// What the table's vectors are built from: the embedding model. Stored in the table's
+    /// schema metadata under [`EMBEDDING_SETTINGS_KEY`].
+    fn embedding_settings(&self) -> String {
+        self.embedder.model_name().to_string()
+    }
+
+    /// Whether the table records the current [`Self::embedding_settings`]. Tables written
+    /// before the settings were recorded never match.
+    async fn settings_match(&self) -> bool {
+        let stored = self
+            .vectordb
+            .table_metadata(SearchEngine::table_name(), EMBEDDING_SETTINGS_KEY)
+            .await
+            .ok()
+            .flatten();
+        stored == Some(self.embedding_settings())
+    }
 
-        info!("generating embeddings for {} guidelines", guidelines.len());
-        let embeddings = self.embedder.embed_documents(&embedding_texts).await?;
+    /// Whether the table can be updated in place: it holds exactly the previously indexed
+    /// rows at the current model's dimension and embedding settings, and the new ids are
+    /// unique.
+    async fn table_matches(
+        &self,
+        previous: &HashMap<String, String>,
+        hashes: &HashMap<String, String>,
+        guideline_count: usize,
+    ) -> bool {
+        let table = SearchEngine::table_name();
+        if hashes.len() != guideline_count {
+            return false;
+        }
+        let rows = self.vectordb.count_rows(table).await.ok();
+        let dim = self.vectordb.table_embedding_dim(table).await.ok();
+        rows == Some(previous.len())
+            && dim == Some(self.embedder.dimensions())
+            && self.settings_match().await
+    }
 
-        if embeddings.len() != guidelines.len() {
+    /// Embed `texts`, checking that the model returned one vector per text.
+    ///
+    /// Logs every further 10% of texts embedded and forwards each batch to `progress`.
+    async fn embed(
+        &self,
+        texts: &[String],
+        progress: Option<EmbedProgress<'_>>,
+    ) -> Result<Vec<Vec<f32>>, AppError> {
+        info!("generating embeddings for {} guidelines", texts.len());
+        let logged_decile = AtomicUsize::new(0);
+        let report = |done: usize, total: usize| {
+            let decile = done * 10 / total.max(1);
+            if logged_decile.swap(decile, Ordering::Relaxed) != decile {
+                info!(done, total, "embedding {}% complete", decile * 10);
+            }
+            if let Some(progress) = progress {
+                progress(done, total);
+            }
+        };
+        let embeddings = self
+            .embedder
+            .embed_documents_with_progress(texts, Some(&report))
+            .await?;
+        if embeddings.len() != texts.len() {
             return Err(AppError::Common(mcp_common::error::CommonError::Embedding(
                 format!(
                     "embedding count mismatch: expected {}, got {}",
-                    guidelines.len(),
+                    texts.len(),
                     embeddings.len()
                 ),
             )));
         }
+        Ok(embeddings)
+    }
 
-        let batch = build_record_batch(&guidelines, &embedding_texts, &embeddings)?;
+    /// Embed every guideline into a fresh table, then build its vector index.
+    async fn rebuild_table(
+        &self,
+        guidelines: &[Guideline],
+        embedding_texts: &[String],
+        progress: Option<EmbedProgress<'_>>,
+    ) -> Result<(), AppError> {
+        let embeddings = self.embed(embedding_texts, progress).await?;
+        let batch = build_record_batch(
+            guidelines,
+            embedding_texts,
+            &embeddings,
+            self.embedder.dimensions(),
+            &self.embedding_settings(),
+        )?;
         let schema = batch.schema();
-
         self.vectordb
             .create_or_replace_table(SearchEngine::table_name(), schema, vec![batch])
             .await?;
+        self.build_index().await;
+        Ok(())
+    }
 
-        self.cache.invalidate_all().await;
-
-        for g in &guidelines {
-            self.cache.set_guideline(g).await;
-        }
-
-        let mut category_list: Vec<_> = categories.values().cloned().collect();
-        category_list.sort_by(|a, b| a.key.cmp(&b.key));
-        self.cache.set_categories(&category_list).await;
-
-        for key in categories.keys() {
-            let mut ids: Vec<String> = guidelines
-                .iter()
-                .filter(|g| &g.category == key)
-                .map(|g| g.id.clone())
-                .collect();
-            ids.sort();
-            self.cache.set_category_guideline_ids(key, &ids).await;
+    /// Retrain the vector index over the table's current rows.
+    ///
+    /// Best-effort: without the index searches still work, just by full scan.
+    async fn build_index(&self) {
+        if let Err(e) = self
+            .vectordb
+            .create_index(SearchEngine::table_name(), "embedding", SearchEngine::distance_type())
+            .await
+        {
+            warn!(error = %e, "vector index creation failed, searches will scan the table");
         }
+    }
 
-        self.cache.set_repo_commit(&current_commit).await;
-
+    /// Re-embed and upsert guidelines whose hash changed, and delete rows for guidelines
+    /// that no longer exist.
+    ///
+    /// The index is rebuilt after any change: rows upserted since it was trained are only
+    /// found by a flat scan of the unindexed part, and its partitions drift from the data,
+    /// so recall would fall behind a full re-index as updates accumulate.
+    async fn apply_changes(
+        &self,
+        guidelines: &[Guideline],
+        embedding_texts: &[String],
+        hashes: &HashMap<String, String>,
+        previous: &HashMap<String, String>,
+        progress: Option<EmbedProgress<'_>>,
+    ) -> Result<(), AppError> {
+        let table = SearchEngine::table_name();
+        let changed: Vec<usize> = (0..guidelines.len())
+            .filter(|&i| previous.get(&guidelines[i].id) != hashes.get(&guidelines[i].id))
+            .collect();
+        let removed: Vec<&String> =
+            previous.keys().filter(|id| !hashes.contains_key(*id)).collect();
         info!(
-            commit = %current_commit,
-            guidelines = guidelines.len(),
-            "re-index complete"
+            changed = changed.len(),
+            removed = removed.len(),
+            unchanged = guidelines.len() - changed.len(),
+            "incremental re-index"
         );
 
-        Ok((guidelines, categories, current_commit))
+        if !changed.is_empty() {
+            let changed_guidelines: Vec<Guideline> =
+                changed.iter().map(|&i| guidelines[i].clone()).collect();
+            let texts: Vec<String> =
+                changed.iter().map(|&i| embedding_texts[i].clone()).collect();
+            let embeddings = self.embed(&texts, progress).await?;
+            let batch = build_record_batch(
+                &changed_guidelines,
+                &texts,
+                &embeddings,
+                self.embedder.dimensions(),
+                &self.embedding_settings(),
+            )?;
+            let schema = batch.schema();
+            self.vectordb.upsert_batch(table, schema, vec![batch]).await?;
+        }
+        let modified = !changed.is_empty() || !removed.is_empty();
+        for id in removed {
+            self.vectordb.delete_by_id(table, id).await?;
+        }
+        if modified {
+            self.build_index().await;
+        }
+        Ok(())
     }
 
     pub async fn update(
         &self,
+        _lock: &ReindexGuard,
+        expected_rows: Option<usize>,
+        progress: Option<EmbedProgress<'_>>,
     ) -> Result<(UpdateResult, Option<(Vec<Guideline>, HashMap<String, Category>)>), AppError> {
         let current_commit = self.get_repo_commit()?;
 
-        if !self.needs_update().await? {
+        if !self.needs_update(expected_rows).await? {
             info!(commit = %current_commit, "guidelines up to date, skipping re-index");
             return Ok((
                 UpdateResult {
@@ -166,7 +393,7 @@ impl UpdateService {
             ));
         }
 
-        let (guidelines, categories, commit) = self.full_reindex().await?;
+        let (guidelines, categories, commit) = self.full_reindex(progress).await?;
         let count = guidelines.len();
 
         Ok((
@@ -180,12 +407,37 @@ impl UpdateService {
     }
 }
 
+/// Fetch the default branch of `origin` into the repository containing `repo_path`, then
+/// hard-reset the checkout to it. The branch is asked of the remote instead of read from
+/// `origin/HEAD`, which checkouts not made by `git clone` lack.
+fn pull_origin(repo_path: &str) -> Result<(), git2::Error> {
+    let repo = git2::Repository::discover(repo_path)?;
+    let mut remote = repo.find_remote("origin")?;
+    remote.connect(git2::Direction::Fetch)?;
+    let head = remote.default_branch()?;
+    remote.disconnect()?;
+    let branch = head
+        .as_str()
+        .and_then(|head| head.strip_prefix("refs/heads/"))
+        .ok_or_else(|| git2::Error::from_str("origin reports no default branch"))?
+        .to_string();
+    let tracking = format!("refs/remotes/origin/{branch}");
+    remote.fetch(&[format!("+refs/heads/{branch}:{tracking}")], None, None)?;
+    let target = repo.find_reference(&tracking)?.peel_to_commit()?;
+    repo.reset(target.as_object(), git2::ResetType::Hard, None)
+}
+
+/// Build an Arrow RecordBatch from parsed guidelines and their embeddings.
+///
+/// `embedding_settings` is recorded in the schema metadata under [`EMBEDDING_SETTINGS_KEY`].
 fn build_record_batch(
     guidelines: &[Guideline],
     texts: &[String],
     embeddings: &[Vec<f32>],
+    embedding_dim: usize,
+    embedding_settings: &str,
 ) -> Result<RecordBatch, AppError> {
-    let embedding_dim = 768i32;
+    let embedding_dim = embedding_dim as i32;
 
     let ids: Vec<&str> = guidelines.iter().map(|g| g.id.as_str()).collect();
     let titles: Vec<&str> = guidelines.iter().map(|g| g.title.as_str()).collect();
@@ -223,7 +475,11 @@ fn build_record_batch(
             DataType::FixedSizeList(Arc::new(Field::new("item", DataType::Float32, true)), embedding_dim),
             false,
         ),
-    ]));
+    ])
+    .with_metadata(HashMap::from([(
+        EMBEDDING_SETTINGS_KEY.to_string(),
+        embedding_settings.to_string(),
+    )])));
 
     RecordBatch::try_new(
         schema,
@@ -241,3 +497,33 @@ fn build_record_batch(
         )))
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_batches_carry_the_embedding_settings() {
+        let guideline = Guideline {
+            id: "C-CASE".to_string(),
+            anchor: "c-case".to_string(),
+            title: "Casing conforms to RFC 430".to_string(),
+            category: "naming".to_string(),
+            source_file: "naming.md".to_string(),
+            raw_markdown: String::new(),
+            sections: Vec::new(),
+        };
+        let batch = build_record_batch(
+            &[guideline],
+            &["text".to_string()],
+            &[vec![0.5, 0.5]],
+            2,
+            "model-a",
+        )
+        .unwrap();
+        assert_eq!(
+            batch.schema().metadata().get(EMBEDDING_SETTINGS_KEY).map(String::as_str),
+            Some("model-a")
+        );
+    }
+}